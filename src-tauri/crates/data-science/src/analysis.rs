@@ -1,186 +1,1184 @@
-use crate::parser::analyze_csv_columns;
+use crate::error::DataScienceError;
+use crate::parser::{
+    analyze_columns, collect_columns_with_progress, collect_sampled_columns_with_progress, find_key_columns,
+    DEFAULT_SAMPLE_SIZE,
+};
+use crate::storage::{collect_loaded_columns, LoadedColumns};
 use crate::types::*;
+use crate::utils::{
+    check_cancelled, get_default_sample_seed, is_null_token, numeric_stays_text, parse_formatted_number,
+    parse_hex_integer,
+};
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::AtomicBool;
 
+// Placeholder for the ambiguous `NN/NN/YYYY` layout, resolved per column in `infer_date_format`
+const SLASH_DATE: &str = "slash";
+const EPOCH_SECONDS: &str = "%s";
+// Epoch seconds from 2001-09-09 (the first ten-digit value) up to 2100-01-01
+const EPOCH_SECONDS_RANGE: std::ops::RangeInclusive<i64> = 1_000_000_000..=4_102_444_800;
+// Header words that mark a column of ten-digit integers as epoch seconds rather than IDs
+const EPOCH_HEADER_WORDS: [&str; 6] = ["time", "date", "epoch", "created", "updated", "modified"];
+// Correlations at least this strong (in absolute value) are called out in the insights
+const STRONG_CORRELATION: f64 = 0.7;
+// Two-tailed critical t values at the 5% level for 1..=30 degrees of freedom
+const T_CRITICAL_05: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160, 2.145, 2.131, 2.120,
+    2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+lazy_static::lazy_static! {
+    // Ordered (pattern, chrono format) pairs; the first match wins
+    static ref DATE_PATTERNS: Vec<(Regex, &'static str)> = vec![
+        (Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap(), "%Y-%m-%d"),
+        (
+            Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})$").unwrap(),
+            "%+",
+        ),
+        (Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?$").unwrap(), "%Y-%m-%dT%H:%M:%S%.f"),
+        (Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(\.\d+)?$").unwrap(), "%Y-%m-%d %H:%M:%S%.f"),
+        (Regex::new(r"^\d{4}/\d{2}/\d{2}$").unwrap(), "%Y/%m/%d"),
+        (Regex::new(r"^\d{1,2}/\d{1,2}/\d{4}$").unwrap(), SLASH_DATE),
+        (Regex::new(r"^[A-Za-z]{3} \d{1,2}, \d{4}$").unwrap(), "%b %d, %Y"),
+    ];
+    static ref EPOCH_PATTERN: Regex = Regex::new(r"^\d{10}$").unwrap();
+    // Named text formats checked by `infer_value_pattern`, in order
+    static ref VALUE_PATTERNS: Vec<(&'static str, Regex)> = vec![
+        ("email", Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap()),
+        ("url", Regex::new(r"^(?i)https?://[^\s]+$").unwrap()),
+        (
+            "uuid",
+            Regex::new(r"^(?i)[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap(),
+        ),
+        ("ipv4", Regex::new(r"^(25[0-5]|2[0-4]\d|1?\d?\d)(\.(25[0-5]|2[0-4]\d|1?\d?\d)){3}$").unwrap()),
+    ];
+}
+
+fn detect_date_pattern(value: &str) -> Option<&'static str> {
+    DATE_PATTERNS
+        .iter()
+        .find(|(pattern, _)| pattern.is_match(value))
+        .map(|(_, format)| *format)
+}
+
+/// True when a single value is in one of the recognized date formats.
+pub(crate) fn is_date_value(value: &str) -> bool {
+    detect_date_pattern(value.trim()).is_some()
+}
+
+/// True when a header names a time, e.g. "created_at", "timestamp", or "ts".
+fn is_epoch_header(header: &str) -> bool {
+    let header = header.to_ascii_lowercase();
+    EPOCH_HEADER_WORDS.iter().any(|word| header.contains(word))
+        || header
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|part| part == "ts" || part == "at")
+}
+
+/// True when a column holds epoch seconds: its header names a time and every
+/// value is a ten-digit integer inside `EPOCH_SECONDS_RANGE`.
+///
+/// A bare column of ten-digit integers is as likely to be phone numbers or IDs,
+/// so the values alone are never enough.
+pub(crate) fn is_epoch_column(header: &str, values: &[String]) -> bool {
+    if !is_epoch_header(header) {
+        return false;
+    }
+    let mut non_empty = values.iter().map(|v| v.trim()).filter(|v| !is_null_token(v)).peekable();
+    non_empty.peek().is_some()
+        && non_empty.all(|v| {
+            EPOCH_PATTERN.is_match(v)
+                && v.parse::<i64>()
+                    .is_ok_and(|seconds| EPOCH_SECONDS_RANGE.contains(&seconds))
+        })
+}
+
+/// `infer_date_format` for a named column, reporting `%s` when `is_epoch_column` holds.
+pub fn infer_column_date_format(header: &str, values: &[String]) -> Option<String> {
+    if is_epoch_column(header, values) {
+        return Some(EPOCH_SECONDS.to_string());
+    }
+    infer_date_format(values)
+}
+
+/// Detect the `chrono` format string shared by most values in a column.
+///
+/// `NN/NN/YYYY` dates are read as `%m/%d/%Y` unless some value has a first
+/// component above 12, in which case the column is `%d/%m/%Y`. Ten-digit
+/// integers are never read as epoch seconds here; that needs the header, see
+/// `infer_column_date_format`.
+pub fn infer_date_format(values: &[String]) -> Option<String> {
+    let non_empty: Vec<&str> = values.iter().map(|v| v.trim()).filter(|v| !is_null_token(v)).collect();
+    if non_empty.is_empty() {
+        return None;
+    }
+
+    let mut format_counts: HashMap<&'static str, usize> = HashMap::new();
+    for value in &non_empty {
+        if let Some(format) = detect_date_pattern(value) {
+            *format_counts.entry(format).or_insert(0) += 1;
+        }
+    }
+
+    let (format, count) = format_counts.into_iter().max_by_key(|(_, count)| *count)?;
+    if count * 2 <= non_empty.len() {
+        return None;
+    }
+
+    if format == SLASH_DATE {
+        let day_first = non_empty
+            .iter()
+            .filter(|v| detect_date_pattern(v) == Some(SLASH_DATE))
+            .any(|v| {
+                v.split('/')
+                    .next()
+                    .and_then(|d| d.parse::<u32>().ok())
+                    .is_some_and(|d| d > 12)
+            });
+        return Some(if day_first { "%d/%m/%Y" } else { "%m/%d/%Y" }.to_string());
+    }
+
+    Some(format.to_string())
+}
+
+/// Name the text format ("email", "url", "uuid", or "ipv4") shared by every non-null value, if any.
+pub fn infer_value_pattern(values: &[String]) -> Option<&'static str> {
+    let mut non_empty = values.iter().map(|v| v.trim()).filter(|v| !is_null_token(v)).peekable();
+    let first = *non_empty.peek()?;
+    let (name, pattern) = VALUE_PATTERNS.iter().find(|(_, pattern)| pattern.is_match(first))?;
+    non_empty.all(|v| pattern.is_match(v)).then_some(*name)
+}
+
+/// Classify a single non-null value as "boolean", "number", "date", or "string".
+pub fn classify_value(value: &str) -> &'static str {
+    let trimmed = value.trim();
+    if trimmed.eq_ignore_ascii_case("true") || trimmed.eq_ignore_ascii_case("false") {
+        "boolean"
+    } else if numeric_stays_text(trimmed) {
+        "string"
+    } else if trimmed.parse::<f64>().is_ok() || parse_formatted_number(trimmed).is_some() {
+        "number"
+    } else if detect_date_pattern(trimmed).is_some() {
+        "date"
+    } else {
+        "string"
+    }
+}
+
+/// The majority type of a column's values; ten-digit integers stay "number" without a header.
 pub fn infer_data_type(values: &[String]) -> String {
+    infer_column_data_type("", values)
+}
+
+/// `infer_data_type` for a named column, reporting "date" when `is_epoch_column` holds.
+pub fn infer_column_data_type(header: &str, values: &[String]) -> String {
     if values.is_empty() {
         return "unknown".to_string();
     }
 
-    let mut numeric_count = 0;
-    let mut date_count = 0;
-    let mut boolean_count = 0;
-
+    let mut counts = TypeCounts::default();
     for value in values {
+        counts.add(value);
+    }
+    counts.data_type(|| is_epoch_column(header, values))
+}
+
+/// How many of a column's present values look like each type, for `infer_data_type`.
+#[derive(Default)]
+pub(crate) struct TypeCounts {
+    present: usize,
+    numeric: usize,
+    date: usize,
+    boolean: usize,
+    hex: usize, // "0x" hex values, counted only when `recognize_hex` is on
+}
+
+impl TypeCounts {
+    pub(crate) fn add(&mut self, value: &str) {
         let trimmed = value.trim();
-        if trimmed.is_empty() {
-            continue;
+        if is_null_token(trimmed) {
+            return;
         }
+        self.present += 1;
 
-        // Check for boolean
-        if trimmed.to_lowercase() == "true" || trimmed.to_lowercase() == "false" {
-            boolean_count += 1;
-            continue;
+        match classify_value(trimmed) {
+            "boolean" => self.boolean += 1,
+            "number" => self.numeric += 1,
+            "date" => self.date += 1,
+            _ if parse_hex_integer(trimmed).is_some() => self.hex += 1,
+            _ => {}
         }
+    }
 
-        // Check for numeric
-        if trimmed.parse::<f64>().is_ok() {
-            numeric_count += 1;
-            continue;
+    /// The column's type; `is_epoch` reports whether a mostly numeric column holds epoch seconds.
+    pub(crate) fn data_type(&self, is_epoch: impl FnOnce() -> bool) -> String {
+        // Missing values don't count towards (or against) any type
+        let total_values = self.present;
+        if total_values > 0 && self.hex == total_values {
+            "number".to_string()
+        } else if self.numeric > total_values * 3 / 4 {
+            if is_epoch() {
+                "date".to_string()
+            } else {
+                "number".to_string()
+            }
+        } else if self.boolean > total_values / 2 {
+            "boolean".to_string()
+        } else if self.date > total_values / 4 {
+            "date".to_string()
+        } else {
+            "string".to_string()
         }
+    }
+}
+
+/// Run one analysis over a CSV file.
+///
+/// `cancel` is checked while the file is read and between the sub-reports of
+/// `full_report`; once set, the analysis fails with `CANCELLED_ERROR`. With
+/// `options.sample` only the sampled rows are analyzed and the summary gets
+/// `sampled: true` plus a `sample` object describing the sample.
+pub async fn perform_data_science(
+    file_path: String,
+    analysis_type: String,
+    options: &AnalysisOptions,
+    cancel: &AtomicBool,
+) -> Result<AnalysisResult, DataScienceError> {
+    perform_data_science_with_progress(file_path, analysis_type, options, cancel, |_| {}).await
+}
 
-        // Check for date (simple pattern)
-        if Regex::new(r"^\d{4}-\d{2}-\d{2}").unwrap().is_match(trimmed)
-            || Regex::new(r"^\d{2}/\d{2}/\d{4}").unwrap().is_match(trimmed)
-        {
-            date_count += 1;
+/// `perform_data_science`, reporting progress as the file is read and as each analysis starts.
+///
+/// While reading, `on_progress` gets stage `reading` every
+/// `PROGRESS_INTERVAL_ROWS` rows and once the file is read. Then it gets the
+/// name of the analysis, or of each `full_report` sub-report, before it runs.
+/// The callback runs on the analyzing thread, so it should return quickly.
+pub async fn perform_data_science_with_progress(
+    file_path: String,
+    analysis_type: String,
+    options: &AnalysisOptions,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(AnalysisProgress),
+) -> Result<AnalysisResult, DataScienceError> {
+    let mut reading = |rows_processed| {
+        on_progress(AnalysisProgress {
+            stage: "reading".to_string(),
+            rows_processed,
+        })
+    };
+
+    // Read the file once; every analysis works from the same parsed columns
+    let (headers, columns, sample_info) = match &options.sample {
+        Some(sample) => {
+            // Pin the seed first so the one reported is the one used
+            let sample = &SampleSpec {
+                seed: Some(sample.seed.unwrap_or_else(get_default_sample_seed)),
+                ..sample.clone()
+            };
+            let outcome = collect_sampled_columns_with_progress(
+                file_path,
+                &CsvParseOptions::default(),
+                sample,
+                cancel,
+                &mut reading,
+            )?;
+            let sample_rows = outcome.columns.first().map_or(0, Vec::len);
+            let info = SampleInfo {
+                mode: sample.mode.clone(),
+                sample_rows,
+                rows_scanned: outcome.rows_scanned,
+                seed: sample.seed.filter(|_| sample.mode == "random"),
+            };
+            (outcome.headers, outcome.columns, Some(info))
+        }
+        None => {
+            let (headers, columns) =
+                collect_columns_with_progress(file_path, &CsvParseOptions::default(), cancel, &mut reading)?;
+            (headers, columns, None)
         }
+    };
+
+    let mut result = run_analysis(&headers, &columns, &analysis_type, options, cancel, &mut on_progress)?;
+
+    // Flag sampled results so their statistics aren't read as exact
+    if let Some(info) = sample_info {
+        result.insights.insert(
+            0,
+            format!(
+                "Based on a sample of {} rows (out of {} rows read); figures are estimates",
+                info.sample_rows, info.rows_scanned
+            ),
+        );
+        let scope = result.report.scope_mut();
+        scope.sampled = Some(true);
+        scope.sample = Some(info);
     }
 
-    let total_values = values.len();
-    if numeric_count > total_values * 3 / 4 {
-        "number".to_string()
-    } else if boolean_count > total_values / 2 {
-        "boolean".to_string()
-    } else if date_count > total_values / 4 {
-        "date".to_string()
-    } else {
-        "string".to_string()
+    Ok(result)
+}
+
+/// Run an analysis on a file already loaded into the store, instead of re-reading it from disk.
+///
+/// Only rows passing the filters of the last `query_csv_data` call are
+/// analyzed, so results match what the grid shows; the summary then includes
+/// `filters_applied`. Sampling isn't supported here since the data is already in memory.
+pub async fn analyze_loaded_data(
+    file_id: String,
+    analysis_type: String,
+    options: &AnalysisOptions,
+    cancel: &AtomicBool,
+) -> Result<AnalysisResult, DataScienceError> {
+    analyze_loaded_data_with_progress(file_id, analysis_type, options, cancel, |_| {}).await
+}
+
+/// `analyze_loaded_data`, passing `on_progress` the name of the analysis, or of
+/// each `full_report` sub-report, before it runs.
+pub async fn analyze_loaded_data_with_progress(
+    file_id: String,
+    analysis_type: String,
+    options: &AnalysisOptions,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(AnalysisProgress),
+) -> Result<AnalysisResult, DataScienceError> {
+    if options.sample.is_some() {
+        return Err(DataScienceError::InvalidArgument(
+            "Sampling isn't supported for loaded data".to_string(),
+        ));
+    }
+
+    let LoadedColumns {
+        headers,
+        columns,
+        filter_count,
+    } = collect_loaded_columns(&file_id)?;
+    let mut result = run_analysis(&headers, &columns, &analysis_type, options, cancel, &mut on_progress)?;
+
+    if filter_count > 0 {
+        result.report.scope_mut().filters_applied = Some(filter_count);
+        let rows = columns.first().map_or(0, Vec::len);
+        result.insights.insert(
+            0,
+            format!("Based on the {} rows matching {} active filter(s)", rows, filter_count),
+        );
     }
+
+    Ok(result)
 }
 
-pub async fn perform_data_science(file_path: String, analysis_type: String) -> Result<AnalysisResult, String> {
-    match analysis_type.as_str() {
-        "summary" => perform_summary_analysis(&file_path),
-        "correlation" => perform_correlation_analysis(&file_path),
-        "distribution" => perform_distribution_analysis(&file_path),
-        "outliers" => perform_outlier_analysis(&file_path),
-        "patterns" => perform_pattern_analysis(&file_path),
-        _ => Err(format!("Unknown analysis type: {}", analysis_type)),
+fn run_analysis(
+    headers: &[String],
+    columns: &[Vec<String>],
+    analysis_type: &str,
+    options: &AnalysisOptions,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(AnalysisProgress),
+) -> Result<AnalysisResult, DataScienceError> {
+    // `full_report` reports each of its sub-reports instead
+    if matches!(
+        analysis_type,
+        "summary" | "correlation" | "distribution" | "outliers" | "patterns"
+    ) {
+        report_stage(on_progress, analysis_type, columns);
     }
+    match analysis_type {
+        "summary" => perform_summary_analysis(headers, columns),
+        "correlation" => perform_correlation_analysis(headers, columns, options),
+        "distribution" => perform_distribution_analysis(headers, columns),
+        "outliers" => perform_outlier_analysis(headers, columns),
+        "patterns" => perform_pattern_analysis(headers, columns),
+        "full_report" => perform_full_report(headers, columns, options, cancel, on_progress),
+        _ => Err(DataScienceError::InvalidArgument(format!(
+            "Unknown analysis type: {}",
+            analysis_type
+        ))),
+    }
+}
+
+// Tell `on_progress` that `stage` is about to run over every row of `columns`
+fn report_stage(on_progress: &mut dyn FnMut(AnalysisProgress), stage: &str, columns: &[Vec<String>]) {
+    on_progress(AnalysisProgress {
+        stage: stage.to_string(),
+        rows_processed: columns.first().map_or(0, Vec::len),
+    });
+}
+
+/// Run every sub-analysis over the same parsed columns and nest each report under its own key.
+fn perform_full_report(
+    headers: &[String],
+    columns: &[Vec<String>],
+    options: &AnalysisOptions,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(AnalysisProgress),
+) -> Result<AnalysisResult, DataScienceError> {
+    let mut insights: Vec<String> = Vec::new();
+    let mut merge_insights = |new: Vec<String>| {
+        for insight in new {
+            if !insights.contains(&insight) {
+                insights.push(insight);
+            }
+        }
+    };
+
+    check_cancelled(cancel)?;
+    // Every sub-report works from the same column analyses
+    let analyses = analyze_columns(headers, columns, DEFAULT_SAMPLE_SIZE);
+    check_cancelled(cancel)?;
+    report_stage(on_progress, "summary", columns);
+    let (summary, new) = summary_report(headers, columns, &analyses);
+    merge_insights(new);
+    check_cancelled(cancel)?;
+    report_stage(on_progress, "correlation", columns);
+    let (correlation, new) = correlation_report(columns, &analyses, options)?;
+    merge_insights(new);
+    check_cancelled(cancel)?;
+    report_stage(on_progress, "distribution", columns);
+    let (distribution, new) = distribution_report(&analyses);
+    merge_insights(new);
+    check_cancelled(cancel)?;
+    report_stage(on_progress, "outliers", columns);
+    let (outliers, new) = outlier_report(&analyses);
+    merge_insights(new);
+    check_cancelled(cancel)?;
+    report_stage(on_progress, "patterns", columns);
+    let (patterns, new) = pattern_report(&analyses);
+    merge_insights(new);
+
+    let report = FullReport {
+        summary,
+        correlation,
+        distribution,
+        outliers,
+        patterns,
+        scope: ReportScope::default(),
+    };
+    Ok(AnalysisResult {
+        report: AnalysisReport::FullReport(Box::new(report)),
+        insights,
+    })
+}
+
+fn perform_summary_analysis(headers: &[String], columns: &[Vec<String>]) -> Result<AnalysisResult, DataScienceError> {
+    let analyses = analyze_columns(headers, columns, DEFAULT_SAMPLE_SIZE);
+    let (report, insights) = summary_report(headers, columns, &analyses);
+    Ok(AnalysisResult {
+        report: AnalysisReport::Summary(report),
+        insights,
+    })
 }
 
-fn perform_summary_analysis(file_path: &str) -> Result<AnalysisResult, String> {
-    let analyses = analyze_csv_columns(file_path.to_string())?;
+fn summary_report(
+    headers: &[String],
+    columns: &[Vec<String>],
+    analyses: &[ColumnAnalysis],
+) -> (SummaryReport, Vec<String>) {
+    let total_rows = columns.first().map_or(0, Vec::len);
 
     let total_columns = analyses.len();
-    let total_rows = analyses
-        .first()
-        .map(|a| a.unique_count + (a.null_percentage * a.unique_count as f64 / (100.0 - a.null_percentage)) as usize)
-        .unwrap_or(0);
 
-    let data_types: HashMap<String, usize> =
+    let data_types: BTreeMap<String, usize> =
         analyses
             .iter()
             .map(|a| a.data_type.clone())
-            .fold(HashMap::new(), |mut map, dt| {
+            .fold(BTreeMap::new(), |mut map, dt| {
                 *map.entry(dt).or_insert(0) += 1;
                 map
             });
 
-    let summary = serde_json::json!({
-        "total_rows": total_rows,
-        "total_columns": total_columns,
-        "data_types": data_types,
-        "columns": analyses.iter().map(|a| {
-            serde_json::json!({
-                "name": a.name,
-                "type": a.data_type,
-                "null_percentage": a.null_percentage,
-                "unique_count": a.unique_count
-            })
-        }).collect::<Vec<_>>()
-    });
+    let key_columns = find_key_columns(headers, columns);
 
-    let insights = vec![
+    let mut insights = vec![
         format!("Dataset contains {} rows and {} columns", total_rows, total_columns),
         format!("Data types found: {:?}", data_types),
     ];
+    if let Some(key) = key_columns.first() {
+        insights.push(format!("Rows are uniquely identified by: {}", key.join(" + ")));
+    }
+    let constant: Vec<&str> = analyses
+        .iter()
+        .filter(|a| a.is_constant)
+        .map(|a| a.name.as_str())
+        .collect();
+    if !constant.is_empty() {
+        insights.push(format!("Constant columns: {}", constant.join(", ")));
+    }
+
+    let report = SummaryReport {
+        total_rows,
+        total_columns,
+        data_types,
+        key_columns,
+        columns: analyses
+            .iter()
+            .map(|a| SummaryColumn {
+                name: a.name.clone(),
+                data_type: a.data_type.clone(),
+                null_percentage: a.null_percentage,
+                unique_count: a.unique_count,
+                is_constant: a.is_constant,
+                dominant_value_ratio: a.dominant_value_ratio,
+            })
+            .collect(),
+        scope: ReportScope::default(),
+    };
+    (report, insights)
+}
 
+/// Pairwise correlations between the numeric columns.
+///
+/// Pearson (the default) measures linear association and suits roughly normal
+/// data without heavy outliers. Spearman correlates ranks instead, so it captures
+/// any monotonic relationship (e.g. exponential growth) and is robust to outliers
+/// and skew; prefer it for ordinal or non-normal data. Each pair uses the rows
+/// where both values are present.
+fn perform_correlation_analysis(
+    headers: &[String],
+    columns: &[Vec<String>],
+    options: &AnalysisOptions,
+) -> Result<AnalysisResult, DataScienceError> {
+    let analyses = analyze_columns(headers, columns, DEFAULT_SAMPLE_SIZE);
+    let (report, insights) = correlation_report(columns, &analyses, options)?;
     Ok(AnalysisResult {
-        analysis_type: "summary".to_string(),
-        summary,
+        report: AnalysisReport::Correlation(report),
         insights,
     })
 }
 
-fn perform_correlation_analysis(file_path: &str) -> Result<AnalysisResult, String> {
-    let analyses = analyze_csv_columns(file_path.to_string())?;
+fn correlation_report(
+    columns: &[Vec<String>],
+    analyses: &[ColumnAnalysis],
+    options: &AnalysisOptions,
+) -> Result<(CorrelationReport, Vec<String>), DataScienceError> {
+    let method = options.correlation_method.as_deref().unwrap_or("pearson");
+    if method != "pearson" && method != "spearman" {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Unknown correlation method: {}",
+            method
+        )));
+    }
 
-    let numeric_columns: Vec<&ColumnAnalysis> = analyses.iter().filter(|a| a.data_type == "number").collect();
+    let numeric: Vec<(&String, Vec<Option<f64>>)> = analyses
+        .iter()
+        .zip(columns)
+        .filter(|(a, _)| a.data_type == "number")
+        .map(|(a, values)| (&a.name, values.iter().map(|v| parse_numeric(v)).collect()))
+        .collect();
 
-    let summary = serde_json::json!({
-        "numeric_columns_count": numeric_columns.len(),
-        "correlation_available": numeric_columns.len() >= 2,
-        "columns": numeric_columns.iter().map(|a| a.name.clone()).collect::<Vec<_>>()
-    });
+    let mut pairs = Vec::new();
+    let mut insights = Vec::new();
+    for (i, (name_a, values_a)) in numeric.iter().enumerate() {
+        for (name_b, values_b) in &numeric[i + 1..] {
+            let (xs, ys): (Vec<f64>, Vec<f64>) = values_a
+                .iter()
+                .zip(values_b)
+                .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+                .unzip();
 
-    let insights = if numeric_columns.len() >= 2 {
-        vec!["Correlation analysis available for numeric columns".to_string()]
-    } else {
-        vec!["Need at least 2 numeric columns for correlation analysis".to_string()]
+            let coefficient = if method == "spearman" {
+                pearson(&average_ranks(&xs), &average_ranks(&ys))
+            } else {
+                pearson(&xs, &ys)
+            };
+
+            pairs.push(CorrelationPair {
+                column_a: (*name_a).clone(),
+                column_b: (*name_b).clone(),
+                coefficient,
+                n: xs.len(),
+                significant: options
+                    .include_significance
+                    .then(|| coefficient.is_some_and(|r| is_significant(r, xs.len()))),
+            });
+
+            if let Some(r) = coefficient.filter(|r| r.abs() >= STRONG_CORRELATION) {
+                let direction = if r > 0.0 { "positive" } else { "negative" };
+                insights.push(format!(
+                    "Strong {} correlation between {} and {} ({:.2})",
+                    direction, name_a, name_b, r
+                ));
+            }
+        }
+    }
+
+    if numeric.len() < 2 {
+        insights.push("Need at least 2 numeric columns for correlation analysis".to_string());
+    } else if insights.is_empty() {
+        insights.push("No strong correlations between numeric columns".to_string());
+    }
+
+    let report = CorrelationReport {
+        method: method.to_string(),
+        numeric_columns_count: numeric.len(),
+        correlation_available: numeric.len() >= 2,
+        columns: numeric.iter().map(|(name, _)| (*name).clone()).collect(),
+        pairs,
+        scope: ReportScope::default(),
     };
+    Ok((report, insights))
+}
+
+pub(crate) fn parse_numeric(value: &str) -> Option<f64> {
+    let trimmed = value.trim();
+    if is_null_token(trimmed) {
+        return None;
+    }
+    trimmed
+        .parse::<f64>()
+        .ok()
+        .or_else(|| parse_formatted_number(trimmed))
+        .filter(|v| v.is_finite())
+}
+
+/// Pearson correlation coefficient; `None` with fewer than two points or a constant series.
+pub fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len().min(ys.len());
+    if n < 2 {
+        return None;
+    }
+
+    let mean_x = xs[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = ys[..n].iter().sum::<f64>() / n as f64;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// 1-based ranks of `values`; tied values share the average of their ranks.
+pub fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        // Positions start..end hold ranks start+1..=end
+        let rank = (start + 1 + end) as f64 / 2.0;
+        for &idx in &order[start..end] {
+            ranks[idx] = rank;
+        }
+        start = end;
+    }
+
+    ranks
+}
 
+// t = r * sqrt((n - 2) / (1 - r^2)) against the two-tailed 5% critical value;
+// beyond 30 degrees of freedom the normal approximation (1.96) is used
+fn is_significant(r: f64, n: usize) -> bool {
+    if n < 3 {
+        return false;
+    }
+    let df = n - 2;
+    let critical = T_CRITICAL_05.get(df - 1).copied().unwrap_or(1.96);
+    if r.abs() >= 1.0 {
+        return true;
+    }
+    let t = r * (df as f64 / (1.0 - r * r)).sqrt();
+    t.abs() > critical
+}
+
+fn perform_distribution_analysis(
+    headers: &[String],
+    columns: &[Vec<String>],
+) -> Result<AnalysisResult, DataScienceError> {
+    let (report, insights) = distribution_report(&analyze_columns(headers, columns, DEFAULT_SAMPLE_SIZE));
     Ok(AnalysisResult {
-        analysis_type: "correlation".to_string(),
-        summary,
+        report: AnalysisReport::Distribution(report),
         insights,
     })
 }
 
-fn perform_distribution_analysis(file_path: &str) -> Result<AnalysisResult, String> {
-    let analyses = analyze_csv_columns(file_path.to_string())?;
-
-    let summary = serde_json::json!({
-        "columns_analyzed": analyses.len(),
-        "distribution_data": analyses.iter().map(|a| {
-            serde_json::json!({
-                "column": a.name,
-                "type": a.data_type,
-                "unique_values": a.unique_count,
-                "null_percentage": a.null_percentage
+fn distribution_report(analyses: &[ColumnAnalysis]) -> (DistributionReport, Vec<String>) {
+    let report = DistributionReport {
+        columns_analyzed: analyses.len(),
+        distribution_data: analyses
+            .iter()
+            .map(|a| DistributionColumn {
+                column: a.name.clone(),
+                data_type: a.data_type.clone(),
+                unique_values: a.unique_count,
+                null_percentage: a.null_percentage,
             })
-        }).collect::<Vec<_>>()
-    });
+            .collect(),
+        scope: ReportScope::default(),
+    };
+    (report, vec!["Distribution analysis completed".to_string()])
+}
 
+fn perform_outlier_analysis(headers: &[String], columns: &[Vec<String>]) -> Result<AnalysisResult, DataScienceError> {
+    let (report, insights) = outlier_report(&analyze_columns(headers, columns, DEFAULT_SAMPLE_SIZE));
     Ok(AnalysisResult {
-        analysis_type: "distribution".to_string(),
-        summary,
-        insights: vec!["Distribution analysis completed".to_string()],
+        report: AnalysisReport::Outliers(report),
+        insights,
     })
 }
 
-fn perform_outlier_analysis(file_path: &str) -> Result<AnalysisResult, String> {
-    let analyses = analyze_csv_columns(file_path.to_string())?;
-
+fn outlier_report(analyses: &[ColumnAnalysis]) -> (OutlierReport, Vec<String>) {
     let numeric_columns: Vec<&ColumnAnalysis> = analyses.iter().filter(|a| a.data_type == "number").collect();
 
-    let summary = serde_json::json!({
-        "numeric_columns": numeric_columns.len(),
-        "outlier_analysis_available": numeric_columns.len() > 0
-    });
+    let report = OutlierReport {
+        numeric_columns: numeric_columns.len(),
+        outlier_analysis_available: !numeric_columns.is_empty(),
+        scope: ReportScope::default(),
+    };
+    (report, vec!["Outlier detection requires numeric columns".to_string()])
+}
 
+fn perform_pattern_analysis(headers: &[String], columns: &[Vec<String>]) -> Result<AnalysisResult, DataScienceError> {
+    let (report, insights) = pattern_report(&analyze_columns(headers, columns, DEFAULT_SAMPLE_SIZE));
     Ok(AnalysisResult {
-        analysis_type: "outliers".to_string(),
-        summary,
-        insights: vec!["Outlier detection requires numeric columns".to_string()],
+        report: AnalysisReport::Patterns(report),
+        insights,
     })
 }
 
-fn perform_pattern_analysis(file_path: &str) -> Result<AnalysisResult, String> {
-    let analyses = analyze_csv_columns(file_path.to_string())?;
-
+fn pattern_report(analyses: &[ColumnAnalysis]) -> (PatternReport, Vec<String>) {
     let text_columns: Vec<&ColumnAnalysis> = analyses.iter().filter(|a| a.data_type == "string").collect();
 
-    let summary = serde_json::json!({
-        "text_columns": text_columns.len(),
-        "pattern_analysis_available": text_columns.len() > 0
-    });
+    let report = PatternReport {
+        text_columns: text_columns.len(),
+        pattern_analysis_available: !text_columns.is_empty(),
+        scope: ReportScope::default(),
+    };
+    (
+        report,
+        vec!["Pattern recognition available for text columns".to_string()],
+    )
+}
 
-    Ok(AnalysisResult {
-        analysis_type: "patterns".to_string(),
-        summary,
-        insights: vec!["Pattern recognition available for text columns".to_string()],
-    })
+#[cfg(test)]
+// Each tokio test runs on its own single-threaded runtime, so holding the
+// settings guard across an await can't deadlock
+#[allow(clippy::await_holding_lock)]
+mod tests {
+    use super::*;
+    use crate::test_support::{csv_file, globals, path};
+
+    fn values(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn date_format(items: &[&str]) -> Option<String> {
+        infer_date_format(&values(items))
+    }
+
+    #[test]
+    fn iso_dates_and_datetimes() {
+        let _globals = globals();
+        assert_eq!(date_format(&["2024-01-02", "2024-03-04"]).as_deref(), Some("%Y-%m-%d"));
+        assert_eq!(date_format(&["2024/01/02"]).as_deref(), Some("%Y/%m/%d"));
+        assert_eq!(
+            date_format(&["2024-01-02T10:00:00Z", "2024-03-04T10:00:00.5+02:00"]).as_deref(),
+            Some("%+")
+        );
+        assert_eq!(
+            date_format(&["2024-01-02T10:00:00", "2024-03-04T10:00:00.123"]).as_deref(),
+            Some("%Y-%m-%dT%H:%M:%S%.f")
+        );
+    }
+
+    #[test]
+    fn slash_dates_are_disambiguated_by_the_whole_column() {
+        let _globals = globals();
+        assert_eq!(date_format(&["01/02/2024", "03/04/2024"]).as_deref(), Some("%m/%d/%Y"));
+        assert_eq!(date_format(&["01/02/2024", "13/04/2024"]).as_deref(), Some("%d/%m/%Y"));
+    }
+
+    #[test]
+    fn month_names_and_epoch_seconds() {
+        let _globals = globals();
+        assert_eq!(
+            date_format(&["Jan 5, 2024", "Feb 15, 2024"]).as_deref(),
+            Some("%b %d, %Y")
+        );
+        let epochs = values(&["1700000000", "1700000100"]);
+        assert_eq!(infer_column_date_format("created_at", &epochs).as_deref(), Some("%s"));
+        assert_eq!(infer_column_data_type("created_at", &epochs), "date");
+        assert_eq!(infer_column_data_type("ts", &epochs), "date");
+        assert_eq!(infer_data_type(&values(&["Jan 5, 2024", "Feb 15, 2024"])), "date");
+        assert_eq!(infer_data_type(&values(&["12", "13"])), "number");
+    }
+
+    #[test]
+    fn ten_digit_ids_stay_numbers() {
+        let _globals = globals();
+        let phones = values(&["5551234567", "5559876543"]);
+        let epochs = values(&["1700000000", "1700000100"]);
+
+        // Without a time-like header ten digits are just a number
+        assert_eq!(infer_column_data_type("phone", &phones), "number");
+        assert_eq!(infer_column_data_type("customer_id", &epochs), "number");
+        assert_eq!(infer_data_type(&epochs), "number");
+        assert_eq!(infer_date_format(&epochs), None);
+        // Past 2100 isn't a plausible timestamp, whatever the header says
+        assert_eq!(infer_column_data_type("updated", &phones), "number");
+        assert_eq!(infer_column_date_format("updated", &phones), None);
+
+        let file = csv_file("phone,name\n5551234567,ann\n5559876543,bob\n");
+        let analyses =
+            crate::parser::analyze_csv_columns(path(&file).to_string(), &CsvParseOptions::default(), 10, None).unwrap();
+        assert_eq!(analyses[0].data_type, "number");
+        assert_eq!(analyses[0].date_format, None);
+    }
+
+    #[test]
+    fn detected_formats_parse_their_values() {
+        for (value, format) in [
+            ("2024-01-02T10:00:00.123", "%Y-%m-%dT%H:%M:%S%.f"),
+            ("2024-01-02T10:00:00", "%Y-%m-%dT%H:%M:%S%.f"),
+            ("1700000000", "%s"),
+        ] {
+            assert!(
+                chrono::NaiveDateTime::parse_from_str(value, format).is_ok(),
+                "{} {}",
+                value,
+                format
+            );
+        }
+        assert!(chrono::NaiveDate::parse_from_str("Jan 5, 2024", "%b %d, %Y").is_ok());
+        assert!(chrono::DateTime::parse_from_str("2024-01-02T10:00:00Z", "%+").is_ok());
+    }
+
+    #[test]
+    fn mostly_non_dates_have_no_format() {
+        let _globals = globals();
+        assert_eq!(date_format(&["hello", "2024-01-01", "x"]), None);
+        assert_eq!(date_format(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn summary_reports_the_real_row_count() {
+        let _globals = globals();
+        let mut contents = String::from("id,empty,cat\n");
+        for i in 0..1000 {
+            contents.push_str(&format!("{},,{}\n", i, i % 3));
+        }
+        let file = csv_file(&contents);
+        let result = perform_data_science(
+            path(&file).to_string(),
+            "summary".to_string(),
+            &AnalysisOptions::default(),
+            &AtomicBool::new(false),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(serde_json::to_value(&result).unwrap()["summary"]["total_rows"], 1000);
+        assert!(result.insights[0].contains("1000 rows"));
+    }
+
+    async fn correlation_pairs(file: &tempfile::NamedTempFile, options: &AnalysisOptions) -> serde_json::Value {
+        let result = perform_data_science(
+            path(file).to_string(),
+            "correlation".to_string(),
+            options,
+            &AtomicBool::new(false),
+        )
+        .await
+        .unwrap();
+        serde_json::to_value(&result).unwrap()["summary"].clone()
+    }
+
+    // y grows exponentially with x, z falls linearly, w alternates
+    fn monotonic_file() -> tempfile::NamedTempFile {
+        let mut contents = String::from("x,y,z,w\n");
+        for i in 1..=10 {
+            let w = if i % 2 == 0 { 1 } else { 2 };
+            contents.push_str(&format!("{},{},{},{}\n", i, (i as f64).exp(), 11 - i, w));
+        }
+        csv_file(&contents)
+    }
+
+    #[tokio::test]
+    async fn pearson_understates_a_nonlinear_monotonic_relation() {
+        let _globals = globals();
+        let summary = correlation_pairs(&monotonic_file(), &AnalysisOptions::default()).await;
+        assert_eq!(summary["method"], "pearson");
+        let xy = &summary["pairs"][0];
+        let coefficient = xy["coefficient"].as_f64().unwrap();
+        assert!(coefficient > 0.7 && coefficient < 0.8, "{}", coefficient);
+        assert!(xy.get("significant").is_none());
+    }
+
+    #[tokio::test]
+    async fn spearman_ranks_a_monotonic_relation_perfectly() {
+        let _globals = globals();
+        let options = AnalysisOptions {
+            correlation_method: Some("spearman".to_string()),
+            include_significance: true,
+            ..Default::default()
+        };
+        let summary = correlation_pairs(&monotonic_file(), &options).await;
+        assert_eq!(summary["method"], "spearman");
+        let pairs = summary["pairs"].as_array().unwrap();
+        assert!((pairs[0]["coefficient"].as_f64().unwrap() - 1.0).abs() < 1e-12);
+        assert!((pairs[1]["coefficient"].as_f64().unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(pairs[0]["significant"], true);
+        assert_eq!(pairs[2]["significant"], false);
+    }
+
+    #[tokio::test]
+    async fn unknown_correlation_method_is_an_error() {
+        let _globals = globals();
+        let options = AnalysisOptions {
+            correlation_method: Some("kendall".to_string()),
+            ..Default::default()
+        };
+        let result = perform_data_science(
+            path(&monotonic_file()).to_string(),
+            "correlation".to_string(),
+            &options,
+            &AtomicBool::new(false),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn tied_values_share_their_average_rank() {
+        assert_eq!(average_ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+    }
+
+    #[test]
+    fn currency_and_percent_columns_are_numbers() {
+        let _globals = globals();
+        assert_eq!(infer_data_type(&values(&["$1,000", "$2,500.50", "45%"])), "number");
+        assert_eq!(infer_data_type(&values(&["1.234,56", "99,90"])), "number");
+    }
+
+    #[tokio::test]
+    async fn sampled_summaries_say_so() {
+        let _globals = globals();
+        let mut contents = String::from("id,v\n");
+        for i in 0..1000 {
+            contents.push_str(&format!("{},{}\n", i, i % 7));
+        }
+        let file = csv_file(&contents);
+        let summary = |options: AnalysisOptions| {
+            let file_path = path(&file).to_string();
+            async move {
+                let result = perform_data_science(file_path, "summary".to_string(), &options, &AtomicBool::new(false))
+                    .await
+                    .unwrap();
+                serde_json::to_value(&result).unwrap()["summary"].clone()
+            }
+        };
+
+        let sampled = summary(AnalysisOptions {
+            sample: Some(SampleSpec {
+                mode: "random".to_string(),
+                size: 100,
+                seed: Some(1),
+            }),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(sampled["sampled"], true);
+        assert_eq!(sampled["total_rows"], 100);
+        assert_eq!(sampled["sample"]["sample_rows"], 100);
+        assert_eq!(sampled["sample"]["rows_scanned"], 1000);
+        assert_eq!(sampled["sample"]["seed"], 1);
+
+        let exact = summary(AnalysisOptions::default()).await;
+        assert!(exact.get("sampled").is_none());
+    }
+
+    #[tokio::test]
+    async fn summary_lists_key_columns() {
+        let _globals = globals();
+        let file = csv_file("id,first,last\n1,Ann,Lee\n2,Ann,Kim\n3,Bob,Lee\n");
+        let result = perform_data_science(
+            path(&file).to_string(),
+            "summary".to_string(),
+            &AnalysisOptions::default(),
+            &AtomicBool::new(false),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&result).unwrap()["summary"]["key_columns"],
+            serde_json::json!([["id"]])
+        );
+    }
+
+    async fn loaded_summary(file_id: &str) -> serde_json::Value {
+        let result = analyze_loaded_data(
+            file_id.to_string(),
+            "summary".to_string(),
+            &AnalysisOptions::default(),
+            &AtomicBool::new(false),
+        )
+        .await
+        .unwrap();
+        serde_json::to_value(&result).unwrap()["summary"].clone()
+    }
+
+    #[tokio::test]
+    async fn loaded_analysis_follows_the_active_filters() {
+        let _globals = globals();
+        let file = csv_file("city,amount\nA,1\nA,2\nB,30\nB,40\nB,50\n");
+        let file_id = crate::storage::load_csv_into_store(path(&file), &CSVLoadOptions::default())
+            .unwrap()
+            .file_id;
+        let unfiltered = loaded_summary(&file_id).await;
+
+        let query: DataQuery = serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {"city": {"column": "city", "operator": "equals", "value": "B", "case_sensitive": true}},
+            "pagination": {"page": 1, "page_size": 10},
+        }))
+        .unwrap();
+        crate::storage::query_csv_data(&query).unwrap();
+        let filtered = loaded_summary(&file_id).await;
+        crate::storage::unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(unfiltered["total_rows"], 5);
+        assert!(unfiltered.get("filters_applied").is_none());
+        assert_eq!(filtered["total_rows"], 3);
+        assert_eq!(filtered["filters_applied"], 1);
+    }
+
+    #[tokio::test]
+    async fn loaded_analysis_rejects_samples_and_unknown_ids() {
+        let _globals = globals();
+        let options = AnalysisOptions {
+            sample: Some(SampleSpec {
+                mode: "head".to_string(),
+                size: 2,
+                seed: None,
+            }),
+            ..Default::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let sampled = analyze_loaded_data("any".to_string(), "summary".to_string(), &options, &cancel).await;
+        assert!(sampled.is_err());
+        let unknown = analyze_loaded_data(
+            "nope".to_string(),
+            "summary".to_string(),
+            &AnalysisOptions::default(),
+            &cancel,
+        )
+        .await;
+        assert!(unknown.is_err());
+    }
+
+    fn numeric_file() -> tempfile::NamedTempFile {
+        let mut contents = String::from("id,x,y,name\n");
+        for i in 0..20 {
+            contents.push_str(&format!("{},{},{},n{}\n", i, i, i * 2, i % 3));
+        }
+        csv_file(&contents)
+    }
+
+    async fn report_json(
+        file: &tempfile::NamedTempFile,
+        analysis_type: &str,
+        options: &AnalysisOptions,
+    ) -> serde_json::Value {
+        let result = perform_data_science(
+            path(file).to_string(),
+            analysis_type.to_string(),
+            options,
+            &AtomicBool::new(false),
+        )
+        .await
+        .unwrap();
+        serde_json::to_value(&result).unwrap()
+    }
+
+    #[tokio::test]
+    async fn summary_report_keeps_its_json_shape() {
+        let _globals = globals();
+        let file = numeric_file();
+        let json = report_json(&file, "summary", &AnalysisOptions::default()).await;
+
+        let mut keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["analysis_type", "insights", "summary"]);
+        assert_eq!(json["analysis_type"], "summary");
+        assert_eq!(json["summary"]["total_rows"], 20);
+        assert_eq!(json["summary"]["data_types"]["number"], 3);
+        assert_eq!(json["summary"]["columns"][0]["type"], "number");
+        assert!(json["summary"]["columns"][0].get("data_type").is_none());
+        assert!(json["summary"].get("sampled").is_none());
+
+        let typed: AnalysisResult = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&typed).unwrap(), json);
+    }
+
+    #[tokio::test]
+    async fn full_report_nests_each_analysis() {
+        let _globals = globals();
+        let file = numeric_file();
+        let json = report_json(&file, "full_report", &AnalysisOptions::default()).await;
+        assert_eq!(json["analysis_type"], "full_report");
+        assert_eq!(json["summary"]["outliers"]["outlier_analysis_available"], true);
+        assert_eq!(
+            json["summary"]["distribution"]["distribution_data"][3]["type"],
+            "string"
+        );
+        let typed: AnalysisResult = serde_json::from_value(json).unwrap();
+        assert_eq!(typed.analysis_type(), "full_report");
+
+        let sampled = AnalysisOptions {
+            sample: Some(SampleSpec {
+                mode: "head".to_string(),
+                size: 5,
+                seed: None,
+            }),
+            ..Default::default()
+        };
+        let json = report_json(&file, "full_report", &sampled).await;
+        assert_eq!(json["summary"]["sampled"], true);
+        assert_eq!(json["summary"]["sample"]["seed"], serde_json::Value::Null);
+        assert_eq!(json["summary"]["summary"]["total_rows"], 5);
+    }
+
+    #[tokio::test]
+    async fn full_report_progress_covers_reading_and_each_report() {
+        let _globals = globals();
+        let mut contents = String::from("x,y\n");
+        for i in 0..25_000 {
+            contents.push_str(&format!("{},{}\n", i, i % 7));
+        }
+        let file = csv_file(&contents);
+        let mut stages = Vec::new();
+        perform_data_science_with_progress(
+            path(&file).to_string(),
+            "full_report".to_string(),
+            &AnalysisOptions::default(),
+            &AtomicBool::new(false),
+            |progress| stages.push((progress.stage, progress.rows_processed)),
+        )
+        .await
+        .unwrap();
+
+        let expected: Vec<(String, usize)> = [
+            ("reading", 10_000),
+            ("reading", 20_000),
+            ("reading", 25_000),
+            ("summary", 25_000),
+            ("correlation", 25_000),
+            ("distribution", 25_000),
+            ("outliers", 25_000),
+            ("patterns", 25_000),
+        ]
+        .into_iter()
+        .map(|(stage, rows)| (stage.to_string(), rows))
+        .collect();
+        assert_eq!(stages, expected);
+    }
 }
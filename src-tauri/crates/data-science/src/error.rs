@@ -0,0 +1,115 @@
+use crate::utils::CANCELLED_ERROR;
+use thiserror::Error;
+
+/// Error returned across the crate, so callers can react to the kind of failure.
+///
+/// Messages are the same text the crate used to return as a plain `String`;
+/// `String::from` gives that text back for the Tauri boundary. Errors without
+/// a more specific kind are `Other`.
+#[derive(Debug, Error)]
+pub enum DataScienceError {
+    #[error("File not found: {path}")]
+    FileNotFound { path: String },
+    #[error("{path} is a directory, not a file")]
+    NotAFile { path: String },
+    #[error("Permission denied: can't read {path}")]
+    PermissionDenied { path: String },
+    #[error("Failed to {action} {path}: {source}")]
+    Io {
+        action: &'static str, // e.g. "read", "write", "open"
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{0}")]
+    Parse(String),
+    #[error("{0}")]
+    InvalidDelimiter(String),
+    #[error("Column '{0}' not found")]
+    ColumnNotFound(String),
+    #[error("CSV file with ID '{0}' not found")]
+    DatasetNotFound(String),
+    /// A query the data can't answer: bad filters, sorts, pages, or aggregations.
+    #[error("{0}")]
+    InvalidQuery(String),
+    /// An option or parameter outside what the operation accepts.
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("{}", CANCELLED_ERROR)]
+    Cancelled,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl DataScienceError {
+    /// `Io`, or the more specific `FileNotFound`, `NotAFile`, or `PermissionDenied`.
+    pub fn io(action: &'static str, path: impl Into<String>, source: std::io::Error) -> Self {
+        let path = path.into();
+        match source.kind() {
+            std::io::ErrorKind::NotFound => DataScienceError::FileNotFound { path },
+            std::io::ErrorKind::IsADirectory => DataScienceError::NotAFile { path },
+            std::io::ErrorKind::PermissionDenied => DataScienceError::PermissionDenied { path },
+            _ => DataScienceError::Io { action, path, source },
+        }
+    }
+
+    /// Stable name of the variant, for callers that only see the error as data.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DataScienceError::FileNotFound { .. } => "file_not_found",
+            DataScienceError::NotAFile { .. } => "not_a_file",
+            DataScienceError::PermissionDenied { .. } => "permission_denied",
+            DataScienceError::Io { .. } => "io",
+            DataScienceError::Parse(_) => "parse",
+            DataScienceError::InvalidDelimiter(_) => "invalid_delimiter",
+            DataScienceError::ColumnNotFound(_) => "column_not_found",
+            DataScienceError::DatasetNotFound(_) => "dataset_not_found",
+            DataScienceError::InvalidQuery(_) => "invalid_query",
+            DataScienceError::InvalidArgument(_) => "invalid_argument",
+            DataScienceError::Cancelled => "cancelled",
+            DataScienceError::Other(_) => "other",
+        }
+    }
+}
+
+impl From<String> for DataScienceError {
+    fn from(message: String) -> Self {
+        DataScienceError::Other(message)
+    }
+}
+
+impl From<&str> for DataScienceError {
+    fn from(message: &str) -> Self {
+        DataScienceError::Other(message.to_string())
+    }
+}
+
+impl From<DataScienceError> for String {
+    fn from(e: DataScienceError) -> Self {
+        e.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Error, ErrorKind};
+
+    #[test]
+    fn io_errors_map_to_specific_kinds() {
+        let kind = |error_kind| DataScienceError::io("read", "data.csv", Error::from(error_kind)).kind();
+
+        assert_eq!(kind(ErrorKind::NotFound), "file_not_found");
+        assert_eq!(kind(ErrorKind::IsADirectory), "not_a_file");
+        assert_eq!(kind(ErrorKind::PermissionDenied), "permission_denied");
+        assert_eq!(kind(ErrorKind::UnexpectedEof), "io");
+    }
+
+    #[test]
+    fn string_conversion_keeps_the_message() {
+        let error = DataScienceError::DatasetNotFound("abc".into());
+
+        assert_eq!(String::from(error), "CSV file with ID 'abc' not found");
+        assert_eq!(DataScienceError::from("boom").kind(), "other");
+    }
+}
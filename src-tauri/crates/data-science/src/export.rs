@@ -1,40 +1,1279 @@
-use crate::types::ExportOptions;
-use std::io::Write;
-
-pub fn export_to_csv(data: Vec<serde_json::Value>, file_path: String, options: ExportOptions) -> Result<(), String> {
-    let mut writer = std::fs::File::create(&file_path).map_err(|e| format!("Failed to create file: {}", e))?;
-
-    // If we have data and include_headers is true, try to extract headers from first object
-    if options.include_headers && !data.is_empty() {
-        if let Some(first_item) = data.first() {
-            if let Some(obj) = first_item.as_object() {
-                let headers: Vec<String> = obj.keys().cloned().collect();
-                let header_line = headers.join(&options.delimiter);
-                writeln!(writer, "{}", header_line).map_err(|e| format!("Failed to write headers: {}", e))?;
-
-                // Write data rows
-                for item in &data {
-                    if let Some(obj) = item.as_object() {
-                        let values: Vec<String> = headers
-                            .iter()
-                            .map(|key| obj.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string())
-                            .collect();
-                        let line = values.join(&options.delimiter);
-                        writeln!(writer, "{}", line).map_err(|e| format!("Failed to write data row: {}", e))?;
+#[cfg(feature = "parquet")]
+use crate::analysis::{infer_column_date_format, infer_data_type};
+use crate::error::DataScienceError;
+use crate::parser::{
+    collect_columns, column_detail, delimiter_byte, infer_schema, validate_data_quality, DEFAULT_SAMPLE_SIZE,
+};
+use crate::types::{CsvParseOptions, DataDictionary, ExportOptions, QualityWeights};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
+use flate2::write::GzEncoder;
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Write `data` to a CSV file and return the path written.
+///
+/// With `options.compression` the output goes through a gzip encoder or into a
+/// single-entry zip archive as it is written, and the path gets a `.gz` or
+/// `.zip` extension unless it already has one.
+pub fn export_to_csv(
+    data: Vec<serde_json::Value>,
+    file_path: String,
+    options: ExportOptions,
+) -> Result<String, DataScienceError> {
+    // Resolve the encoding and separators up front so bad options fail before anything is written
+    let encoding = resolve_export_encoding(&options.encoding)?;
+    let delimiter = delimiter_byte(&options.delimiter)?;
+    check_decimal_separator(&options)?;
+    write_export(&file_path, options.compression.as_deref(), |writer, file_path| {
+        write_csv_rows(writer, csv_rows(&data, &options), delimiter, encoding, file_path)
+    })
+}
+
+/// Write `data` to a JSON file as an array and return the path written.
+///
+/// The output is always UTF-8; `options.compression` works as for `export_to_csv`.
+pub fn export_to_json(
+    data: Vec<serde_json::Value>,
+    file_path: String,
+    options: ExportOptions,
+) -> Result<String, DataScienceError> {
+    write_export(&file_path, options.compression.as_deref(), |writer, file_path| {
+        write_json_array(
+            writer,
+            data.iter(),
+            |writer, value| serde_json::to_writer(writer, value),
+            file_path,
+        )
+    })
+}
+
+/// Write stored rows to a CSV file in `headers` order and return the path written.
+///
+/// Rows are rendered and compressed one at a time, so the file's text is never held in memory.
+pub(crate) fn write_dataset_csv(
+    headers: &[String],
+    rows: &[HashMap<String, serde_json::Value>],
+    file_path: &str,
+    options: &ExportOptions,
+) -> Result<String, DataScienceError> {
+    let encoding = resolve_export_encoding(&options.encoding)?;
+    let delimiter = delimiter_byte(&options.delimiter)?;
+    check_decimal_separator(options)?;
+    let decimal_comma = options.decimal_separator.as_deref() == Some(",");
+    let header_row = options.include_headers.then(|| headers.to_vec());
+    let records = header_row.into_iter().chain(rows.iter().map(|row| {
+        headers
+            .iter()
+            .map(|header| {
+                row.get(header)
+                    .map(|v| export_field(v, decimal_comma))
+                    .unwrap_or_default()
+            })
+            .collect()
+    }));
+    write_export(file_path, options.compression.as_deref(), |writer, file_path| {
+        write_csv_rows(writer, records, delimiter, encoding, file_path)
+    })
+}
+
+/// Write stored rows to a JSON array of objects, keys in `headers` order, and return the path written.
+pub(crate) fn write_dataset_json(
+    headers: &[String],
+    rows: &[HashMap<String, serde_json::Value>],
+    file_path: &str,
+    options: &ExportOptions,
+) -> Result<String, DataScienceError> {
+    write_export(file_path, options.compression.as_deref(), |writer, file_path| {
+        write_json_array(
+            writer,
+            rows.iter(),
+            |writer, row| {
+                // Written key by key, since a serde_json map would reorder the columns
+                writer.write_all(b"{").map_err(serde_json::Error::io)?;
+                for (i, header) in headers.iter().enumerate() {
+                    if i > 0 {
+                        writer.write_all(b",").map_err(serde_json::Error::io)?;
                     }
+                    serde_json::to_writer(&mut *writer, header)?;
+                    writer.write_all(b":").map_err(serde_json::Error::io)?;
+                    serde_json::to_writer(&mut *writer, row.get(header).unwrap_or(&serde_json::Value::Null))?;
                 }
+                writer.write_all(b"}").map_err(serde_json::Error::io)
+            },
+            file_path,
+        )
+    })
+}
+
+/// Create `file_path` (with the `compression` extension added), fill it through `write`, and return the path written.
+///
+/// A failed export removes the file rather than leaving a truncated one behind.
+pub(crate) fn write_export(
+    file_path: &str,
+    compression: Option<&str>,
+    write: impl FnOnce(&mut ExportWriter, &str) -> Result<(), DataScienceError>,
+) -> Result<String, DataScienceError> {
+    let file_path = compressed_path(file_path, compression)?;
+    let file = std::fs::File::create(&file_path).map_err(|e| DataScienceError::io("create", &file_path, e))?;
+    let written = ExportWriter::new(file, &file_path, compression).and_then(|mut writer| {
+        write(&mut writer, &file_path)?;
+        writer
+            .finish()
+            .map_err(|e| DataScienceError::io("write", &file_path, e))
+    });
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&file_path);
+        return Err(e);
+    }
+    Ok(file_path)
+}
+
+/// Write CSV records one at a time in `encoding`, after its byte order mark.
+///
+/// Fields holding the delimiter, a quote, or a line break are quoted as in RFC 4180.
+pub(crate) fn write_csv_rows(
+    writer: &mut ExportWriter,
+    rows: impl Iterator<Item = Vec<String>>,
+    delimiter: u8,
+    encoding: &'static Encoding,
+    file_path: &str,
+) -> Result<(), DataScienceError> {
+    writer
+        .write_all(byte_order_mark(encoding))
+        .map_err(|e| DataScienceError::io("write", file_path, e))?;
+    let mut csv_writer = csv_writer(
+        EncodedWriter {
+            inner: writer,
+            encoding,
+            pending: Vec::new(),
+        },
+        delimiter,
+    );
+    for row in rows {
+        csv_writer
+            .write_record(&row)
+            .map_err(|e| csv_write_error(e, file_path))?;
+    }
+    csv_writer.flush().map_err(|e| csv_write_error(e.into(), file_path))
+}
+
+// Rows end in `\n`, and fields are quoted only when they need it
+fn csv_writer<W: Write>(writer: W, delimiter: u8) -> csv::Writer<W> {
+    csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(writer)
+}
+
+// The encoding error that stopped a CSV write, or the write failure itself
+fn csv_write_error(error: csv::Error, file_path: &str) -> DataScienceError {
+    match error.into_kind() {
+        csv::ErrorKind::Io(e) => e
+            .downcast::<DataScienceError>()
+            .unwrap_or_else(|e| DataScienceError::io("write", file_path, e)),
+        kind => format!("Failed to write CSV record: {:?}", kind).into(),
+    }
+}
+
+/// Passes the UTF-8 text written to it on to an `ExportWriter` in `encoding`.
+///
+/// The bytes of a character split across writes are held until the rest arrive.
+/// Text the encoding can't represent fails the write with the `encode_text` error.
+struct EncodedWriter<'a> {
+    inner: &'a mut ExportWriter,
+    encoding: &'static Encoding,
+    pending: Vec<u8>,
+}
+
+impl Write for EncodedWriter<'_> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.pending.extend_from_slice(bytes);
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text,
+            Err(e) => std::str::from_utf8(&self.pending[..e.valid_up_to()]).unwrap_or_default(),
+        };
+        let encoded = encode_text(complete, self.encoding).map_err(std::io::Error::other)?;
+        let written = complete.len();
+        self.inner.write_all(&encoded)?;
+        self.pending.drain(..written);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Write `items` as a JSON array with one element per line, serializing each through `write_item`.
+pub(crate) fn write_json_array<T>(
+    writer: &mut ExportWriter,
+    items: impl Iterator<Item = T>,
+    write_item: impl Fn(&mut ExportWriter, T) -> serde_json::Result<()>,
+    file_path: &str,
+) -> Result<(), DataScienceError> {
+    let io_error = |e| DataScienceError::io("write", file_path, e);
+    writer.write_all(b"[").map_err(io_error)?;
+    for (i, item) in items.enumerate() {
+        writer
+            .write_all(if i == 0 { b"\n" } else { b",\n" })
+            .map_err(io_error)?;
+        write_item(writer, item).map_err(|e| format!("Failed to serialize row {}: {}", i + 1, e))?;
+    }
+    writer.write_all(b"\n]\n").map_err(io_error)
+}
+
+/// Output file of an export, compressed as the caller asked.
+pub(crate) enum ExportWriter {
+    Plain(BufWriter<std::fs::File>),
+    Gzip(GzEncoder<BufWriter<std::fs::File>>),
+    Zip(Box<ZipWriter<std::fs::File>>),
+}
+
+impl ExportWriter {
+    fn new(file: std::fs::File, file_path: &str, compression: Option<&str>) -> Result<Self, DataScienceError> {
+        Ok(match compression {
+            Some("gzip") => ExportWriter::Gzip(GzEncoder::new(BufWriter::new(file), flate2::Compression::default())),
+            Some("zip") => {
+                // The archive holds one entry named after the file, without the `.zip`
+                let name = Path::new(file_path)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("export");
+                let entry = name
+                    .strip_suffix(".zip")
+                    .filter(|entry| !entry.is_empty())
+                    .unwrap_or("export");
+                let mut zip = ZipWriter::new(file);
+                zip.start_file(
+                    entry,
+                    SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+                )
+                .map_err(|e| format!("Failed to start zip entry: {}", e))?;
+                ExportWriter::Zip(Box::new(zip))
+            }
+            _ => ExportWriter::Plain(BufWriter::new(file)),
+        })
+    }
+
+    // Flush everything and write the gzip trailer or zip central directory
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            ExportWriter::Plain(mut writer) => writer.flush(),
+            ExportWriter::Gzip(writer) => writer.finish()?.flush(),
+            ExportWriter::Zip(writer) => writer.finish().map(drop).map_err(std::io::Error::other),
+        }
+    }
+}
+
+impl Write for ExportWriter {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        match self {
+            ExportWriter::Plain(writer) => writer.write(bytes),
+            ExportWriter::Gzip(writer) => writer.write(bytes),
+            ExportWriter::Zip(writer) => writer.write(bytes),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ExportWriter::Plain(writer) => writer.flush(),
+            ExportWriter::Gzip(writer) => writer.flush(),
+            ExportWriter::Zip(writer) => writer.flush(),
+        }
+    }
+}
+
+/// `file_path` with the extension for `compression` appended if missing; only "gzip" and "zip" are supported.
+fn compressed_path(file_path: &str, compression: Option<&str>) -> Result<String, DataScienceError> {
+    let extension = match compression {
+        None => return Ok(file_path.to_string()),
+        Some("gzip") => ".gz",
+        Some("zip") => ".zip",
+        Some(other) => {
+            return Err(DataScienceError::InvalidArgument(format!(
+                "Unsupported export compression: '{}'",
+                other
+            )))
+        }
+    };
+    if file_path.to_ascii_lowercase().ends_with(extension) {
+        Ok(file_path.to_string())
+    } else {
+        Ok(format!("{}{}", file_path, extension))
+    }
+}
+
+/// The first `max_lines` records `export_to_csv` would write for `data` and `options`, without writing a file.
+///
+/// The text is returned as UTF-8, but fails like the export would if the encoding is
+/// unsupported or can't represent the previewed rows.
+pub fn preview_export(
+    data: &[serde_json::Value],
+    options: &ExportOptions,
+    max_lines: usize,
+) -> Result<String, DataScienceError> {
+    let encoding = resolve_export_encoding(&options.encoding)?;
+    let delimiter = delimiter_byte(&options.delimiter)?;
+    check_decimal_separator(options)?;
+    let mut writer = csv_writer(Vec::new(), delimiter);
+    for row in csv_rows(data, options).take(max_lines) {
+        writer
+            .write_record(&row)
+            .map_err(|e| format!("Failed to write CSV record: {}", e))?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to write CSV record: {}", e.error()))?;
+    let text = String::from_utf8(bytes).map_err(|e| format!("Exported text isn't UTF-8: {}", e))?;
+    encode_text(&text, encoding)?;
+    Ok(text)
+}
+
+/// The CSV records (headers included) for the object rows in `data`, rendered as they are consumed.
+///
+/// With `include_headers` the columns are the first row's keys, and nothing is rendered
+/// if the first row isn't an object; without it each row's values are written in order.
+fn csv_rows<'a>(
+    data: &'a [serde_json::Value],
+    options: &'a ExportOptions,
+) -> Box<dyn Iterator<Item = Vec<String>> + 'a> {
+    let headers: Option<Vec<String>> = match data.first() {
+        Some(first) if options.include_headers => match first.as_object() {
+            Some(obj) => Some(obj.keys().cloned().collect()),
+            None => return Box::new(std::iter::empty()),
+        },
+        _ => None,
+    };
+
+    let decimal_comma = options.decimal_separator.as_deref() == Some(",");
+    let header_row = headers.clone();
+    let rows = data
+        .iter()
+        .filter_map(|item| item.as_object())
+        .map(move |obj| match &headers {
+            Some(headers) => headers
+                .iter()
+                .map(|key| obj.get(key).map(|v| export_field(v, decimal_comma)).unwrap_or_default())
+                .collect(),
+            None => obj.values().map(|v| export_field(v, decimal_comma)).collect(),
+        });
+    Box::new(header_row.into_iter().chain(rows))
+}
+
+// Numbers and booleans are written as their JSON text, numbers with a decimal comma
+// if `decimal_comma` is set; null becomes an empty field
+fn export_field(value: &serde_json::Value, decimal_comma: bool) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::Number(n) if decimal_comma => n.to_string().replace('.', ","),
+        other => other.to_string(),
+    }
+}
+
+/// Only "." and "," are supported; a number holding the delimiter is quoted like any other field.
+fn check_decimal_separator(options: &ExportOptions) -> Result<(), DataScienceError> {
+    let separator = options.decimal_separator.as_deref().unwrap_or(".");
+    if separator != "." && separator != "," {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Unsupported decimal separator: '{}'",
+            separator
+        )));
+    }
+    Ok(())
+}
+
+/// Look up an export encoding by any WHATWG label (e.g. "utf-8", "utf-16le", "windows-1252").
+fn resolve_export_encoding(name: &str) -> Result<&'static Encoding, DataScienceError> {
+    let encoding = Encoding::for_label(name.trim().as_bytes())
+        .ok_or_else(|| DataScienceError::InvalidArgument(format!("Unsupported export encoding: '{}'", name)))?;
+
+    // encoding_rs can only encode into encodings that are their own output encoding (plus UTF-16, handled below)
+    if encoding != UTF_16LE && encoding != UTF_16BE && encoding.output_encoding() != encoding {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Unsupported export encoding: '{}'",
+            name
+        )));
+    }
+    Ok(encoding)
+}
+
+/// The byte order mark written at the start of UTF-16 output; empty for other encodings.
+fn byte_order_mark(encoding: &'static Encoding) -> &'static [u8] {
+    if encoding == UTF_16LE {
+        &[0xFF, 0xFE]
+    } else if encoding == UTF_16BE {
+        &[0xFE, 0xFF]
+    } else {
+        &[]
+    }
+}
+
+/// Encode CSV text, without a byte order mark.
+fn encode_text(text: &str, encoding: &'static Encoding) -> Result<Vec<u8>, DataScienceError> {
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let mut bytes = Vec::with_capacity(text.len() * 2);
+        for unit in text.encode_utf16() {
+            if encoding == UTF_16LE {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            } else {
+                bytes.extend_from_slice(&unit.to_be_bytes());
             }
         }
+        return Ok(bytes);
+    }
+
+    let (bytes, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        return Err(format!(
+            "Data contains characters that cannot be represented in {}",
+            encoding.name()
+        )
+        .into());
+    }
+    Ok(bytes.into_owned())
+}
+
+/// Write a schema from `infer_schema` to `file_path` as pretty-printed JSON.
+pub fn write_schema_file(schema: &serde_json::Value, file_path: &str) -> Result<(), DataScienceError> {
+    let text = serde_json::to_string_pretty(schema).map_err(|e| format!("Failed to serialize schema: {}", e))?;
+    std::fs::write(file_path, text).map_err(|e| DataScienceError::io("write schema to", file_path, e))
+}
+
+/// Profile a file and write its data dictionary to `out_path` as "json" or "markdown".
+///
+/// The dictionary combines each column's `column_detail`, the default-weighted
+/// `validate_data_quality` report, and `infer_schema`. JSON is the
+/// `DataDictionary` itself; Markdown has an overview table with a row per
+/// column, then a table of properties for each column.
+pub fn export_data_dictionary(
+    file_path: &str,
+    out_path: &str,
+    format: &str,
+    options: &CsvParseOptions,
+) -> Result<DataDictionary, DataScienceError> {
+    if !matches!(format, "json" | "markdown") {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Unknown data dictionary format: {}",
+            format
+        )));
+    }
+
+    let (headers, columns) = collect_columns(file_path.to_string(), options)?;
+    let dictionary = DataDictionary {
+        file_path: file_path.to_string(),
+        row_count: columns.first().map_or(0, Vec::len),
+        columns: headers
+            .iter()
+            .zip(&columns)
+            .map(|(header, values)| column_detail(header, values, DEFAULT_SAMPLE_SIZE))
+            .collect(),
+        quality: validate_data_quality(file_path.to_string(), options, &[], &QualityWeights::default())?,
+        schema: infer_schema(file_path.to_string(), options)?,
+    };
+
+    let text = if format == "json" {
+        serde_json::to_string_pretty(&dictionary).map_err(|e| format!("Failed to serialize data dictionary: {}", e))?
     } else {
-        // Write data without headers
-        for item in &data {
-            if let Some(obj) = item.as_object() {
-                let values: Vec<String> = obj.values().map(|v| v.as_str().unwrap_or("").to_string()).collect();
-                let line = values.join(&options.delimiter);
-                writeln!(writer, "{}", line).map_err(|e| format!("Failed to write data row: {}", e))?;
+        data_dictionary_markdown(&dictionary)
+    };
+    std::fs::write(out_path, text).map_err(|e| DataScienceError::io("write data dictionary to", out_path, e))?;
+    Ok(dictionary)
+}
+
+fn data_dictionary_markdown(dictionary: &DataDictionary) -> String {
+    let title = std::path::Path::new(&dictionary.file_path).file_name().map_or_else(
+        || dictionary.file_path.clone(),
+        |name| name.to_string_lossy().to_string(),
+    );
+    let mut out = format!(
+        "# Data dictionary: {}\n\n{} rows, {} columns. Quality score {:.1}/100, completeness {:.1}%, {} duplicate rows.\n",
+        markdown_cell(&title),
+        dictionary.row_count,
+        dictionary.columns.len(),
+        dictionary.quality.overall_score,
+        dictionary.quality.completeness_score,
+        dictionary.quality.duplicate_count
+    );
+    for issue in &dictionary.quality.issues {
+        out.push_str(&format!("\n- {}", markdown_cell(issue)));
+    }
+    if !dictionary.quality.issues.is_empty() {
+        out.push('\n');
+    }
+
+    out.push_str(
+        "\n## Columns\n\n| Column | Type | Nullable | Null % | Unique | Issues |\n|---|---|---|---|---|---|\n",
+    );
+    for column in &dictionary.columns {
+        let a = &column.analysis;
+        out.push_str(&format!(
+            "| {} | {} | {} | {:.1} | {} | {} |\n",
+            markdown_cell(&a.name),
+            a.data_type,
+            if a.nullable { "yes" } else { "no" },
+            a.null_percentage,
+            a.unique_count,
+            markdown_cell(&column_issues(dictionary, &a.name).join(", "))
+        ));
+    }
+
+    for column in &dictionary.columns {
+        let a = &column.analysis;
+        let mut rows: Vec<(&str, String)> = vec![("Type", a.data_type.clone())];
+        let property = &dictionary.schema["properties"][&a.name];
+        if let Some(format) = property.get("format").and_then(serde_json::Value::as_str) {
+            rows.push(("Format", format.to_string()));
+        }
+        if let Some(date_format) = &a.date_format {
+            rows.push(("Date format", format!("`{}`", date_format)));
+        }
+        rows.push(("Nullable", if a.nullable { "yes" } else { "no" }.to_string()));
+        rows.push(("Null %", format!("{:.1}", a.null_percentage)));
+        rows.push(("Unique values", a.unique_count.to_string()));
+        if let Some(stats) = &column.numeric_stats {
+            rows.push(("Min", stats.min.to_string()));
+            rows.push(("Max", stats.max.to_string()));
+            rows.push(("Mean", format!("{:.4}", stats.mean)));
+            rows.push(("Median", stats.median.to_string()));
+            if let Some(std_dev) = stats.std_dev {
+                rows.push(("Std dev", format!("{:.4}", std_dev)));
             }
         }
+        if let Some(counts) = &column.value_counts {
+            let top: Vec<String> = counts
+                .iter()
+                .take(DEFAULT_SAMPLE_SIZE)
+                .map(|(value, count)| format!("{} ({})", value, count))
+                .collect();
+            rows.push(("Top values", top.join(", ")));
+        }
+        if !a.sample_values.is_empty() {
+            rows.push(("Sample values", a.sample_values.join(", ")));
+        }
+        let issues = column_issues(dictionary, &a.name);
+        if !issues.is_empty() {
+            rows.push(("Issues", issues.join(", ")));
+        }
+
+        out.push_str(&format!(
+            "\n### {}\n\n| Property | Value |\n|---|---|\n",
+            markdown_cell(&a.name)
+        ));
+        for (property, value) in rows {
+            out.push_str(&format!("| {} | {} |\n", property, markdown_cell(&value)));
+        }
+    }
+    out
+}
+
+fn column_issues<'a>(dictionary: &'a DataDictionary, column: &str) -> &'a [String] {
+    dictionary.quality.column_issues.get(column).map_or(&[], Vec::as_slice)
+}
+
+// Pipes would end the cell and newlines the row
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+/// Write rows of JSON objects to a Parquet file, one typed column per key.
+///
+/// Columns come from the first object's keys. `schema_hint` maps column names
+/// to "number", "integer", "boolean", "date", or "string"; other columns are
+/// typed from their values. Numbers become Int64 (when every value is whole)
+/// or Float64, booleans Boolean, dates millisecond Timestamps, and everything
+/// else Utf8. Values that don't fit their column's type are written as null.
+#[cfg(feature = "parquet")]
+pub fn export_to_parquet(
+    data: Vec<serde_json::Value>,
+    file_path: String,
+    schema_hint: Option<HashMap<String, String>>,
+) -> Result<(), DataScienceError> {
+    let rows: Vec<&serde_json::Map<String, serde_json::Value>> = data.iter().filter_map(|v| v.as_object()).collect();
+    let headers: Vec<String> = rows
+        .first()
+        .map(|row| row.keys().cloned().collect())
+        .ok_or("No rows to export")?;
+
+    let schema_hint = schema_hint.unwrap_or_default();
+    let column_types: Vec<String> = headers
+        .iter()
+        .map(|header| {
+            schema_hint
+                .get(header)
+                .cloned()
+                .unwrap_or_else(|| json_column_type(rows.iter().filter_map(|row| row.get(header))))
+        })
+        .collect();
+
+    write_parquet(
+        &headers,
+        &column_types,
+        rows.len(),
+        |row, header| rows[row].get(header),
+        &file_path,
+    )
+}
+
+// The analysis type ("number", "boolean", "date", "string") shared by a column's non-null JSON values
+#[cfg(feature = "parquet")]
+fn json_column_type<'a>(values: impl Iterator<Item = &'a serde_json::Value>) -> String {
+    let values: Vec<&serde_json::Value> = values.filter(|v| !v.is_null()).collect();
+    if values.is_empty() {
+        "string".to_string()
+    } else if values.iter().all(|v| v.is_number()) {
+        "number".to_string()
+    } else if values.iter().all(|v| v.is_boolean()) {
+        "boolean".to_string()
+    } else if values.iter().all(|v| v.is_string()) {
+        let text: Vec<String> = values.iter().map(|v| export_field(v, false)).collect();
+        match infer_data_type(&text).as_str() {
+            "date" => "date".to_string(),
+            _ => "string".to_string(),
+        }
+    } else {
+        "string".to_string()
+    }
+}
+
+/// Write `row_count` rows to a Parquet file, reading each cell through `cell(row, header)`.
+#[cfg(feature = "parquet")]
+pub(crate) fn write_parquet<'a>(
+    headers: &[String],
+    column_types: &[String],
+    row_count: usize,
+    cell: impl Fn(usize, &str) -> Option<&'a serde_json::Value>,
+    file_path: &str,
+) -> Result<(), DataScienceError> {
+    use arrow::array::{ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray, TimestampMillisecondArray};
+    use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use std::sync::Arc;
+
+    let mut fields = Vec::with_capacity(headers.len());
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(headers.len());
+
+    for (header, column_type) in headers.iter().zip(column_types) {
+        let values: Vec<Option<&serde_json::Value>> = (0..row_count)
+            .map(|row| cell(row, header).filter(|v| !v.is_null()))
+            .collect();
+
+        let whole_numbers = values.iter().flatten().all(|v| as_i64(v).is_some());
+        let (data_type, array): (DataType, ArrayRef) = match column_type.as_str() {
+            "integer" => (
+                DataType::Int64,
+                Arc::new(Int64Array::from_iter(values.iter().map(|v| v.and_then(as_i64)))),
+            ),
+            "number" if whole_numbers => (
+                DataType::Int64,
+                Arc::new(Int64Array::from_iter(values.iter().map(|v| v.and_then(as_i64)))),
+            ),
+            "number" => (
+                DataType::Float64,
+                Arc::new(Float64Array::from_iter(values.iter().map(|v| v.and_then(as_f64)))),
+            ),
+            "boolean" => (
+                DataType::Boolean,
+                Arc::new(BooleanArray::from_iter(values.iter().map(|v| v.and_then(as_bool)))),
+            ),
+            "date" => {
+                let text: Vec<String> = values.iter().flatten().map(|v| export_field(v, false)).collect();
+                let format = infer_column_date_format(header, &text);
+                (
+                    DataType::Timestamp(TimeUnit::Millisecond, None),
+                    Arc::new(TimestampMillisecondArray::from_iter(values.iter().map(|v| {
+                        v.and_then(|v| timestamp_millis(&export_field(v, false), format.as_deref()))
+                    }))),
+                )
+            }
+            _ => (
+                DataType::Utf8,
+                Arc::new(StringArray::from_iter(
+                    values.iter().map(|v| v.map(|v| export_field(v, false))),
+                )),
+            ),
+        };
+
+        fields.push(Field::new(header, data_type, true));
+        arrays.push(array);
     }
 
+    let schema = Arc::new(Schema::new(fields));
+    let batch = arrow::record_batch::RecordBatch::try_new(schema.clone(), arrays)
+        .map_err(|e| format!("Failed to build Parquet data: {}", e))?;
+
+    let file = std::fs::File::create(file_path).map_err(|e| DataScienceError::io("create", file_path, e))?;
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, schema, None)
+        .map_err(|e| format!("Failed to write Parquet file: {}", e))?;
+    writer
+        .write(&batch)
+        .map_err(|e| format!("Failed to write Parquet file: {}", e))?;
+    writer
+        .close()
+        .map_err(|e| format!("Failed to write Parquet file: {}", e))?;
+
     Ok(())
 }
+
+// Numeric cells may arrive as JSON numbers or as numeric text
+#[cfg(feature = "parquet")]
+fn as_i64(value: &serde_json::Value) -> Option<i64> {
+    value.as_i64().or_else(|| value.as_str()?.trim().parse().ok())
+}
+
+#[cfg(feature = "parquet")]
+fn as_f64(value: &serde_json::Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str()?.trim().parse().ok())
+}
+
+#[cfg(feature = "parquet")]
+fn as_bool(value: &serde_json::Value) -> Option<bool> {
+    value
+        .as_bool()
+        .or_else(|| match value.as_str()?.trim().to_lowercase().as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        })
+}
+
+// Milliseconds since the Unix epoch for a date in `format` (a chrono format, "%s", or RFC 3339)
+#[cfg(feature = "parquet")]
+fn timestamp_millis(value: &str, format: Option<&str>) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.timestamp_millis());
+    }
+    match format? {
+        "%s" => value.parse::<i64>().ok()?.checked_mul(1000),
+        format => chrono::NaiveDateTime::parse_from_str(value, format)
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDate::parse_from_str(value, format)
+                    .ok()?
+                    .and_hms_opt(0, 0, 0)
+            })
+            .map(|datetime| datetime.and_utc().timestamp_millis()),
+    }
+}
+
+/// Replace `table_name` in the SQLite database at `db_path` with the given rows.
+#[cfg(feature = "sqlite")]
+pub(crate) fn write_sqlite(
+    headers: &[String],
+    column_types: &[String],
+    rows: &[HashMap<String, serde_json::Value>],
+    db_path: &str,
+    table_name: &str,
+) -> Result<usize, DataScienceError> {
+    use crate::parser::sanitize_sql_identifier;
+    use rusqlite::types::Value;
+
+    let table = sanitize_sql_identifier(table_name);
+
+    // Distinct headers can sanitize to the same name, so later ones get a numeric suffix
+    let mut columns: Vec<String> = Vec::with_capacity(headers.len());
+    for header in headers {
+        let base = sanitize_sql_identifier(header);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while columns.iter().any(|c| c.eq_ignore_ascii_case(&name)) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        columns.push(name);
+    }
+
+    // Whole-number columns are stored as INTEGER, other number columns as REAL
+    let sql_types: Vec<&str> = headers
+        .iter()
+        .zip(column_types)
+        .map(|(header, column_type)| match column_type.as_str() {
+            "number"
+                if rows
+                    .iter()
+                    .all(|row| row.get(header).is_none_or(|v| v.is_null() || v.is_i64())) =>
+            {
+                "INTEGER"
+            }
+            "number" => "REAL",
+            "boolean" => "INTEGER",
+            _ => "TEXT",
+        })
+        .collect();
+
+    let mut connection =
+        rusqlite::Connection::open(db_path).map_err(|e| format!("Failed to open SQLite database: {}", e))?;
+    let transaction = connection
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let column_definitions: Vec<String> = columns
+        .iter()
+        .zip(&sql_types)
+        .map(|(name, sql_type)| format!("\"{}\" {}", name, sql_type))
+        .collect();
+    transaction
+        .execute_batch(&format!(
+            "DROP TABLE IF EXISTS \"{table}\"; CREATE TABLE \"{table}\" ({});",
+            column_definitions.join(", ")
+        ))
+        .map_err(|e| format!("Failed to create table '{}': {}", table, e))?;
+
+    {
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut insert = transaction
+            .prepare(&format!("INSERT INTO \"{}\" VALUES ({})", table, placeholders))
+            .map_err(|e| format!("Failed to prepare insert: {}", e))?;
+
+        for row in rows {
+            let values = headers.iter().zip(&sql_types).map(|(header, sql_type)| {
+                match (row.get(header).unwrap_or(&serde_json::Value::Null), *sql_type) {
+                    (serde_json::Value::Null, _) => Value::Null,
+                    (serde_json::Value::Bool(b), _) => Value::Integer(i64::from(*b)),
+                    (serde_json::Value::Number(n), "INTEGER") => n.as_i64().map_or(Value::Null, Value::Integer),
+                    (serde_json::Value::Number(n), "REAL") => n.as_f64().map_or(Value::Null, Value::Real),
+                    (other, _) => Value::Text(export_field(other, false)),
+                }
+            });
+            insert
+                .execute(rusqlite::params_from_iter(values))
+                .map_err(|e| format!("Failed to insert row: {}", e))?;
+        }
+    }
+
+    transaction
+        .commit()
+        .map_err(|e| format!("Failed to commit rows: {}", e))?;
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{csv_file, globals, path};
+    use serde_json::json;
+    use std::io::Read;
+
+    fn options(compression: Option<&str>, encoding: &str) -> ExportOptions {
+        ExportOptions {
+            delimiter: ",".to_string(),
+            include_headers: true,
+            encoding: encoding.to_string(),
+            quote_fields: "never".to_string(),
+            decimal_separator: None,
+            compression: compression.map(str::to_string),
+        }
+    }
+
+    fn rows(count: usize) -> Vec<serde_json::Value> {
+        (0..count)
+            .map(|i| json!({"id": i, "name": format!("row {}", i), "v": i as f64 / 4.0}))
+            .collect()
+    }
+
+    fn path_in(dir: &tempfile::TempDir, name: &str) -> String {
+        dir.path().join(name).to_string_lossy().to_string()
+    }
+
+    fn gunzip(path: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(path).unwrap())
+            .read_to_end(&mut out)
+            .unwrap();
+        out
+    }
+
+    fn unzip(path: &str) -> (String, Vec<u8>) {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 1);
+        let mut entry = archive.by_index(0).unwrap();
+        let mut out = Vec::new();
+        entry.read_to_end(&mut out).unwrap();
+        (entry.name().to_string(), out)
+    }
+
+    #[test]
+    fn gzip_csv_decompresses_to_the_plain_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = export_to_csv(rows(5000), path_in(&dir, "plain.csv"), options(None, "utf-8")).unwrap();
+        let expected = std::fs::read(&plain).unwrap();
+        assert!(expected.starts_with(b"id,name,v\n0,row 0,0.0\n"));
+
+        let gz = export_to_csv(rows(5000), path_in(&dir, "out.csv"), options(Some("gzip"), "utf-8")).unwrap();
+        assert!(gz.ends_with("out.csv.gz"));
+        assert_eq!(gunzip(&gz), expected);
+        assert!(std::fs::metadata(&gz).unwrap().len() < expected.len() as u64 / 2);
+    }
+
+    #[test]
+    fn zip_csv_holds_one_entry_named_after_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = export_to_csv(rows(100), path_in(&dir, "plain.csv"), options(None, "utf-8")).unwrap();
+        let zipped = export_to_csv(rows(100), path_in(&dir, "out.csv"), options(Some("zip"), "utf-8")).unwrap();
+        assert!(zipped.ends_with("out.csv.zip"));
+        assert_eq!(unzip(&zipped), ("out.csv".to_string(), std::fs::read(&plain).unwrap()));
+    }
+
+    #[test]
+    fn compressed_path_keeps_an_existing_extension() {
+        assert_eq!(compressed_path("a.csv.GZ", Some("gzip")).unwrap(), "a.csv.GZ");
+        assert_eq!(compressed_path("a.json", Some("zip")).unwrap(), "a.json.zip");
+        assert_eq!(compressed_path("a.csv", None).unwrap(), "a.csv");
+        assert!(compressed_path("a.csv", Some("bzip2")).is_err());
+    }
+
+    #[test]
+    fn compressed_utf16_has_a_single_byte_order_mark() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = export_to_csv(rows(2), path_in(&dir, "plain.csv"), options(None, "utf-16le")).unwrap();
+        let gz = export_to_csv(rows(2), path_in(&dir, "out.csv"), options(Some("gzip"), "utf-16le")).unwrap();
+        let out = gunzip(&gz);
+        assert_eq!(out, std::fs::read(&plain).unwrap());
+        assert_eq!(&out[..4], &[0xFF, 0xFE, b'i', 0]);
+        assert_eq!(out.windows(2).filter(|w| w == &[0xFF, 0xFE]).count(), 1);
+    }
+
+    #[test]
+    fn failed_export_leaves_no_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = vec![json!({"a": "plain"}), json!({"a": "snow ☃"})];
+        assert!(export_to_csv(data, path_in(&dir, "x.csv"), options(Some("gzip"), "windows-1252")).is_err());
+        assert!(export_to_csv(rows(1), path_in(&dir, "y.csv"), options(Some("bzip2"), "utf-8")).is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn json_export_round_trips_through_gzip_and_zip() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = rows(50);
+        let plain = export_to_json(data.clone(), path_in(&dir, "plain.json"), options(None, "utf-8")).unwrap();
+        let expected = std::fs::read(&plain).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Vec<serde_json::Value>>(&expected).unwrap(),
+            data
+        );
+
+        let gz = export_to_json(data.clone(), path_in(&dir, "out.json"), options(Some("gzip"), "utf-8")).unwrap();
+        assert!(gz.ends_with("out.json.gz"));
+        assert_eq!(gunzip(&gz), expected);
+
+        let zipped = export_to_json(data, path_in(&dir, "out.json"), options(Some("zip"), "utf-8")).unwrap();
+        assert_eq!(unzip(&zipped), ("out.json".to_string(), expected));
+    }
+
+    #[test]
+    fn dataset_exports_keep_column_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let headers = vec!["z".to_string(), "a".to_string()];
+        let rows: Vec<HashMap<String, serde_json::Value>> = vec![
+            HashMap::from([("z".to_string(), json!(1.5)), ("a".to_string(), json!("x"))]),
+            HashMap::from([("z".to_string(), json!(null)), ("a".to_string(), json!("y"))]),
+        ];
+
+        let csv = write_dataset_csv(
+            &headers,
+            &rows,
+            &path_in(&dir, "d.csv"),
+            &options(Some("gzip"), "utf-8"),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(gunzip(&csv)).unwrap(), "z,a\n1.5,x\n,y\n");
+
+        let json = write_dataset_json(
+            &headers,
+            &rows,
+            &path_in(&dir, "d.json"),
+            &options(Some("zip"), "utf-8"),
+        )
+        .unwrap();
+        let (entry, text) = unzip(&json);
+        assert_eq!(entry, "d.json");
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "[\n{\"z\":1.5,\"a\":\"x\"},\n{\"z\":null,\"a\":\"y\"}\n]\n"
+        );
+    }
+
+    #[test]
+    fn windows_1252_export_reads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "legacy.csv");
+        let data = vec![json!({"name": "café", "city": "Zürich"})];
+        export_to_csv(data, path.clone(), options(None, "windows-1252")).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.contains(&0xE9));
+        let (text, _, had_errors) = encoding_rs::WINDOWS_1252.decode(&bytes);
+        assert!(!had_errors);
+        assert!(text.contains("café") && text.contains("Zürich"));
+    }
+
+    #[test]
+    fn utf16le_export_reads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "wide.csv");
+        export_to_csv(vec![json!({"name": "café"})], path.clone(), options(None, "UTF-16LE")).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..2], &[0xFF, 0xFE]);
+        let (text, had_errors) = encoding_rs::UTF_16LE.decode_with_bom_removal(&bytes);
+        assert!(!had_errors);
+        assert_eq!(text, "name\ncafé\n");
+    }
+
+    #[test]
+    fn unsupported_or_unmappable_encodings_fail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "bad.csv");
+        let error = export_to_csv(rows(1), path.clone(), options(None, "klingon")).unwrap_err();
+        assert!(error.to_string().contains("Unsupported"));
+        let error = export_to_csv(vec![json!({"x": "日本"})], path, options(None, "windows-1252")).unwrap_err();
+        assert!(error.to_string().contains("cannot be represented"), "{}", error);
+    }
+
+    #[test]
+    fn fields_are_quoted_when_they_need_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = vec![json!({"a": "x,y", "b": "say \"hi\"", "c": "two\nlines", "d": "plain"})];
+        let path = path_in(&dir, "quoted.csv");
+        export_to_csv(data.clone(), path.clone(), options(None, "utf-8")).unwrap();
+
+        let expected = "a,b,c,d\n\"x,y\",\"say \"\"hi\"\"\",\"two\nlines\",plain\n";
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+        assert_eq!(preview_export(&data, &options(None, "utf-8"), 2).unwrap(), expected);
+
+        let headers = vec!["a".to_string(), "b".to_string()];
+        let rows = vec![HashMap::from([
+            ("a".to_string(), json!("x,y")),
+            ("b".to_string(), json!(1)),
+        ])];
+        let dataset = write_dataset_csv(&headers, &rows, &path_in(&dir, "d.csv"), &options(None, "utf-8")).unwrap();
+        assert_eq!(std::fs::read_to_string(&dataset).unwrap(), "a,b\n\"x,y\",1\n");
+    }
+
+    #[test]
+    fn characters_split_across_buffered_writes_survive_reencoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "long.csv");
+        let text = "é".repeat(10_000);
+        export_to_csv(vec![json!({"x": text})], path.clone(), options(None, "windows-1252")).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let (decoded, _, had_errors) = encoding_rs::WINDOWS_1252.decode(&bytes);
+        assert!(!had_errors);
+        assert_eq!(decoded, format!("x\n{}\n", text));
+    }
+
+    #[test]
+    fn schema_file_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "schema.json");
+        let schema = json!({"title": "mixed.csv", "properties": {"id": {"type": "number"}}});
+        write_schema_file(&schema, &path).unwrap();
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, schema);
+    }
+
+    #[test]
+    fn nulls_export_as_empty_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "nulls.csv");
+        export_to_csv(
+            vec![json!({"x": 1, "y": null, "z": "s"})],
+            path.clone(),
+            options(None, "utf-8"),
+        )
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "x,y,z\n1,,s\n");
+    }
+
+    #[cfg(feature = "parquet")]
+    fn read_parquet(path: &str) -> arrow::record_batch::RecordBatch {
+        let file = std::fs::File::open(path).unwrap();
+        let mut reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+        reader.next().unwrap().unwrap()
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_round_trip_keeps_types_and_values() {
+        use arrow::array::{Array, Float64Array, Int64Array, TimestampMillisecondArray};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = path_in(&dir, "typed.parquet");
+        let data = vec![
+            json!({"n": 1, "f": 1.5, "b": true, "d": "2024-01-02", "s": "x"}),
+            json!({"n": null, "f": 2, "b": false, "d": "2024-02-03", "s": "y"}),
+        ];
+        export_to_parquet(data, path.clone(), None).unwrap();
+
+        let batch = read_parquet(&path);
+        let types: Vec<String> = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| format!("{:?}", f.data_type()))
+            .collect();
+        assert_eq!(
+            types,
+            ["Boolean", "Timestamp(Millisecond, None)", "Float64", "Int64", "Utf8"]
+        );
+        let dates = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(dates.value(0), 1704153600000);
+        let floats = batch.column(2).as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(floats.value(1), 2.0);
+        let integers = batch.column(3).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(integers.value(0), 1);
+        assert!(integers.is_null(1));
+
+        assert!(export_to_parquet(vec![], path, None).is_err());
+    }
+
+    #[test]
+    fn previews_match_the_head_of_the_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<serde_json::Value> = (0..50)
+            .map(|i| json!({"id": i, "name": format!("n; {}", i), "ok": i % 2 == 0, "x": null}))
+            .collect();
+        for include_headers in [true, false] {
+            let options = ExportOptions {
+                delimiter: ";".to_string(),
+                include_headers,
+                quote_fields: "minimal".to_string(),
+                ..options(None, "utf-8")
+            };
+            let path = path_in(&dir, "preview.csv");
+            export_to_csv(data.clone(), path.clone(), options.clone()).unwrap();
+            let full = std::fs::read_to_string(&path).unwrap();
+
+            for max_lines in [0, 1, 3, 51, 200] {
+                let head: String = full.lines().take(max_lines).map(|l| format!("{}\n", l)).collect();
+                let preview = preview_export(&data, &options, max_lines).unwrap();
+                assert_eq!(preview, head, "headers={} lines={}", include_headers, max_lines);
+            }
+        }
+    }
+
+    #[test]
+    fn previews_fail_like_the_export() {
+        assert!(preview_export(&rows(3), &options(None, "klingon"), 3).is_err());
+        let unmappable = vec![json!({"a": "日本"})];
+        assert!(preview_export(&unmappable, &options(None, "windows-1252"), 3).is_err());
+    }
+
+    fn decimal_comma_options() -> ExportOptions {
+        ExportOptions {
+            delimiter: ";".to_string(),
+            decimal_separator: Some(",".to_string()),
+            ..options(None, "utf-8")
+        }
+    }
+
+    #[test]
+    fn decimal_commas_with_a_semicolon_delimiter() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = vec![
+            json!({"name": "q", "v": 1.25, "n": 42, "s": "1.5"}),
+            json!({"name": "e", "v": -2.5e-7, "n": null, "s": "x"}),
+        ];
+        let path = path_in(&dir, "decimal.csv");
+        export_to_csv(data.clone(), path.clone(), decimal_comma_options()).unwrap();
+
+        // Only numbers are rewritten; numeric-looking strings are left alone
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "n;name;s;v\n42;q;1.5;1,25\n;e;x;-2,5e-7\n"
+        );
+        assert_eq!(
+            preview_export(&data, &decimal_comma_options(), 2).unwrap(),
+            "n;name;s;v\n42;q;1.5;1,25\n"
+        );
+        let dots = ExportOptions {
+            include_headers: false,
+            ..options(None, "utf-8")
+        };
+        assert_eq!(preview_export(&data, &dots, 1).unwrap(), "42,q,1.5,1.25\n");
+    }
+
+    #[test]
+    fn decimal_commas_with_a_comma_delimiter_are_quoted() {
+        let dir = tempfile::tempdir().unwrap();
+        let commas = ExportOptions {
+            delimiter: ",".to_string(),
+            ..decimal_comma_options()
+        };
+        let data = vec![json!({"n": 42, "v": 1.25})];
+        let path = path_in(&dir, "commas.csv");
+        export_to_csv(data.clone(), path.clone(), commas.clone()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "n,v\n42,\"1,25\"\n");
+        assert_eq!(preview_export(&data, &commas, 2).unwrap(), "n,v\n42,\"1,25\"\n");
+    }
+
+    #[test]
+    fn unsupported_decimal_separators_are_rejected() {
+        let unsupported = ExportOptions {
+            decimal_separator: Some("'".to_string()),
+            ..decimal_comma_options()
+        };
+        assert!(preview_export(&rows(2), &unsupported, 2).is_err());
+    }
+
+    const DICTIONARY_CSV: &str =
+        "id,price,color,when\n1,10.5,red,2024-01-01\n2,20,blue|green,2024-02-01\n3,,red,2024-03-01\n";
+
+    #[test]
+    fn json_dictionaries_cover_every_column() {
+        let _globals = globals();
+        let dir = tempfile::tempdir().unwrap();
+        let file = csv_file(DICTIONARY_CSV);
+        let out_path = path_in(&dir, "dictionary.json");
+        let dictionary = export_data_dictionary(path(&file), &out_path, "json", &CsvParseOptions::default()).unwrap();
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&out_path).unwrap()).unwrap();
+
+        let names: Vec<&str> = written["columns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["id", "price", "color", "when"]);
+        assert_eq!(dictionary.columns.len(), 4);
+        assert_eq!(written["row_count"], 3);
+        assert!(written["columns"][1]["numeric_stats"]["mean"].is_number());
+        assert!(written["schema"]["properties"]["when"].is_object());
+        assert!(written["quality"]["overall_score"].is_number());
+    }
+
+    #[test]
+    fn markdown_dictionaries_render_a_row_per_column() {
+        let _globals = globals();
+        let dir = tempfile::tempdir().unwrap();
+        let file = csv_file(DICTIONARY_CSV);
+        let out_path = path_in(&dir, "dictionary.md");
+        export_data_dictionary(path(&file), &out_path, "markdown", &CsvParseOptions::default()).unwrap();
+        let text = std::fs::read_to_string(&out_path).unwrap();
+
+        for name in ["id", "price", "color", "when"] {
+            assert!(
+                text.lines().any(|l| l.starts_with(&format!("| {} |", name))),
+                "{}",
+                name
+            );
+            assert!(text.contains(&format!("### {}\n", name)), "{}", name);
+        }
+        // Pipes in values would otherwise end the table cell
+        assert!(text.contains("blue\\|green"));
+        let html = export_data_dictionary(path(&file), &out_path, "html", &CsvParseOptions::default());
+        assert!(html.is_err());
+    }
+}
@@ -1,16 +1,54 @@
 // Module declarations
 pub mod analysis;
+pub mod error;
 pub mod export;
 pub mod parser;
 pub mod storage;
 pub mod types;
 pub mod utils;
 
+#[cfg(test)]
+mod test_support;
+
 // Re-export public types and functions for external use
-pub use analysis::perform_data_science;
-pub use export::export_to_csv;
+pub use analysis::{
+    analyze_loaded_data, analyze_loaded_data_with_progress, infer_column_date_format, infer_date_format,
+    perform_data_science, perform_data_science_with_progress,
+};
+pub use error::DataScienceError;
+#[cfg(feature = "parquet")]
+pub use export::export_to_parquet;
+pub use export::{export_data_dictionary, export_to_csv, export_to_json, preview_export, write_schema_file};
+#[cfg(feature = "sqlite")]
+pub use parser::read_sqlite_table;
+#[cfg(feature = "xlsx")]
+pub use parser::read_xlsx;
 pub use parser::{
-    analyze_csv_columns, read_csv_file, scan_directory_for_csvs, validate_csv_file, validate_data_quality,
+    analyze_columns, analyze_columns_with_distinct_limit, analyze_csv_columns, analyze_single_column_file,
+    check_dropped_file, check_input_file, collect_columns, collect_columns_cancellable, collect_sampled_columns,
+    count_rows_streaming, describe_csv, detect_key_columns, detect_line_ending, detect_preamble, find_key_columns,
+    infer_schema, normalize_line_endings, profile_csv_streaming, read_csv_file, read_csv_range, read_fixed_width,
+    sanitize_sql_identifier, scan_directory_for_csvs, validate_against_schema, validate_csv_file,
+    validate_data_quality, DEFAULT_MAX_DROPPED_FILE_BYTES, DEFAULT_SAMPLE_SEED, DEFAULT_SAMPLE_SIZE,
+    STREAMING_RESERVOIR_SIZE,
 };
-pub use storage::{get_csv_headers, load_csv_into_store, query_csv_data, unload_csv_data};
+#[cfg(feature = "parquet")]
+pub use storage::export_dataset_to_parquet;
+#[cfg(feature = "xlsx")]
+pub use storage::load_xlsx_into_store;
+pub use storage::{
+    add_computed_column, aggregate_csv_data, analyze_single_column, append_rows_to_store, collect_loaded_columns,
+    count_csv_data, deduplicate_store, export_dataset_to_csv, export_dataset_to_json, get_column_distinct_values,
+    get_column_value_counts, get_csv_headers, join_datasets, list_loaded_csvs, load_and_merge_csvs,
+    load_csv_into_store, load_csv_into_store_with_progress, load_fixed_width_into_store, query_csv_data,
+    reload_csv_in_store, rename_columns, trim_store_columns, unload_all_csv_data, unload_csv_data,
+    PROGRESS_INTERVAL_ROWS, SOURCE_FILE_COLUMN,
+};
+#[cfg(feature = "sqlite")]
+pub use storage::{export_to_sqlite, import_from_sqlite};
 pub use types::*;
+pub use utils::{
+    cancel_task, finish_task, get_default_sample_seed, get_null_tokens, get_numeric_inference_options,
+    parse_formatted_number, register_task, set_default_sample_seed, set_null_tokens, set_numeric_inference_options,
+    CANCELLED_ERROR,
+};
@@ -1,42 +1,88 @@
-use crate::analysis::infer_data_type;
+use crate::analysis::{
+    classify_value, infer_column_data_type, infer_column_date_format, infer_data_type, infer_value_pattern,
+    is_epoch_column, parse_numeric, TypeCounts,
+};
+use crate::error::DataScienceError;
+use crate::storage::PROGRESS_INTERVAL_ROWS;
 use crate::types::*;
-use crate::utils::detect_encoding;
+use crate::utils::{
+    check_cancelled, detect_encoding_report, detect_encoding_with_confidence, get_default_sample_seed, is_null_token,
+    parse_formatted_number, HyperLogLog, SampleRng,
+};
 use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::time::SystemTime;
 
-pub fn read_csv_file(file_path: String) -> Result<CSVContent, String> {
-    // Check if file exists and get metadata
-    let path = Path::new(&file_path);
-    if !path.exists() {
-        return Err("File does not exist".to_string());
-    }
+const CANDIDATE_DELIMITERS: [&str; 4] = [",", ";", "\t", "|"];
+// Text columns with more distinct (case-insensitive) values than this aren't checked for casing
+const CATEGORICAL_MAX_DISTINCT: usize = 20;
+const MAX_DUPLICATE_EXAMPLES: usize = 5;
+// Detections less confident than this produce a validation warning
+const LOW_CONFIDENCE_THRESHOLD: f64 = 0.5;
+// Sample values and most-common entries reported per column unless the caller asks for another count
+pub const DEFAULT_SAMPLE_SIZE: usize = 5;
+// Seed for random sampling when the caller doesn't pick one, so repeated runs agree
+pub const DEFAULT_SAMPLE_SEED: u64 = 0x5EED_CAFE;
+// Narrower files are analyzed serially even with the `parallel` feature; thread-pool overhead isn't worth it
+#[cfg(feature = "parallel")]
+const PARALLEL_COLUMN_THRESHOLD: usize = 16;
+// Bytes inspected when deciding whether a file is binary
+const BINARY_SNIFF_BYTES: usize = 8_192;
+// Rows checked for a consistent field count during validation
+const CONSISTENCY_SAMPLE_ROWS: usize = 100;
+// A lone line longer than this with no delimiter isn't treated as CSV
+const MAX_UNSPLIT_LINE_LENGTH: usize = 4_096;
+// Non-null values kept per column by `profile_csv_streaming`
+pub const STREAMING_RESERVOIR_SIZE: usize = 1_000;
+// Columns considered when searching for a composite primary key
+const MAX_COMPOSITE_KEY_COLUMNS: usize = 12;
+// Largest drag-and-drop payload `check_dropped_file` accepts unless the caller sets another limit
+pub const DEFAULT_MAX_DROPPED_FILE_BYTES: usize = 512 * 1024 * 1024;
+// Columns where at least this share of the values is one value are flagged as near-constant
+pub const NEAR_CONSTANT_RATIO: f64 = 0.99;
+// Most distinct values listed in a non-numeric column's `ColumnDetail::value_counts`
+const MAX_DETAIL_VALUE_COUNTS: usize = 100;
+// Row names of `describe_csv`, in pandas' order
+const DESCRIBE_STATISTICS: [&str; 11] = [
+    "count", "unique", "top", "freq", "mean", "std", "min", "25%", "50%", "75%", "max",
+];
+// Rows below the first compared with it to detect a header row
+const HEADER_SAMPLE_ROWS: usize = 20;
+// Bytes read from the start of the file to detect the delimiter for a byte range
+const RANGE_SNIFF_BYTES: u64 = 64 * 1024;
 
-    let metadata = fs::metadata(&file_path).map_err(|e| format!("Failed to read file metadata: {}", e))?;
+pub fn read_csv_file(file_path: String, options: &CsvParseOptions) -> Result<CSVContent, DataScienceError> {
+    let path = Path::new(&file_path);
+    let metadata = check_input_file(&file_path)?;
 
     let file_size = metadata.len();
     let modified: DateTime<Utc> = metadata
         .modified()
         .map_err(|e| format!("Failed to get modification time: {}", e))?
         .into();
-    let created: DateTime<Utc> = metadata
-        .created()
-        .map_err(|e| format!("Failed to get creation time: {}", e))?
-        .into();
+    let created = creation_time(metadata.created(), modified)?;
 
     // Detect encoding
-    let content_bytes = fs::read(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let content_bytes = read_csv_bytes(&file_path)?;
 
-    let encoding_name = detect_encoding(&content_bytes);
+    let encoding_report = detect_encoding_report(&content_bytes);
 
     // Convert to UTF-8 if needed
-    let content = String::from_utf8_lossy(&content_bytes).to_string();
+    let content = normalize_line_endings(&decode_file_text(&content_bytes, true)?);
+    let content = skip_preamble(&content, options);
 
     // Detect delimiter
-    let delimiter = detect_delimiter(&content)?;
+    let delimiter = resolve_delimiter(content, options.delimiter_override.as_deref())?;
 
-    // Estimate rows
-    let estimated_rows = content.lines().count().saturating_sub(1); // Subtract header row
+    // Estimate rows, counting a quoted field's embedded newlines as part of its row
+    let records =
+        count_records(content.as_bytes(), options).map_err(|e| DataScienceError::io("read", &file_path, e))?;
+    let estimated_rows = (records as usize).saturating_sub(1); // Subtract header row
 
     let file_metadata = FileMetadata {
         size: file_size,
@@ -50,64 +96,253 @@ pub fn read_csv_file(file_path: String) -> Result<CSVContent, String> {
     Ok(CSVContent {
         content: content.to_string(),
         metadata: file_metadata,
-        encoding: encoding_name,
+        encoding: encoding_report.encoding.clone(),
         estimated_rows,
         can_process: true,
         file_size: file_size as usize,
         delimiter: Some(delimiter),
+        encoding_report,
     })
 }
 
-pub fn validate_csv_file(file_path: String) -> Result<CsvValidationResult, String> {
-    let content = fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+// Many Linux filesystems don't record creation time; report the modified time there instead
+fn creation_time(
+    created: std::io::Result<SystemTime>,
+    modified: DateTime<Utc>,
+) -> Result<DateTime<Utc>, DataScienceError> {
+    match created {
+        Ok(created) => Ok(created.into()),
+        Err(e) if e.kind() == ErrorKind::Unsupported => Ok(modified),
+        Err(e) => Err(format!("Failed to get creation time: {}", e).into()),
+    }
+}
+
+/// Check that a file looks like usable CSV and detect how to parse it.
+///
+/// Files that can't be read, or are empty, are errors. Files that read fine but
+/// aren't usable CSV (binary content, rows with wildly inconsistent field
+/// counts, or one huge line with no delimiter) get `is_valid: false` and an
+/// `invalid_reason`.
+pub fn validate_csv_file(
+    file_path: String,
+    options: &CsvParseOptions,
+) -> Result<CsvValidationResult, DataScienceError> {
+    let content_bytes = read_csv_bytes(&file_path)?;
+    if looks_binary(&content_bytes) {
+        return Ok(invalid_validation("File appears to be binary, not text".to_string()));
+    }
+    let encoding_detection = detect_encoding_with_confidence(&content_bytes);
+    let encoding_report = detect_encoding_report(&content_bytes);
+    let content = decode_file_text(&content_bytes, false)?;
+    let line_ending = detect_line_ending(&content).to_string();
+    let content = normalize_line_endings(&content);
+    let content = skip_preamble(&content, options);
 
-    let delimiter = detect_delimiter(&content)?;
+    let delimiter_detection = resolve_delimiter_detection(content, options.delimiter_override.as_deref())?;
+    let delimiter = delimiter_detection.value.clone();
+    check_quoting(options, &delimiter)?;
     let lines: Vec<&str> = content.lines().collect();
 
     if lines.is_empty() {
-        return Err("File is empty".to_string());
+        return Err("File is empty".to_string().into());
     }
 
-    // Detect encoding
-    let content_bytes = fs::read(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
-    let encoding_name = detect_encoding(&content_bytes);
-
-    // Check for headers (simple heuristic: if first row contains strings that look like headers)
+    // Check for headers (simple heuristic unless the caller says otherwise)
     let first_row = lines[0];
-    let column_count = first_row.split(&delimiter).count();
-    let has_headers = is_likely_header_row(first_row, &delimiter);
+    let column_count = csv_records(content.as_bytes(), &delimiter, true, options)?.0.len();
+    let has_headers = resolve_has_headers(&lines, &delimiter, options);
 
-    let estimated_rows = if has_headers && lines.len() > 1 {
-        (lines.len() - 1) as u64
+    let records =
+        count_records(content.as_bytes(), options).map_err(|e| DataScienceError::io("read", &file_path, e))?;
+    let estimated_rows = records.saturating_sub(has_headers as u64);
+
+    let invalid_reason = if lines.len() == 1 && column_count == 1 && first_row.len() > MAX_UNSPLIT_LINE_LENGTH {
+        Some(format!(
+            "File is a single {}-character line with no delimiter",
+            first_row.chars().count()
+        ))
     } else {
-        lines.len() as u64
+        inconsistent_rows_reason(content, &delimiter, column_count, has_headers, options)
     };
 
+    let mut warnings = Vec::new();
+    if delimiter_detection.confidence < LOW_CONFIDENCE_THRESHOLD {
+        let alternative = delimiter_detection
+            .runner_up
+            .as_deref()
+            .map(|d| format!(" ('{}' also fits)", display_delimiter(d)))
+            .unwrap_or_default();
+        warnings.push(format!(
+            "Delimiter detection is uncertain{}; confirm that '{}' is correct or set a delimiter override",
+            alternative,
+            display_delimiter(&delimiter)
+        ));
+    }
+    if encoding_detection.confidence < LOW_CONFIDENCE_THRESHOLD {
+        warnings.push(format!(
+            "Encoding detection is uncertain; the file may not be {}",
+            encoding_detection.value
+        ));
+    }
+
     Ok(CsvValidationResult {
-        is_valid: true,
+        is_valid: invalid_reason.is_none(),
+        invalid_reason,
         delimiter,
         estimated_rows,
-        encoding: encoding_name,
+        encoding: encoding_detection.value.clone(),
         has_headers,
         column_count,
+        line_ending,
+        delimiter_detection,
+        encoding_detection,
+        encoding_report,
+        warnings,
+    })
+}
+
+// Text of a file: decoded by its byte order mark, which is dropped, if it has one and as UTF-8
+// otherwise. Invalid UTF-8 is an error unless `lossy` is set.
+fn decode_file_text(bytes: &[u8], lossy: bool) -> Result<String, DataScienceError> {
+    if let Some((encoding, bom_length)) = encoding_rs::Encoding::for_bom(bytes) {
+        return Ok(encoding
+            .decode_without_bom_handling(&bytes[bom_length..])
+            .0
+            .into_owned());
+    }
+    if lossy {
+        return Ok(String::from_utf8_lossy(bytes).into_owned());
+    }
+    std::str::from_utf8(bytes)
+        .map(str::to_string)
+        .map_err(|e| DataScienceError::Parse(format!("Failed to read file: {}", e)))
+}
+
+/// Vet a drag-and-dropped payload before it's written to disk, returning a safe file name for it.
+///
+/// `filename` is cut down to its last `/` or `\` component, so names like `../x.csv` can't
+/// escape the directory it's saved in. Empty payloads, payloads over `max_bytes`, and content
+/// that looks binary are rejected; gzip data is let through so `.csv.gz` files can be dropped.
+pub fn check_dropped_file(file_data: &[u8], filename: &str, max_bytes: usize) -> Result<String, DataScienceError> {
+    let base_name: String = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_control() || ":*?\"<>|".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if base_name.trim_matches(['.', ' ']).is_empty() {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "'{}' is not a valid file name",
+            filename
+        )));
+    }
+    if file_data.is_empty() {
+        return Err(DataScienceError::InvalidArgument(format!("'{}' is empty", base_name)));
+    }
+    if file_data.len() > max_bytes {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "'{}' is {} bytes, over the {} byte limit for dropped files",
+            base_name,
+            file_data.len(),
+            max_bytes
+        )));
+    }
+    if !file_data.starts_with(&[0x1f, 0x8b]) && looks_binary(file_data) {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "'{}' doesn't look like a CSV or text file",
+            base_name
+        )));
+    }
+    Ok(base_name)
+}
+
+// NUL bytes, or many control characters, in the first block mean this isn't a text file
+fn looks_binary(bytes: &[u8]) -> bool {
+    // UTF-16 text is full of NULs but is still text
+    if bytes.starts_with(&[0xFF, 0xFE]) || bytes.starts_with(&[0xFE, 0xFF]) {
+        return false;
+    }
+    let head = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C))
+        .count();
+    head.contains(&0) || control * 10 > head.len()
+}
+
+// Invalid when fewer than half of the sampled data rows have the header's field count
+fn inconsistent_rows_reason(
+    content: &str,
+    delimiter: &str,
+    column_count: usize,
+    has_headers: bool,
+    options: &CsvParseOptions,
+) -> Option<String> {
+    let delimiter = match delimiter_byte(delimiter) {
+        Ok(byte) => byte,
+        Err(e) => return Some(e.to_string()),
+    };
+    let mut reader = csv_reader_builder(delimiter, options)
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let field_counts: Vec<usize> = reader
+        .records()
+        .take(CONSISTENCY_SAMPLE_ROWS)
+        .map(|record| record.map_or(0, |r| r.len()))
+        .collect();
+
+    let matching = field_counts.iter().filter(|&&count| count == column_count).count();
+    (field_counts.len() >= 2 && matching * 2 < field_counts.len()).then(|| {
+        format!(
+            "Only {} of the first {} rows have the header's {} fields",
+            matching,
+            field_counts.len(),
+            column_count
+        )
     })
 }
 
-pub fn scan_directory_for_csvs(dir_path: String) -> Result<Vec<CsvFileInfo>, String> {
+// Result for a file that could be read but isn't usable CSV
+fn invalid_validation(reason: String) -> CsvValidationResult {
+    CsvValidationResult {
+        is_valid: false,
+        invalid_reason: Some(reason),
+        delimiter: ",".to_string(),
+        estimated_rows: 0,
+        encoding: "unknown".to_string(),
+        has_headers: false,
+        column_count: 0,
+        line_ending: "unknown".to_string(),
+        delimiter_detection: DetectionResult::default(),
+        encoding_detection: DetectionResult::default(),
+        encoding_report: EncodingReport::default(),
+        warnings: Vec::new(),
+    }
+}
+
+pub fn scan_directory_for_csvs(dir_path: String) -> Result<Vec<CsvFileInfo>, DataScienceError> {
     let path = Path::new(&dir_path);
     if !path.is_dir() {
-        return Err("Path is not a directory".to_string());
+        return Err(DataScienceError::InvalidArgument("Path is not a directory".to_string()));
     }
 
     let mut csv_files = Vec::new();
 
-    for entry in fs::read_dir(path).map_err(|e| format!("Failed to read directory: {}", e))? {
+    for entry in fs::read_dir(path).map_err(|e| DataScienceError::io("read directory", &dir_path, e))? {
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
         let file_path = entry.path();
 
-        // Check if it's a CSV file
+        // Check if it's a CSV file (plain or gzip-compressed)
         if let Some(extension) = file_path.extension() {
-            if extension.to_str().unwrap_or("").to_lowercase() == "csv" {
+            if extension.to_str().unwrap_or("").to_lowercase() == "csv" || is_csv_gz_path(&file_path) {
                 let metadata = entry
                     .metadata()
                     .map_err(|e| format!("Failed to read metadata: {}", e))?;
@@ -121,17 +356,8 @@ pub fn scan_directory_for_csvs(dir_path: String) -> Result<Vec<CsvFileInfo>, Str
                 let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
 
                 // Validate the CSV file
-                let validation_result = match validate_csv_file(file_path_str.clone()) {
-                    Ok(result) => result,
-                    Err(_e) => CsvValidationResult {
-                        is_valid: false,
-                        delimiter: ",".to_string(),
-                        estimated_rows: 0,
-                        encoding: "unknown".to_string(),
-                        has_headers: false,
-                        column_count: 0,
-                    },
-                };
+                let validation_result = validate_csv_file(file_path_str.clone(), &CsvParseOptions::default())
+                    .unwrap_or_else(|e| invalid_validation(e.to_string()));
 
                 csv_files.push(CsvFileInfo {
                     path: file_path_str,
@@ -147,177 +373,3194 @@ pub fn scan_directory_for_csvs(dir_path: String) -> Result<Vec<CsvFileInfo>, Str
     Ok(csv_files)
 }
 
-pub fn analyze_csv_columns(file_path: String) -> Result<Vec<ColumnAnalysis>, String> {
-    let content = fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+pub fn analyze_csv_columns(
+    file_path: String,
+    options: &CsvParseOptions,
+    sample_size: usize,
+    distinct_limit: Option<usize>,
+) -> Result<Vec<ColumnAnalysis>, DataScienceError> {
+    let (headers, columns) = collect_columns(file_path, options)?;
+    Ok(analyze_columns_with_distinct_limit(
+        &headers,
+        &columns,
+        sample_size,
+        distinct_limit,
+    ))
+}
 
-    let delimiter = detect_delimiter(&content)?;
-    let lines: Vec<&str> = content.lines().collect();
+/// Analyze one column of a file, keeping only that column's values in memory.
+///
+/// Rows are read as `analyze_csv_columns` reads them, so `analysis` matches
+/// that column's entry there.
+pub fn analyze_single_column_file(
+    file_path: &str,
+    column: &str,
+    options: &CsvParseOptions,
+) -> Result<ColumnDetail, DataScienceError> {
+    let (headers, rows) = stream_csv_rows(file_path, options)?;
+    let index = headers
+        .iter()
+        .position(|h| h == column)
+        .ok_or_else(|| DataScienceError::ColumnNotFound(column.to_string()))?;
 
-    if lines.is_empty() {
-        return Err("File is empty".to_string());
+    let mut values = Vec::new();
+    for row in rows {
+        let mut row = row?;
+        if row.len() == headers.len() {
+            values.push(row.swap_remove(index));
+        }
     }
+    Ok(column_detail(column, &values, DEFAULT_SAMPLE_SIZE))
+}
 
-    let first_row = lines[0];
-    let headers: Vec<String> = first_row.split(&delimiter).map(|s| s.trim().to_string()).collect();
+/// `ColumnAnalysis` of one column, plus numeric statistics for number columns
+/// or value counts for the rest.
+pub fn column_detail(header: &str, values: &[String], sample_size: usize) -> ColumnDetail {
+    let analysis = analyze_column(header, values, values.len(), sample_size, None);
+    if analysis.data_type == "number" {
+        let numbers: Vec<f64> = values.iter().filter_map(|v| parse_numeric(v)).collect();
+        ColumnDetail {
+            analysis,
+            numeric_stats: numeric_stats(numbers),
+            value_counts: None,
+        }
+    } else {
+        let non_null: Vec<&String> = values.iter().filter(|v| !is_null_token(v)).collect();
+        let mut counts = most_common_values(&non_null);
+        counts.truncate(MAX_DETAIL_VALUE_COUNTS);
+        ColumnDetail {
+            analysis,
+            numeric_stats: None,
+            value_counts: Some(counts),
+        }
+    }
+}
 
-    let mut column_data: Vec<Vec<String>> = vec![Vec::new(); headers.len()];
-    let mut total_rows = 0;
+fn numeric_stats(mut numbers: Vec<f64>) -> Option<NumericStats> {
+    if numbers.is_empty() {
+        return None;
+    }
+    numbers.sort_by(f64::total_cmp);
+    let count = numbers.len();
+    let sum: f64 = numbers.iter().sum();
+    let mean = sum / count as f64;
+    let median = if count % 2 == 1 {
+        numbers[count / 2]
+    } else {
+        (numbers[count / 2 - 1] + numbers[count / 2]) / 2.0
+    };
+    let std_dev =
+        (count > 1).then(|| (numbers.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1) as f64).sqrt());
+    Some(NumericStats {
+        count,
+        min: numbers[0],
+        max: numbers[count - 1],
+        sum,
+        mean,
+        median,
+        q1: quantile(&numbers, 0.25),
+        q3: quantile(&numbers, 0.75),
+        std_dev,
+    })
+}
 
-    // Collect data for each column
-    for line in lines.iter().skip(1) {
-        let values: Vec<String> = line.split(&delimiter).map(|s| s.trim().to_string()).collect();
+// The `q` quantile of sorted, non-empty values, interpolating linearly between neighbours like pandas
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
 
-        if values.len() == headers.len() {
-            for (i, value) in values.iter().enumerate() {
-                if i < column_data.len() {
-                    column_data[i].push(value.clone());
-                }
-            }
-            total_rows += 1;
+/// Summarize a file's columns like pandas' `DataFrame.describe(include="all")`.
+///
+/// Number columns get count, mean, std, min, 25%, 50%, 75%, and max; other
+/// columns get count, unique, top, and freq. `count` excludes nulls. The
+/// result is pandas' "split" layout: `columns`, `index` (the statistic names,
+/// only those some column has), and `data`, one row per statistic with a
+/// value per column, null where the statistic doesn't apply.
+pub fn describe_csv(file_path: &str, options: &CsvParseOptions) -> Result<serde_json::Value, DataScienceError> {
+    let (headers, columns) = collect_columns(file_path.to_string(), options)?;
+    let described: Vec<HashMap<&str, serde_json::Value>> = headers
+        .iter()
+        .zip(&columns)
+        .map(|(header, values)| describe_column(header, values))
+        .collect();
+
+    let has_stat = |stat: &str| described.iter().any(|column| column.contains_key(stat));
+    let index: Vec<&str> = DESCRIBE_STATISTICS.into_iter().filter(|stat| has_stat(stat)).collect();
+    let data: Vec<Vec<serde_json::Value>> = index
+        .iter()
+        .map(|stat| {
+            described
+                .iter()
+                .map(|column| column.get(stat).cloned().unwrap_or(serde_json::Value::Null))
+                .collect()
+        })
+        .collect();
+
+    Ok(serde_json::json!({ "columns": headers, "index": index, "data": data }))
+}
+
+// One column's `describe_csv` statistics, keyed by their row names
+fn describe_column<'a>(header: &str, values: &[String]) -> HashMap<&'a str, serde_json::Value> {
+    let detail = column_detail(header, values, 0);
+    if let Some(stats) = detail.numeric_stats {
+        return HashMap::from([
+            ("count", stats.count.into()),
+            ("mean", stats.mean.into()),
+            ("std", stats.std_dev.into()),
+            ("min", stats.min.into()),
+            ("25%", stats.q1.into()),
+            ("50%", stats.median.into()),
+            ("75%", stats.q3.into()),
+            ("max", stats.max.into()),
+        ]);
+    }
+
+    let non_null: Vec<&String> = values.iter().filter(|v| !is_null_token(v)).collect();
+    let counts = most_common_values(&non_null);
+    let (top, freq) = counts
+        .first()
+        .map_or((serde_json::Value::Null, serde_json::Value::Null), |(value, count)| {
+            (value.as_str().into(), (*count).into())
+        });
+    HashMap::from([
+        ("count", non_null.len().into()),
+        ("unique", counts.len().into()),
+        ("top", top),
+        ("freq", freq),
+    ])
+}
+
+/// Find the column(s) that uniquely identify every row of a file.
+///
+/// See `find_key_columns` for how candidates are chosen.
+pub fn detect_key_columns(file_path: String, options: &CsvParseOptions) -> Result<Vec<Vec<String>>, DataScienceError> {
+    let (headers, columns) = collect_columns(file_path, options)?;
+    Ok(find_key_columns(&headers, &columns))
+}
+
+/// Candidate primary keys, each a list of column names.
+///
+/// A key column has no missing and no repeated values. Every single-column key
+/// is returned; only when there is none are combinations of 2, then 3, columns
+/// tried, returning all combinations of the smallest size that works. Files
+/// with fewer than two rows have no meaningful key.
+pub fn find_key_columns(headers: &[String], columns: &[Vec<String>]) -> Vec<Vec<String>> {
+    let row_count = columns.first().map_or(0, Vec::len);
+    if row_count < 2 {
+        return Vec::new();
+    }
+
+    let complete: Vec<usize> = (0..columns.len())
+        .filter(|&i| !columns[i].iter().any(|value| is_null_token(value)))
+        .collect();
+    let key_names = |combination: &[usize]| combination.iter().map(|&i| headers[i].clone()).collect::<Vec<_>>();
+
+    let single_keys: Vec<Vec<String>> = complete
+        .iter()
+        .filter(|&&i| is_unique_combination(columns, &[i]))
+        .map(|&i| key_names(&[i]))
+        .collect();
+    if !single_keys.is_empty() {
+        return single_keys;
+    }
+
+    // The composite search grows combinatorially, so only the most distinct columns take part
+    let mut candidates = complete;
+    candidates.sort_by_key(|&i| std::cmp::Reverse(columns[i].iter().collect::<std::collections::HashSet<_>>().len()));
+    candidates.truncate(MAX_COMPOSITE_KEY_COLUMNS);
+    candidates.sort_unstable();
+
+    for size in 2..=3 {
+        let keys: Vec<Vec<String>> = combinations(&candidates, size)
+            .iter()
+            .filter(|combination| is_unique_combination(columns, combination))
+            .map(|combination| key_names(combination))
+            .collect();
+        if !keys.is_empty() {
+            return keys;
+        }
+    }
+    Vec::new()
+}
+
+fn is_unique_combination(columns: &[Vec<String>], combination: &[usize]) -> bool {
+    let row_count = columns.first().map_or(0, Vec::len);
+    let mut seen = std::collections::HashSet::with_capacity(row_count);
+    (0..row_count).all(|row| seen.insert(combination.iter().map(|&i| columns[i][row].trim()).collect::<Vec<_>>()))
+}
+
+// Every `size`-element subset of `items`, preserving their order
+fn combinations(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    let mut result = Vec::new();
+    for (i, &first) in items.iter().enumerate() {
+        for rest in combinations(&items[i + 1..], size - 1) {
+            let mut combination = vec![first];
+            combination.extend(rest);
+            result.push(combination);
         }
     }
+    result
+}
 
-    let mut analyses = Vec::new();
+/// Describe a file's columns as a JSON-Schema-like document.
+///
+/// `properties` maps each column to a JSON Schema type (plus `"null"` when the
+/// column has missing values), a `format` for RFC 3339 dates and recognized
+/// text patterns, and the chrono `x-date-format` for any date column. Because object keys aren't ordered, `x-columns` repeats the manifest
+/// in file order with the raw inferred `type`, `nullable`, `date_format`, and `pattern`.
+pub fn infer_schema(file_path: String, options: &CsvParseOptions) -> Result<serde_json::Value, DataScienceError> {
+    let title = Path::new(&file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (headers, columns) = collect_columns(file_path, options)?;
+    let analyses = analyze_columns(&headers, &columns, 0);
 
-    for (i, header) in headers.iter().enumerate() {
-        let column_values = &column_data[i];
-        let null_count = column_values.iter().filter(|v| v.is_empty() || *v == "").count();
-        let null_percentage = if total_rows > 0 {
-            (null_count as f64 / total_rows as f64) * 100.0
+    let mut properties = serde_json::Map::new();
+    let mut manifest = Vec::with_capacity(analyses.len());
+    for (analysis, values) in analyses.iter().zip(&columns) {
+        let pattern = if analysis.data_type == "string" {
+            infer_value_pattern(values)
         } else {
-            0.0
+            None
+        };
+
+        let json_type = match (analysis.data_type.as_str(), analysis.date_format.as_deref()) {
+            ("number", _) | ("date", Some("%s")) => "number",
+            ("boolean", _) => "boolean",
+            ("unknown", _) => "null",
+            _ => "string",
+        };
+        let mut property = serde_json::json!({
+            "type": if analysis.nullable && json_type != "null" {
+                serde_json::json!([json_type, "null"])
+            } else {
+                serde_json::json!(json_type)
+            },
+        });
+        // Only RFC 3339 layouts map onto JSON Schema formats; other dates keep just their chrono format
+        let format = match (analysis.date_format.as_deref(), pattern) {
+            (Some("%Y-%m-%d"), _) => Some("date"),
+            (Some("%+"), _) => Some("date-time"),
+            (Some(_), _) => None,
+            (None, Some("url")) => Some("uri"),
+            (None, pattern) => pattern,
         };
+        if let Some(format) = format {
+            property["format"] = serde_json::json!(format);
+        }
+        if let Some(date_format) = &analysis.date_format {
+            property["x-date-format"] = serde_json::json!(date_format);
+        }
+        properties.insert(analysis.name.clone(), property);
 
-        let unique_values: std::collections::HashSet<String> = column_values.iter().cloned().collect();
-        let unique_count = unique_values.len();
+        manifest.push(serde_json::json!({
+            "name": analysis.name,
+            "type": analysis.data_type,
+            "nullable": analysis.nullable,
+            "date_format": analysis.date_format,
+            "pattern": pattern,
+        }));
+    }
 
-        let data_type = infer_data_type(column_values);
+    Ok(serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": title,
+        "type": "object",
+        "properties": properties,
+        "required": headers,
+        "x-columns": manifest,
+    }))
+}
 
-        let sample_values = column_values.iter().take(5).cloned().collect();
+/// Check a file against a schema produced by `infer_schema`.
+///
+/// Reports columns missing from the file or absent from the schema, rows with
+/// the wrong number of fields, values of the wrong type or format, and nulls in
+/// columns whose type doesn't allow `"null"`. Only the first `max_violations`
+/// are listed; `violation_count` still counts them all.
+pub fn validate_against_schema(
+    file_path: String,
+    options: &CsvParseOptions,
+    schema: &serde_json::Value,
+    max_violations: usize,
+) -> Result<SchemaValidationReport, DataScienceError> {
+    let properties = schema
+        .get("properties")
+        .and_then(serde_json::Value::as_object)
+        .ok_or_else(|| "Schema has no 'properties' object".to_string())?;
+    let required: Vec<String> = match schema.get("required").and_then(serde_json::Value::as_array) {
+        Some(required) => required.iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+        None => properties.keys().cloned().collect(),
+    };
 
-        analyses.push(ColumnAnalysis {
-            name: header.clone(),
-            data_type,
-            nullable: null_count > 0,
-            unique_count,
-            null_percentage,
-            sample_values,
+    let content = read_csv_text(&file_path)?;
+    let content = skip_preamble(&content, options);
+    let delimiter = resolve_delimiter(content, options.delimiter_override.as_deref())?;
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return Err("File is empty".to_string().into());
+    }
+
+    let has_headers = resolve_has_headers(&lines, &delimiter, options);
+    let (headers, records) = csv_records(content.as_bytes(), &delimiter, has_headers, options)?;
+
+    let mut violations = Vec::new();
+    let mut violation_count = 0;
+    let mut report = |violation: SchemaViolation| {
+        violation_count += 1;
+        if violations.len() < max_violations {
+            violations.push(violation);
+        }
+    };
+
+    let missing_columns: Vec<String> = required.iter().filter(|c| !headers.contains(c)).cloned().collect();
+    let extra_columns: Vec<String> = headers
+        .iter()
+        .filter(|h| !properties.contains_key(h.as_str()))
+        .cloned()
+        .collect();
+    for (column, kind, message) in missing_columns
+        .iter()
+        .map(|c| (c, "missing_column", "Column required by the schema is missing"))
+        .chain(
+            extra_columns
+                .iter()
+                .map(|c| (c, "extra_column", "Column is not in the schema")),
+        )
+    {
+        report(SchemaViolation {
+            line: None,
+            column: Some(column.clone()),
+            kind: kind.to_string(),
+            value: None,
+            message: message.to_string(),
         });
     }
 
-    Ok(analyses)
+    let checks: Vec<(usize, &String, &serde_json::Value)> = headers
+        .iter()
+        .enumerate()
+        .filter_map(|(i, h)| properties.get(h).map(|property| (i, h, property)))
+        .collect();
+    let preamble_lines = preamble_line_count(options) as u64;
+    let mut rows_checked = 0;
+
+    for record in records {
+        let record = record?;
+        // Positions are 1-based lines of the content after the preamble
+        let line_number = preamble_lines + record.position().map_or(0, |p| p.line());
+        let values: Vec<&str> = record.iter().map(str::trim).collect();
+        rows_checked += 1;
+
+        if values.len() != headers.len() {
+            report(SchemaViolation {
+                line: Some(line_number),
+                column: None,
+                kind: "field_count".to_string(),
+                value: Some(record.iter().collect::<Vec<_>>().join(&delimiter)),
+                message: format!("Expected {} fields but found {}", headers.len(), values.len()),
+            });
+            continue;
+        }
+
+        for &(i, column, property) in &checks {
+            if let Some((kind, message)) = schema_value_violation(values[i], property) {
+                report(SchemaViolation {
+                    line: Some(line_number),
+                    column: Some(column.clone()),
+                    kind: kind.to_string(),
+                    value: Some(values[i].to_string()),
+                    message,
+                });
+            }
+        }
+    }
+
+    Ok(SchemaValidationReport {
+        is_valid: violation_count == 0,
+        rows_checked,
+        missing_columns,
+        extra_columns,
+        violations,
+        violation_count,
+        truncated: violation_count > max_violations,
+    })
 }
 
-pub fn validate_data_quality(file_path: String) -> Result<DataQualityReport, String> {
-    let content = fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+// Why a single cell breaks its schema property, if it does
+fn schema_value_violation(value: &str, property: &serde_json::Value) -> Option<(&'static str, String)> {
+    let types: Vec<&str> = match property.get("type") {
+        Some(serde_json::Value::String(t)) => vec![t.as_str()],
+        Some(serde_json::Value::Array(ts)) => ts.iter().filter_map(serde_json::Value::as_str).collect(),
+        _ => Vec::new(),
+    };
 
-    let delimiter = detect_delimiter(&content)?;
-    let lines: Vec<&str> = content.lines().collect();
+    if is_null_token(value) {
+        return (!types.is_empty() && !types.contains(&"null"))
+            .then(|| ("unexpected_null", "Null value in a non-nullable column".to_string()));
+    }
 
-    if lines.is_empty() {
-        return Err("File is empty".to_string());
+    let actual = classify_value(value);
+    let type_ok = types.is_empty()
+        || types.iter().any(|t| match *t {
+            "number" => actual == "number",
+            "integer" => actual == "number" && value.parse::<i64>().is_ok(),
+            "boolean" => actual == "boolean",
+            "string" => true,
+            _ => false,
+        });
+    if !type_ok {
+        return Some((
+            "wrong_type",
+            format!("Expected {} but found {}", types.join(" or "), actual),
+        ));
     }
 
-    let mut issues = Vec::new();
-    let mut recommendations = Vec::new();
-    let mut duplicate_count = 0;
+    if let Some(date_format) = property.get("x-date-format").and_then(serde_json::Value::as_str) {
+        if !matches_date_format(value, date_format) {
+            return Some(("wrong_type", format!("Expected a date in the format {}", date_format)));
+        }
+    } else if let Some(format) = property.get("format").and_then(serde_json::Value::as_str) {
+        let pattern = if format == "uri" { "url" } else { format };
+        let values = [value.to_string()];
+        if matches!(pattern, "email" | "url" | "uuid" | "ipv4") && infer_value_pattern(&values) != Some(pattern) {
+            return Some(("wrong_type", format!("Expected a value in {} format", format)));
+        }
+    }
 
-    // Check for duplicate rows
-    let mut seen_rows = std::collections::HashSet::new();
-    for (_i, line) in lines.iter().enumerate().skip(1) {
-        // Skip header
-        if !seen_rows.insert(*line) {
-            duplicate_count += 1;
+    None
+}
+
+fn matches_date_format(value: &str, format: &str) -> bool {
+    match format {
+        "%s" => value.parse::<i64>().is_ok(),
+        "%+" => DateTime::parse_from_rfc3339(value).is_ok(),
+        _ => {
+            chrono::NaiveDateTime::parse_from_str(value, format).is_ok()
+                || chrono::NaiveDate::parse_from_str(value, format).is_ok()
         }
     }
+}
 
-    // Check for completeness
-    let first_row = lines[0];
-    let column_count = first_row.split(&delimiter).count();
-    let mut total_cells = 0;
-    let mut empty_cells = 0;
+/// Read a fixed-width file, slicing each line into fields of `field_widths` characters.
+///
+/// Fields are trimmed, and lines shorter than the full width get empty
+/// trailing fields; characters past the last field are ignored. Without
+/// `headers` the first line is read as the header row. Blank lines are skipped.
+pub fn read_fixed_width(
+    file_path: String,
+    field_widths: &[usize],
+    headers: Option<Vec<String>>,
+) -> Result<(Vec<String>, Vec<Vec<String>>), DataScienceError> {
+    if field_widths.is_empty() || field_widths.contains(&0) {
+        return Err(DataScienceError::InvalidArgument(
+            "Field widths must be non-empty and greater than zero".to_string(),
+        ));
+    }
+    if let Some(names) = headers.as_ref().filter(|names| names.len() != field_widths.len()) {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Expected {} header names for {} fields but got {}",
+            field_widths.len(),
+            field_widths.len(),
+            names.len()
+        )));
+    }
+    check_input_file(&file_path)?;
 
-    for line in lines.iter().skip(1) {
-        let cells: Vec<&str> = line.split(&delimiter).collect();
-        total_cells += column_count;
-        empty_cells += cells.iter().filter(|cell| cell.trim().is_empty()).count();
+    let content = normalize_line_endings(&String::from_utf8_lossy(&read_csv_bytes(&file_path)?));
+    let mut lines = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| split_fixed_width(line, field_widths));
+
+    let headers = match headers {
+        Some(headers) => headers,
+        None => lines.next().ok_or("File is empty")?,
+    };
+
+    Ok((headers, lines.collect()))
+}
+
+/// Read every row of a SQLite table as text, with NULL as an empty string.
+#[cfg(feature = "sqlite")]
+pub fn read_sqlite_table(db_path: &str, table_name: &str) -> Result<(Vec<String>, Vec<Vec<String>>), DataScienceError> {
+    use rusqlite::types::ValueRef;
+
+    check_input_file(db_path)?;
+    let connection = rusqlite::Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open SQLite database: {}", e))?;
+
+    let mut statement = connection
+        .prepare(&format!("SELECT * FROM \"{}\"", table_name.replace('"', "\"\"")))
+        .map_err(|e| format!("Failed to read table '{}': {}", table_name, e))?;
+    let headers: Vec<String> = statement.column_names().iter().map(|name| name.to_string()).collect();
+
+    let mut rows = statement
+        .query([])
+        .map_err(|e| format!("Failed to read table '{}': {}", table_name, e))?;
+    let mut raw_data = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| format!("Failed to read row: {}", e))? {
+        let values = (0..headers.len())
+            .map(|i| {
+                Ok(match row.get_ref(i)? {
+                    ValueRef::Null => String::new(),
+                    ValueRef::Integer(value) => value.to_string(),
+                    ValueRef::Real(value) => value.to_string(),
+                    ValueRef::Text(text) | ValueRef::Blob(text) => String::from_utf8_lossy(text).to_string(),
+                })
+            })
+            .collect::<Result<Vec<String>, rusqlite::Error>>()
+            .map_err(|e| format!("Failed to read row: {}", e))?;
+        raw_data.push(values);
     }
 
-    let completeness_score = if total_cells > 0 {
-        ((total_cells - empty_cells) as f64 / total_cells as f64) * 100.0
-    } else {
-        100.0
+    Ok((headers, raw_data))
+}
+
+/// Read a worksheet of an Excel workbook as text: the named sheet, or the first one.
+///
+/// The first row is the header row, and blank header cells become `column_N`.
+/// Numbers keep no trailing `.0` when whole, date cells (serial values with a
+/// date format) become ISO 8601 strings, and empty or error cells become empty strings.
+#[cfg(feature = "xlsx")]
+pub fn read_xlsx(file_path: &str, sheet: Option<&str>) -> Result<(Vec<String>, Vec<Vec<String>>), DataScienceError> {
+    use calamine::{open_workbook, Reader, Xlsx};
+
+    check_input_file(file_path)?;
+    let mut workbook: Xlsx<_> =
+        open_workbook(file_path).map_err(|e| format!("Failed to open Excel workbook: {}", e))?;
+
+    let sheet_name = match sheet {
+        Some(name) => name.to_string(),
+        None => workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or("Workbook has no sheets")?,
     };
+    let range = workbook
+        .worksheet_range(&sheet_name)
+        .map_err(|e| format!("Failed to read sheet '{}': {}", sheet_name, e))?;
 
-    // Generate issues and recommendations
-    if duplicate_count > 0 {
-        issues.push(format!("Found {} duplicate rows", duplicate_count));
-        recommendations.push("Consider removing duplicate rows to improve data quality".to_string());
+    let mut rows = range
+        .rows()
+        .map(|row| row.iter().map(xlsx_cell_text).collect::<Vec<String>>());
+    let headers: Vec<String> = rows
+        .next()
+        .ok_or_else(|| format!("Sheet '{}' is empty", sheet_name))?
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            if name.trim().is_empty() {
+                format!("column_{}", i + 1)
+            } else {
+                name
+            }
+        })
+        .collect();
+
+    Ok((headers, rows.collect()))
+}
+
+// One worksheet cell as the text the store would have read from a CSV
+#[cfg(feature = "xlsx")]
+fn xlsx_cell_text(cell: &calamine::Data) -> String {
+    use calamine::Data;
+
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(text) | Data::DateTimeIso(text) | Data::DurationIso(text) => text.clone(),
+        Data::Int(value) => value.to_string(),
+        Data::Float(value) => value.to_string(),
+        Data::Bool(value) => value.to_string(),
+        Data::DateTime(value) if value.is_datetime() => match value.as_datetime() {
+            // Whole days carry no time, so they read back as plain dates
+            Some(datetime) if datetime.time() == chrono::NaiveTime::MIN => datetime.date().to_string(),
+            Some(datetime) => datetime.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            None => value.as_f64().to_string(),
+        },
+        Data::DateTime(value) => value.as_f64().to_string(),
     }
+}
 
-    if completeness_score < 80.0 {
-        issues.push(format!("Data completeness is only {:.1}%", completeness_score));
-        recommendations.push("Review and fill missing values to improve completeness".to_string());
+/// Make a name safe to use as a SQLite table or column name.
+///
+/// Characters other than ASCII letters, digits, and `_` become `_`, and names
+/// that are empty or start with a digit get a leading `_`.
+pub fn sanitize_sql_identifier(name: &str) -> String {
+    let sanitized: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", sanitized)
+    } else {
+        sanitized
     }
+}
 
-    if column_count < 2 {
-        issues.push("File appears to have only one column".to_string());
-        recommendations.push("Verify that the correct delimiter is being used".to_string());
+// Offsets count characters rather than bytes so multi-byte text doesn't split mid-character
+fn split_fixed_width(line: &str, field_widths: &[usize]) -> Vec<String> {
+    let mut chars = line.chars();
+    field_widths
+        .iter()
+        .map(|&width| chars.by_ref().take(width).collect::<String>().trim().to_string())
+        .collect()
+}
+
+/// Read a CSV file once and split it into its headers and per-column values.
+///
+/// Rows whose field count doesn't match the header are skipped, so every
+/// column holds the same number of values.
+pub fn collect_columns(
+    file_path: String,
+    options: &CsvParseOptions,
+) -> Result<(Vec<String>, Vec<Vec<String>>), DataScienceError> {
+    collect_columns_cancellable(file_path, options, &AtomicBool::new(false))
+}
+
+/// `collect_columns`, failing with `CANCELLED_ERROR` once `cancel` is set.
+pub fn collect_columns_cancellable(
+    file_path: String,
+    options: &CsvParseOptions,
+    cancel: &AtomicBool,
+) -> Result<(Vec<String>, Vec<Vec<String>>), DataScienceError> {
+    collect_columns_with_progress(file_path, options, cancel, &mut |_| {})
+}
+
+/// `collect_columns_cancellable`, passing the rows read so far to `on_progress`
+/// every `PROGRESS_INTERVAL_ROWS` rows and once at the end.
+pub(crate) fn collect_columns_with_progress(
+    file_path: String,
+    options: &CsvParseOptions,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(usize),
+) -> Result<(Vec<String>, Vec<Vec<String>>), DataScienceError> {
+    let content = read_csv_text(&file_path)?;
+    let content = skip_preamble(&content, options);
+
+    let delimiter = resolve_delimiter(content, options.delimiter_override.as_deref())?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    if lines.is_empty() {
+        return Err("File is empty".to_string().into());
     }
 
-    let overall_score = (completeness_score + (if duplicate_count == 0 { 100.0 } else { 50.0 })) / 2.0;
+    let has_headers = resolve_has_headers(&lines, &delimiter, options);
+    let (headers, records) = csv_records(content.as_bytes(), &delimiter, has_headers, options)?;
 
-    Ok(DataQualityReport {
-        overall_score,
-        issues,
-        recommendations,
-        duplicate_count,
-        completeness_score,
-    })
+    let mut column_data: Vec<Vec<String>> = vec![Vec::new(); headers.len()];
+    let mut rows_read = 0;
+
+    // Collect data for each column
+    for record in records {
+        check_cancelled(cancel)?;
+        let record = record?;
+        rows_read += 1;
+        if rows_read % PROGRESS_INTERVAL_ROWS == 0 {
+            on_progress(rows_read);
+        }
+        if record.len() == headers.len() {
+            for (i, value) in record.iter().enumerate() {
+                column_data[i].push(value.trim().to_string());
+            }
+        }
+    }
+    on_progress(rows_read);
+
+    Ok((headers, column_data))
 }
 
-pub fn detect_delimiter(content: &str) -> Result<String, String> {
-    let sample = &content[..content.len().min(10000)]; // Sample first 10KB
-    let delimiters = [",", ";", "\t", "|"];
+/// Rows a sampled read kept, and how many well-formed data rows it looked at to choose them.
+pub struct SampleOutcome {
+    pub headers: Vec<String>,
+    pub columns: Vec<Vec<String>>,
+    pub rows_scanned: usize,
+}
+
+/// Like `collect_columns`, but keep only a sample of the rows, streaming the file in one pass.
+///
+/// `head` stops reading after `size` rows, `every_kth` keeps rows 0, k, 2k, ...
+/// and `random` reservoir-samples `size` rows, returned in file order.
+pub fn collect_sampled_columns(
+    file_path: String,
+    options: &CsvParseOptions,
+    sample: &SampleSpec,
+    cancel: &AtomicBool,
+) -> Result<SampleOutcome, DataScienceError> {
+    collect_sampled_columns_with_progress(file_path, options, sample, cancel, &mut |_| {})
+}
+
+/// `collect_sampled_columns`, passing the rows scanned so far to `on_progress`
+/// every `PROGRESS_INTERVAL_ROWS` rows and once at the end.
+pub(crate) fn collect_sampled_columns_with_progress(
+    file_path: String,
+    options: &CsvParseOptions,
+    sample: &SampleSpec,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(usize),
+) -> Result<SampleOutcome, DataScienceError> {
+    if sample.size == 0 {
+        return Err(DataScienceError::InvalidArgument(
+            "Sample size must be greater than zero".to_string(),
+        ));
+    }
+    if !matches!(sample.mode.as_str(), "head" | "random" | "every_kth") {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Unknown sample mode: {}",
+            sample.mode
+        )));
+    }
 
-    let mut best_delimiter = ",";
-    let mut max_consistency = 0;
+    let (headers, mut rows) = stream_csv_rows(&file_path, options)?;
 
-    for delimiter in &delimiters {
-        let lines: Vec<&str> = sample.lines().take(10).collect(); // Check first 10 lines
-        if lines.len() < 2 {
+    let mut rng = SampleRng::new(sample.seed.unwrap_or_else(get_default_sample_seed));
+    let mut kept: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut rows_scanned = 0;
+
+    loop {
+        check_cancelled(cancel)?;
+        if sample.mode == "head" && kept.len() == sample.size {
+            break;
+        }
+        let Some(values) = rows.next() else {
+            break;
+        };
+        let values = values?;
+        if values.len() != headers.len() {
             continue;
         }
 
-        let counts: Vec<usize> = lines.iter().map(|line| line.split(delimiter).count()).collect();
-
-        if counts.len() > 1 {
-            let first_count = counts[0];
-            let consistency = counts.iter().filter(|&&count| count == first_count).count();
-            if consistency > max_consistency {
-                max_consistency = consistency;
-                best_delimiter = delimiter;
+        let index = rows_scanned;
+        rows_scanned += 1;
+        if rows_scanned % PROGRESS_INTERVAL_ROWS == 0 {
+            on_progress(rows_scanned);
+        }
+        match sample.mode.as_str() {
+            "every_kth" if index % sample.size == 0 => kept.push((index, values)),
+            "random" if kept.len() < sample.size => kept.push((index, values)),
+            "random" => {
+                // Algorithm R: row i replaces a kept row with probability size / (i + 1)
+                let slot = rng.below(index as u64 + 1) as usize;
+                if slot < sample.size {
+                    kept[slot] = (index, values);
+                }
             }
+            "head" => kept.push((index, values)),
+            _ => {}
+        }
+    }
+
+    on_progress(rows_scanned);
+
+    kept.sort_by_key(|(index, _)| *index);
+    let mut columns: Vec<Vec<String>> = vec![Vec::with_capacity(kept.len()); headers.len()];
+    for (_, values) in kept {
+        for (i, value) in values.into_iter().enumerate() {
+            columns[i].push(value);
         }
     }
 
-    Ok(best_delimiter.to_string())
+    Ok(SampleOutcome {
+        headers,
+        columns,
+        rows_scanned,
+    })
 }
 
-pub fn is_likely_header_row(row: &str, delimiter: &str) -> bool {
-    let values: Vec<&str> = row.split(delimiter).collect();
-    if values.len() < 2 {
-        return false;
+// A data row's trimmed fields, or the error that stopped the read
+type StreamedRow = Result<Vec<String>, DataScienceError>;
+
+/// Open a file for a single streaming pass, returning its headers and an iterator over data rows.
+///
+/// The delimiter and header row are detected from the first lines after the
+/// preamble. Rows are split into trimmed fields but not checked against the
+/// header count.
+fn stream_csv_rows(
+    file_path: &str,
+    options: &CsvParseOptions,
+) -> Result<(Vec<String>, impl Iterator<Item = StreamedRow>), DataScienceError> {
+    let mut reader = open_csv_reader(file_path)?;
+    let mut next_line = || -> Result<Option<String>, DataScienceError> {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| DataScienceError::io("read", file_path, e))?;
+        let trimmed_len = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(trimmed_len);
+        Ok((read > 0).then_some(line))
+    };
+
+    for _ in 0..preamble_line_count(options) {
+        if next_line()?.is_none() {
+            break;
+        }
     }
 
-    // Simple heuristic: if most values contain letters and are reasonably short
-    let text_like_count = values
+    // Buffer the first lines for delimiter detection, then stream the rest
+    let mut buffered = Vec::new();
+    while buffered.len() < 10 {
+        match next_line()? {
+            Some(line) => buffered.push(line),
+            None => break,
+        }
+    }
+    if buffered.is_empty() {
+        return Err("File is empty".to_string().into());
+    }
+
+    let delimiter = resolve_delimiter(&buffered.join("\n"), options.delimiter_override.as_deref())?;
+    let has_headers = resolve_has_headers(&buffered, &delimiter, options);
+
+    let input = std::io::Cursor::new(buffered.join("\n") + "\n").chain(reader);
+    let (headers, records) = csv_records(input, &delimiter, has_headers, options)?;
+    let rows = records.map(|record| record.map(|record| record.iter().map(|s| s.trim().to_string()).collect()));
+
+    Ok((headers, rows))
+}
+
+/// Profile a file's columns in one streaming pass, without holding its rows in memory.
+///
+/// Each column gets a `ColumnAnalysis` plus running numeric statistics.
+/// `unique_count` is a HyperLogLog estimate, the data type and `sample_values`
+/// come from a `STREAMING_RESERVOIR_SIZE` random sample of non-null values, and
+/// `most_common` is left empty because it can't be tracked in bounded memory.
+/// Rows with the wrong number of fields are skipped. The reservoir is drawn with
+/// `seed`, or the default sample seed, so a profile can be reproduced exactly.
+pub fn profile_csv_streaming(
+    file_path: String,
+    options: &CsvParseOptions,
+    seed: Option<u64>,
+    cancel: &AtomicBool,
+) -> Result<StreamingProfile, DataScienceError> {
+    let (headers, rows) = stream_csv_rows(&file_path, options)?;
+    let mut profilers: Vec<ColumnProfiler> = headers.iter().map(|_| ColumnProfiler::default()).collect();
+    let seed = seed.unwrap_or_else(get_default_sample_seed);
+    let mut rng = SampleRng::new(seed);
+    let mut rows_scanned = 0;
+
+    for values in rows {
+        check_cancelled(cancel)?;
+        let values = values?;
+        if values.len() != headers.len() {
+            continue;
+        }
+        rows_scanned += 1;
+        for (profiler, value) in profilers.iter_mut().zip(values) {
+            profiler.add(value, &mut rng);
+        }
+    }
+
+    let columns = headers
         .iter()
-        .filter(|v| {
-            let trimmed = v.trim();
-            !trimmed.is_empty() && trimmed.chars().any(|c| c.is_alphabetic()) && trimmed.len() < 50
-        })
-        .count();
+        .zip(profilers)
+        .map(|(header, profiler)| profiler.finish(header, rows_scanned))
+        .collect();
 
-    text_like_count >= (values.len() + 1) / 2 // Majority are text-like
+    Ok(StreamingProfile {
+        rows_scanned,
+        seed,
+        columns,
+    })
+}
+
+// Running state for one column of `profile_csv_streaming`
+#[derive(Default)]
+struct ColumnProfiler {
+    type_counts: TypeCounts,
+    null_count: usize,
+    non_null_count: usize,
+    numeric_count: usize,
+    min: Option<f64>,
+    max: Option<f64>,
+    sum: f64,
+    mean: f64,
+    squared_deviations: f64, // Welford's M2: sum of squared distances from the running mean
+    distinct: HyperLogLog,
+    reservoir: Vec<String>,
+}
+
+impl ColumnProfiler {
+    fn add(&mut self, value: String, rng: &mut SampleRng) {
+        self.distinct.insert(&value);
+        if is_null_token(&value) {
+            self.null_count += 1;
+            return;
+        }
+        self.type_counts.add(&value);
+
+        if classify_value(&value) == "number" {
+            let number = value.parse::<f64>().ok().or_else(|| parse_formatted_number(&value));
+            if let Some(number) = number.filter(|n| n.is_finite()) {
+                self.numeric_count += 1;
+                self.min = Some(self.min.map_or(number, |min| min.min(number)));
+                self.max = Some(self.max.map_or(number, |max| max.max(number)));
+                self.sum += number;
+                let delta = number - self.mean;
+                self.mean += delta / self.numeric_count as f64;
+                self.squared_deviations += delta * (number - self.mean);
+            }
+        }
+
+        // Algorithm R over the non-null values
+        self.non_null_count += 1;
+        if self.reservoir.len() < STREAMING_RESERVOIR_SIZE {
+            self.reservoir.push(value);
+        } else {
+            let slot = rng.below(self.non_null_count as u64) as usize;
+            if slot < STREAMING_RESERVOIR_SIZE {
+                self.reservoir[slot] = value;
+            }
+        }
+    }
+
+    fn finish(self, header: &str, rows_scanned: usize) -> StreamingColumnProfile {
+        let data_type = if rows_scanned == 0 {
+            "unknown".to_string()
+        } else {
+            self.type_counts.data_type(|| is_epoch_column(header, &self.reservoir))
+        };
+        let date_format = if data_type == "date" {
+            infer_column_date_format(header, &self.reservoir)
+        } else {
+            None
+        };
+
+        let non_null: Vec<&String> = self.reservoir.iter().collect();
+        let sample_values = sample_column_values(&non_null, &[], DEFAULT_SAMPLE_SIZE);
+        let unique_count = self.distinct.estimate();
+
+        let analysis = ColumnAnalysis {
+            name: header.to_string(),
+            data_type,
+            nullable: self.null_count > 0,
+            unique_count,
+            null_percentage: if rows_scanned > 0 {
+                (self.null_count as f64 / rows_scanned as f64) * 100.0
+            } else {
+                0.0
+            },
+            sample_values,
+            most_common: Vec::new(),
+            date_format,
+            is_constant: unique_count <= 1,
+            dominant_value_ratio: dominant_value_ratio(&most_common_values(&non_null), non_null.len()),
+            is_estimated: true,
+        };
+
+        let stats = StreamingStats {
+            null_count: self.null_count,
+            numeric_count: self.numeric_count,
+            min: self.min,
+            max: self.max,
+            sum: self.sum,
+            mean: (self.numeric_count > 0).then_some(self.mean),
+            variance: (self.numeric_count > 1).then(|| self.squared_deviations / (self.numeric_count - 1) as f64),
+            reservoir_sample: self.reservoir,
+        };
+
+        StreamingColumnProfile { analysis, stats }
+    }
+}
+
+/// Build a `ColumnAnalysis` for each column produced by `collect_columns`.
+///
+/// With the `parallel` feature, files wider than `PARALLEL_COLUMN_THRESHOLD`
+/// columns are analyzed on the rayon thread pool; output order is unchanged.
+/// `sample_size` bounds both `sample_values` and `most_common`.
+pub fn analyze_columns(headers: &[String], columns: &[Vec<String>], sample_size: usize) -> Vec<ColumnAnalysis> {
+    analyze_columns_with_distinct_limit(headers, columns, sample_size, None)
+}
+
+/// `analyze_columns`, estimating `unique_count` for columns with more than `distinct_limit` distinct values.
+///
+/// Such columns stop collecting distinct values once the limit is passed and
+/// get a HyperLogLog estimate with `is_estimated` set. Their `most_common` is
+/// empty, and `dominant_value_ratio` is measured on an evenly spaced sample of
+/// `STREAMING_RESERVOIR_SIZE` non-null values. `None` counts every column exactly.
+///
+/// This bounds the distinct-value bookkeeping, not `columns` itself, which is
+/// already in memory; `profile_csv_streaming` estimates without holding rows.
+pub fn analyze_columns_with_distinct_limit(
+    headers: &[String],
+    columns: &[Vec<String>],
+    sample_size: usize,
+    distinct_limit: Option<usize>,
+) -> Vec<ColumnAnalysis> {
+    let total_rows = columns.first().map_or(0, Vec::len);
+
+    #[cfg(feature = "parallel")]
+    if headers.len() > PARALLEL_COLUMN_THRESHOLD {
+        use rayon::prelude::*;
+        return headers
+            .par_iter()
+            .zip(columns.par_iter())
+            .map(|(header, column_values)| {
+                analyze_column(header, column_values, total_rows, sample_size, distinct_limit)
+            })
+            .collect();
+    }
+
+    headers
+        .iter()
+        .zip(columns)
+        .map(|(header, column_values)| analyze_column(header, column_values, total_rows, sample_size, distinct_limit))
+        .collect()
+}
+
+fn analyze_column(
+    header: &str,
+    column_values: &[String],
+    total_rows: usize,
+    sample_size: usize,
+    distinct_limit: Option<usize>,
+) -> ColumnAnalysis {
+    let null_count = column_values.iter().filter(|v| is_null_token(v)).count();
+    let null_percentage = if total_rows > 0 {
+        (null_count as f64 / total_rows as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    let (unique_count, is_estimated) = distinct_count(column_values, distinct_limit);
+
+    let data_type = infer_column_data_type(header, column_values);
+    let date_format = if data_type == "date" {
+        infer_column_date_format(header, column_values)
+    } else {
+        None
+    };
+
+    let non_null: Vec<&String> = column_values.iter().filter(|v| !is_null_token(v)).collect();
+    let (mut most_common, dominant_value_ratio) = if is_estimated {
+        // Counting every value would copy the whole column; measure dominance on a sample instead
+        let step = (non_null.len() / STREAMING_RESERVOIR_SIZE).max(1);
+        let spaced: Vec<&String> = non_null.iter().step_by(step).copied().collect();
+        (
+            Vec::new(),
+            dominant_value_ratio(&most_common_values(&spaced), spaced.len()),
+        )
+    } else {
+        let most_common = most_common_values(&non_null);
+        let ratio = dominant_value_ratio(&most_common, non_null.len());
+        (most_common, ratio)
+    };
+    most_common.truncate(sample_size);
+    let sample_values = sample_column_values(&non_null, &most_common, sample_size);
+
+    ColumnAnalysis {
+        name: header.to_string(),
+        data_type,
+        nullable: null_count > 0,
+        unique_count,
+        null_percentage,
+        sample_values,
+        most_common,
+        date_format,
+        is_constant: unique_count <= 1,
+        dominant_value_ratio,
+        is_estimated,
+    }
+}
+
+// Exact distinct count, switching to a HyperLogLog estimate once more than `limit` values are distinct
+fn distinct_count(values: &[String], limit: Option<usize>) -> (usize, bool) {
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut seen: HashSet<&str> = HashSet::new();
+    for value in values {
+        seen.insert(value);
+        if seen.len() > limit {
+            drop(seen);
+            let mut distinct = HyperLogLog::default();
+            values.iter().for_each(|v| distinct.insert(v));
+            return (distinct.estimate(), true);
+        }
+    }
+    (seen.len(), false)
+}
+
+/// Share of `non_null_count` values taken by the first (most common) entry of `most_common`.
+fn dominant_value_ratio(most_common: &[(String, usize)], non_null_count: usize) -> f64 {
+    match most_common.first() {
+        Some((_, count)) if non_null_count > 0 => *count as f64 / non_null_count as f64,
+        _ => 0.0,
+    }
+}
+
+/// Non-null values ordered by descending frequency; ties keep first-appearance order.
+fn most_common_values(values: &[&String]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+
+    for value in values {
+        match positions.entry(value.as_str()) {
+            std::collections::hash_map::Entry::Occupied(entry) => counts[*entry.get()].1 += 1,
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(counts.len());
+                counts.push((value.to_string(), 1));
+            }
+        }
+    }
+
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+/// Pick up to `sample_size` distinct non-null values spread across the column.
+///
+/// All but one slot go to values evenly spaced from the first row to the last,
+/// so blank or repetitive leading rows don't crowd out the rest of the file.
+/// The remaining slot goes to the most frequent value not already chosen.
+fn sample_column_values(non_null: &[&String], most_common: &[(String, usize)], sample_size: usize) -> Vec<String> {
+    let mut samples: Vec<String> = Vec::new();
+    if sample_size == 0 || non_null.is_empty() {
+        return samples;
+    }
+
+    let spread = sample_size.saturating_sub(1).max(1);
+    let last = non_null.len() - 1;
+    let spaced = (0..spread).map(|i| if spread == 1 { 0 } else { i * last / (spread - 1) });
+    let candidates = spaced
+        .map(|idx| non_null[idx].as_str())
+        .chain(most_common.iter().map(|(value, _)| value.as_str()))
+        .chain(non_null.iter().map(|value| value.as_str()));
+
+    for value in candidates {
+        if samples.len() == sample_size {
+            break;
+        }
+        if !samples.iter().any(|sample| sample == value) {
+            samples.push(value.to_string());
+        }
+    }
+
+    samples
+}
+
+/// Assess duplicates, completeness, and per-column issues.
+///
+/// Rows are compared after trimming every field. When `key_columns` is
+/// non-empty only those columns are compared, so repeated keys count as
+/// duplicates even if the rest of the row differs.
+pub fn validate_data_quality(
+    file_path: String,
+    options: &CsvParseOptions,
+    key_columns: &[String],
+    weights: &QualityWeights,
+) -> Result<DataQualityReport, DataScienceError> {
+    let weight_total = weights.completeness + weights.duplicates + weights.column_issues;
+    if [weights.completeness, weights.duplicates, weights.column_issues]
+        .iter()
+        .any(|w| !w.is_finite() || *w < 0.0)
+        || weight_total <= 0.0
+    {
+        return Err(DataScienceError::InvalidArgument(
+            "Quality weights must be non-negative and not all zero".to_string(),
+        ));
+    }
+
+    let content = read_csv_text(&file_path)?;
+    let content = skip_preamble(&content, options);
+
+    let delimiter = resolve_delimiter(content, options.delimiter_override.as_deref())?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    if lines.is_empty() {
+        return Err("File is empty".to_string().into());
+    }
+
+    let mut issues = Vec::new();
+    let mut recommendations = Vec::new();
+    let mut duplicate_count = 0;
+    let mut duplicate_examples: Vec<String> = Vec::new();
+
+    let has_headers = resolve_has_headers(&lines, &delimiter, options);
+    let (headers, records) = csv_records(content.as_bytes(), &delimiter, has_headers, options)?;
+    let rows: Vec<csv::StringRecord> = records.collect::<Result<_, _>>()?;
+    let key_indices = key_columns
+        .iter()
+        .map(|key| {
+            headers
+                .iter()
+                .position(|h| h == key)
+                .ok_or_else(|| DataScienceError::ColumnNotFound(key.clone()))
+        })
+        .collect::<Result<Vec<usize>, DataScienceError>>()?;
+
+    // Check for duplicate rows (or duplicate keys), ignoring whitespace around fields
+    let mut seen_rows = std::collections::HashSet::new();
+    for row in &rows {
+        let fields: Vec<&str> = row.iter().map(str::trim).collect();
+        let key: Vec<&str> = if key_indices.is_empty() {
+            fields
+        } else {
+            match key_indices
+                .iter()
+                .map(|&i| fields.get(i).copied())
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(key) => key,
+                None => continue, // Row too short to contain the key
+            }
+        };
+
+        let key_text = key.join(", ");
+        if !seen_rows.insert(key) {
+            duplicate_count += 1;
+            if duplicate_examples.len() < MAX_DUPLICATE_EXAMPLES && !duplicate_examples.contains(&key_text) {
+                duplicate_examples.push(key_text);
+            }
+        }
+    }
+
+    // Check for completeness
+    let column_count = headers.len();
+    let mut total_cells = 0;
+    let mut empty_cells = 0;
+
+    for row in &rows {
+        total_cells += column_count;
+        empty_cells += row.iter().filter(|cell| is_null_token(cell)).count();
+    }
+
+    let completeness_score = if total_cells > 0 {
+        ((total_cells - empty_cells) as f64 / total_cells as f64) * 100.0
+    } else {
+        100.0
+    };
+
+    // Generate issues and recommendations
+    if duplicate_count > 0 {
+        if key_columns.is_empty() {
+            issues.push(format!("Found {} duplicate rows", duplicate_count));
+            recommendations.push("Consider removing duplicate rows to improve data quality".to_string());
+        } else {
+            issues.push(format!(
+                "Found {} rows with a duplicate key ({}), e.g. {}",
+                duplicate_count,
+                key_columns.join(", "),
+                duplicate_examples.join("; ")
+            ));
+            recommendations.push("Key columns should uniquely identify each row".to_string());
+        }
+    }
+
+    if completeness_score < 80.0 {
+        issues.push(format!("Data completeness is only {:.1}%", completeness_score));
+        recommendations.push("Review and fill missing values to improve completeness".to_string());
+    }
+
+    if column_count < 2 {
+        issues.push("File appears to have only one column".to_string());
+        recommendations.push("Verify that the correct delimiter is being used".to_string());
+    }
+
+    // Per-column checks run on the raw (untrimmed) cells of well-formed rows
+    let mut raw_columns: Vec<Vec<&str>> = vec![Vec::new(); headers.len()];
+    for row in &rows {
+        if row.len() == headers.len() {
+            for (i, cell) in row.iter().enumerate() {
+                raw_columns[i].push(cell);
+            }
+        }
+    }
+    let column_issues = detect_column_issues(&headers, &raw_columns, &mut issues, &mut recommendations);
+
+    // Each component is the percentage of rows or columns without that problem
+    let data_rows = rows.len();
+    let unique_row_score = if data_rows > 0 {
+        (1.0 - duplicate_count as f64 / data_rows as f64) * 100.0
+    } else {
+        100.0
+    };
+    let clean_column_score = if column_count > 0 {
+        (1.0 - column_issues.len() as f64 / column_count as f64) * 100.0
+    } else {
+        100.0
+    };
+    let overall_score = ((weights.completeness * completeness_score
+        + weights.duplicates * unique_row_score
+        + weights.column_issues * clean_column_score)
+        / weight_total)
+        .clamp(0.0, 100.0);
+
+    Ok(DataQualityReport {
+        overall_score,
+        issues,
+        recommendations,
+        duplicate_count,
+        duplicate_examples,
+        completeness_score,
+        column_issues,
+    })
+}
+
+/// Flag columns with mixed value types, stray whitespace, or inconsistently cased categories.
+///
+/// Human-readable findings are appended to `issues`/`recommendations`; the
+/// returned map holds the machine-readable issue codes per column. Constant and
+/// near-constant columns only get a recommendation, since they aren't errors.
+fn detect_column_issues(
+    headers: &[String],
+    raw_columns: &[Vec<&str>],
+    issues: &mut Vec<String>,
+    recommendations: &mut Vec<String>,
+) -> HashMap<String, Vec<String>> {
+    let mut column_issues: HashMap<String, Vec<String>> = HashMap::new();
+
+    for (header, cells) in headers.iter().zip(raw_columns) {
+        let present: Vec<&str> = cells.iter().copied().filter(|cell| !is_null_token(cell)).collect();
+        let mut codes = Vec::new();
+
+        let mut value_counts: HashMap<&str, usize> = HashMap::new();
+        for cell in &present {
+            *value_counts.entry(cell).or_insert(0) += 1;
+        }
+        let distinct_cells: HashSet<&str> = cells.iter().copied().collect();
+        let dominant_count = value_counts.values().copied().max().unwrap_or(0);
+        if !cells.is_empty() && distinct_cells.len() <= 1 {
+            recommendations.push(format!("Column '{}' is constant; consider removing", header));
+        } else if !present.is_empty() && dominant_count as f64 / present.len() as f64 >= NEAR_CONSTANT_RATIO {
+            recommendations.push(format!(
+                "Column '{}' is nearly constant ({:.1}% one value); consider removing",
+                header,
+                dominant_count as f64 / present.len() as f64 * 100.0
+            ));
+        }
+
+        let mut type_counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for cell in &present {
+            *type_counts.entry(classify_value(cell)).or_insert(0) += 1;
+        }
+        if type_counts.len() > 1 {
+            let breakdown: Vec<String> = type_counts
+                .iter()
+                .map(|(value_type, count)| format!("{} {}", count, value_type))
+                .collect();
+            issues.push(format!(
+                "Column '{}' mixes value types ({})",
+                header,
+                breakdown.join(", ")
+            ));
+            recommendations.push(format!("Convert column '{}' to a single consistent type", header));
+            codes.push("mixed_types".to_string());
+        }
+
+        let padded = present.iter().filter(|cell| cell.trim() != **cell).count();
+        if padded > 0 {
+            issues.push(format!(
+                "Column '{}' has {} values with leading/trailing whitespace",
+                header, padded
+            ));
+            recommendations.push(format!("Trim whitespace from values in column '{}'", header));
+            codes.push("leading_trailing_whitespace".to_string());
+        }
+
+        // Only low-cardinality text columns are treated as categorical
+        let mut spellings: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+        for cell in present
+            .iter()
+            .map(|cell| cell.trim())
+            .filter(|cell| classify_value(cell) == "string")
+        {
+            spellings.entry(cell.to_lowercase()).or_default().insert(cell);
+        }
+        let text_count = spellings.values().map(BTreeSet::len).sum::<usize>();
+        if !spellings.is_empty() && spellings.len() <= CATEGORICAL_MAX_DISTINCT && spellings.len() < text_count {
+            let variants: Vec<String> = spellings
+                .values()
+                .filter(|variants| variants.len() > 1)
+                .take(3)
+                .map(|variants| variants.iter().copied().collect::<Vec<_>>().join("/"))
+                .collect();
+            if !variants.is_empty() {
+                issues.push(format!(
+                    "Column '{}' has inconsistent casing: {}",
+                    header,
+                    variants.join(", ")
+                ));
+                recommendations.push(format!("Normalize the casing of categories in column '{}'", header));
+                codes.push("inconsistent_casing".to_string());
+            }
+        }
+
+        if !codes.is_empty() {
+            column_issues.insert(header.clone(), codes);
+        }
+    }
+
+    column_issues
+}
+
+/// Read up to `max_rows` records starting at `start_byte`, for resumable processing.
+///
+/// An offset that isn't the start of a record is moved forward to the start of
+/// the next one. Records are found by reading from the start of the file with
+/// the quoting from `options`, so a quoted field is never split, but that read
+/// costs time in proportion to the offset. Offsets returned in `next_offset`
+/// are always record boundaries, so following them reads every record exactly
+/// once. The first record is returned as `headers` when reading from byte 0.
+/// Only the delimiter and quoting fields of `options` apply. Gzip files can't
+/// be read by range.
+pub fn read_csv_range(
+    file_path: &str,
+    start_byte: u64,
+    max_rows: usize,
+    options: &CsvParseOptions,
+) -> Result<CsvRange, DataScienceError> {
+    if is_gzip_file(file_path) {
+        return Err(DataScienceError::InvalidArgument(
+            "Byte ranges can't be read from gzip-compressed files".to_string(),
+        ));
+    }
+    let mut file = fs::File::open(file_path).map_err(|e| DataScienceError::io("open", file_path, e))?;
+    let file_len = file
+        .metadata()
+        .map_err(|e| DataScienceError::io("read metadata of", file_path, e))?
+        .len();
+
+    let mut head = Vec::new();
+    (&mut file)
+        .take(RANGE_SNIFF_BYTES)
+        .read_to_end(&mut head)
+        .map_err(|e| DataScienceError::io("read", file_path, e))?;
+    let head = normalize_line_endings(&String::from_utf8_lossy(&head));
+    let delimiter = resolve_delimiter(&head, options.delimiter_override.as_deref())?;
+    check_quoting(options, &delimiter)?;
+    let mut builder = csv_reader_builder(delimiter_byte(&delimiter)?, options);
+    builder.has_headers(false).flexible(true);
+
+    file.seek(SeekFrom::Start(0))
+        .map_err(|e| DataScienceError::io("read", file_path, e))?;
+    let start_offset = align_to_record_start(&mut file, start_byte.min(file_len), &builder)?;
+    file.seek(SeekFrom::Start(start_offset))
+        .map_err(|e| DataScienceError::io("read", file_path, e))?;
+    let mut reader = builder.from_reader(BufReader::new(file));
+
+    let mut next_record = || -> Result<Option<Vec<String>>, DataScienceError> {
+        let mut record = csv::ByteRecord::new();
+        let read = reader
+            .read_byte_record(&mut record)
+            .map_err(|e| DataScienceError::Parse(format!("Failed to read CSV record: {}", e)))?;
+        Ok(read.then(|| record.iter().map(|f| String::from_utf8_lossy(f).into_owned()).collect()))
+    };
+
+    let headers = if start_offset == 0 {
+        next_record()?.map(|mut headers: Vec<String>| {
+            if let Some(first) = headers.first_mut() {
+                *first = first.trim_start_matches('\u{feff}').to_string();
+            }
+            headers
+        })
+    } else {
+        None
+    };
+    let mut rows = Vec::new();
+    let mut done = false;
+    while rows.len() < max_rows {
+        match next_record()? {
+            Some(row) => rows.push(row),
+            None => {
+                done = true;
+                break;
+            }
+        }
+    }
+
+    let next_offset = if done {
+        file_len
+    } else {
+        start_offset + reader.position().byte()
+    };
+    Ok(CsvRange {
+        headers,
+        rows,
+        start_offset,
+        next_offset,
+        done: done || next_offset >= file_len,
+    })
+}
+
+// The first record boundary at or after `offset`, reading records from the start of `file`
+fn align_to_record_start(file: impl Read, offset: u64, builder: &csv::ReaderBuilder) -> Result<u64, DataScienceError> {
+    if offset == 0 {
+        return Ok(0);
+    }
+    let mut reader = builder.from_reader(BufReader::new(file));
+    let mut record = csv::ByteRecord::new();
+    while reader
+        .read_byte_record(&mut record)
+        .map_err(|e| DataScienceError::Parse(format!("Failed to read CSV record: {}", e)))?
+    {
+        if reader.position().byte() >= offset {
+            break;
+        }
+    }
+    Ok(reader.position().byte())
+}
+
+fn is_csv_gz_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.to_lowercase().ends_with(".csv.gz"))
+}
+
+/// True if the file has a `.gz` extension or starts with the gzip magic bytes.
+pub fn is_gzip_file(file_path: &str) -> bool {
+    let path = Path::new(file_path);
+    if path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+    {
+        return true;
+    }
+    let mut magic = [0u8; 2];
+    fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok_and(|_| magic == [0x1f, 0x8b])
+}
+
+/// Check that `file_path` is an existing, readable regular file and return its metadata.
+///
+/// The error says which problem it is: `FileNotFound`, `NotAFile` for a
+/// directory, `PermissionDenied`, or `Io` when the file can't be opened otherwise.
+pub fn check_input_file(file_path: &str) -> Result<fs::Metadata, DataScienceError> {
+    let metadata = fs::metadata(file_path).map_err(|e| DataScienceError::io("read metadata of", file_path, e))?;
+    if metadata.is_dir() {
+        return Err(DataScienceError::NotAFile {
+            path: file_path.to_string(),
+        });
+    }
+    fs::File::open(file_path).map_err(|e| DataScienceError::io("open", file_path, e))?;
+    Ok(metadata)
+}
+
+/// Open a CSV file for buffered reading, transparently decompressing gzip input.
+pub fn open_csv_reader(file_path: &str) -> Result<Box<dyn BufRead>, DataScienceError> {
+    let file = fs::File::open(file_path).map_err(|e| DataScienceError::io("open", file_path, e))?;
+    if is_gzip_file(file_path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Read the full (decompressed) contents of a CSV file.
+pub fn read_csv_bytes(file_path: &str) -> Result<Vec<u8>, DataScienceError> {
+    let mut bytes = Vec::new();
+    open_csv_reader(file_path)?
+        .read_to_end(&mut bytes)
+        .map_err(|e| DataScienceError::io("read", file_path, e))?;
+    Ok(bytes)
+}
+
+/// Count the records in a CSV file, header included, without holding it in memory.
+///
+/// Newlines inside quoted fields don't end a record, blank lines aren't
+/// records, and `\r\n`, `\n`, and lone `\r` all end a line. Gzip input is
+/// decompressed as it is read.
+pub fn count_rows_streaming(file_path: &str) -> Result<u64, DataScienceError> {
+    check_input_file(file_path)?;
+    count_records(open_csv_reader(file_path)?, &CsvParseOptions::default())
+        .map_err(|e| DataScienceError::io("read", file_path, e))
+}
+
+// Count non-blank records a buffer at a time, tracking whether the reader is inside quotes
+fn count_records(mut reader: impl BufRead, options: &CsvParseOptions) -> std::io::Result<u64> {
+    let quote = options.quote_char.unwrap_or(b'"');
+    // A doubled quote toggles twice, so only a distinct escape character needs handling
+    let escape = options.escape_char.filter(|&escape| escape != quote);
+    let mut records = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut line_has_content = false;
+
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        for &byte in buffer {
+            if escaped {
+                escaped = false;
+            } else if in_quotes && Some(byte) == escape {
+                escaped = true;
+            } else if !in_quotes && matches!(byte, b'\n' | b'\r') {
+                // `\r\n` leaves a blank line behind, which isn't counted
+                records += line_has_content as u64;
+                line_has_content = false;
+            } else {
+                in_quotes ^= byte == quote;
+                line_has_content = true;
+            }
+        }
+        let consumed = buffer.len();
+        reader.consume(consumed);
+    }
+
+    Ok(records + line_has_content as u64)
+}
+
+/// Read the full (decompressed) contents of a CSV file as UTF-8 text with `\n` line endings.
+pub fn read_csv_text(file_path: &str) -> Result<String, DataScienceError> {
+    let content = String::from_utf8(read_csv_bytes(file_path)?)
+        .map_err(|e| DataScienceError::Parse(format!("Failed to read file: {}", e.utf8_error())))?;
+    Ok(normalize_line_endings(&content))
+}
+
+/// Name the line-ending style used in the content: "LF", "CRLF", "CR", "mixed", or "none".
+pub fn detect_line_ending(content: &str) -> &'static str {
+    let crlf = content.matches("\r\n").count();
+    let cr = content.matches('\r').count() - crlf;
+    let lf = content.matches('\n').count() - crlf;
+
+    match (crlf > 0, cr > 0, lf > 0) {
+        (false, false, false) => "none",
+        (true, false, false) => "CRLF",
+        (false, true, false) => "CR",
+        (false, false, true) => "LF",
+        _ => "mixed",
+    }
+}
+
+/// Rewrite `\r\n` and lone `\r` (classic Mac) line endings as `\n`.
+pub fn normalize_line_endings(content: &str) -> String {
+    if content.contains('\r') {
+        content.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        content.to_string()
+    }
+}
+
+/// Number of lines before the header row; an explicit `header_row_index` wins over `skip_rows`.
+pub fn preamble_line_count(options: &CsvParseOptions) -> usize {
+    options.header_row_index.unwrap_or(options.skip_rows)
+}
+
+/// Drop the preamble lines the caller asked to skip so the next line is treated as the header.
+pub fn skip_preamble<'a>(content: &'a str, options: &CsvParseOptions) -> &'a str {
+    let skip = preamble_line_count(options);
+    let offset: usize = content.split_inclusive('\n').take(skip).map(str::len).sum();
+    &content[offset..]
+}
+
+/// Guess how many preamble lines precede the header.
+///
+/// Returns the index of the first line whose column count matches the count
+/// shared by most of the first 50 lines, for whichever candidate delimiter
+/// produces the most consistent layout.
+pub fn detect_preamble(content: &str) -> usize {
+    let lines: Vec<&str> = content.lines().take(50).collect();
+    let mut best: Option<(usize, usize)> = None; // (lines with the majority count, first such line)
+
+    for delimiter in CANDIDATE_DELIMITERS {
+        let counts: Vec<usize> = lines.iter().map(|line| line.split(delimiter).count()).collect();
+
+        let mut frequency: HashMap<usize, usize> = HashMap::new();
+        for &count in counts.iter().filter(|&&count| count > 1) {
+            *frequency.entry(count).or_insert(0) += 1;
+        }
+
+        let Some((&majority, &occurrences)) = frequency
+            .iter()
+            .max_by_key(|&(count, occurrences)| (occurrences, count))
+        else {
+            continue;
+        };
+        if best.is_none_or(|(best_occurrences, _)| occurrences > best_occurrences) {
+            let first = counts.iter().position(|&count| count == majority).unwrap_or(0);
+            best = Some((occurrences, first));
+        }
+    }
+
+    best.map(|(_, first)| first).unwrap_or(0)
+}
+
+/// Use the caller's delimiter if given, otherwise detect it from the content.
+///
+/// A literal `\t` is accepted as a tab. The `csv` crate only supports
+/// single-byte delimiters, so anything longer is rejected.
+pub fn resolve_delimiter(content: &str, delimiter_override: Option<&str>) -> Result<String, DataScienceError> {
+    resolve_delimiter_detection(content, delimiter_override).map(|detection| detection.value)
+}
+
+/// Like `resolve_delimiter`, keeping the detection confidence; an override is fully confident.
+pub fn resolve_delimiter_detection(
+    content: &str,
+    delimiter_override: Option<&str>,
+) -> Result<DetectionResult, DataScienceError> {
+    match delimiter_override {
+        Some(raw) => {
+            let delimiter = if raw == "\\t" { "\t" } else { raw };
+            if delimiter.len() != 1 {
+                return Err(DataScienceError::InvalidDelimiter(format!(
+                    "Delimiter override must be a single byte character, got '{}'",
+                    raw
+                )));
+            }
+            Ok(DetectionResult {
+                value: delimiter.to_string(),
+                confidence: 1.0,
+                runner_up: None,
+            })
+        }
+        None => detect_delimiter(content),
+    }
+}
+
+/// The byte to hand the `csv` reader for `delimiter`, which must be exactly one byte long.
+pub fn delimiter_byte(delimiter: &str) -> Result<u8, DataScienceError> {
+    match delimiter.as_bytes() {
+        [byte] => Ok(*byte),
+        [] => Err(DataScienceError::InvalidDelimiter("Delimiter is empty".to_string())),
+        _ => Err(DataScienceError::InvalidDelimiter(format!(
+            "Delimiter '{}' is {} bytes long; only single-byte delimiters are supported",
+            display_delimiter(delimiter),
+            delimiter.len()
+        ))),
+    }
+}
+
+/// A CSV reader builder for `delimiter`, quoting as `options` says.
+///
+/// Fields are quoted with `"` unless `quote_char` is set, and a quote inside a
+/// quoted field is written twice (RFC 4180) unless `escape_char` is set, in
+/// which case that byte escapes it instead. Check the options with
+/// `check_quoting` first.
+pub fn csv_reader_builder(delimiter: u8, options: &CsvParseOptions) -> csv::ReaderBuilder {
+    let quote = options.quote_char.unwrap_or(b'"');
+    let mut builder = csv::ReaderBuilder::new();
+    builder.delimiter(delimiter).quote(quote);
+    if let Some(escape) = options.escape_char.filter(|&escape| escape != quote) {
+        builder.escape(Some(escape)).double_quote(false);
+    }
+    builder
+}
+
+// A record from `csv_records`, or the error that stopped the read
+type CsvRecord = Result<csv::StringRecord, DataScienceError>;
+
+/// Split `input` into its headers and data records with a `csv_reader_builder` reader.
+///
+/// Records may have any number of fields and blank lines are skipped. Header
+/// names are trimmed; without a header row the columns are named `column_1`,
+/// `column_2`, ... after the first record, which is then data.
+fn csv_records<R: Read>(
+    input: R,
+    delimiter: &str,
+    has_headers: bool,
+    options: &CsvParseOptions,
+) -> Result<(Vec<String>, impl Iterator<Item = CsvRecord> + use<R>), DataScienceError> {
+    check_quoting(options, delimiter)?;
+    let mut records = csv_reader_builder(delimiter_byte(delimiter)?, options)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(input)
+        .into_records()
+        .map(|record| record.map_err(|e| DataScienceError::Parse(format!("Failed to read CSV record: {}", e))));
+
+    let first = records.next().ok_or("File is empty")??;
+    if has_headers {
+        let headers = first.iter().map(|h| h.trim().to_string()).collect();
+        return Ok((headers, None.into_iter().chain(records)));
+    }
+    Ok((
+        generated_column_names(first.len()),
+        Some(Ok(first)).into_iter().chain(records),
+    ))
+}
+
+/// The quote and escape characters must be ASCII, not line breaks, and different from the delimiter.
+pub fn check_quoting(options: &CsvParseOptions, delimiter: &str) -> Result<(), DataScienceError> {
+    for (name, byte) in [("quote", options.quote_char), ("escape", options.escape_char)] {
+        let Some(byte) = byte else {
+            continue;
+        };
+        if !byte.is_ascii() || byte == b'\n' || byte == b'\r' {
+            return Err(DataScienceError::InvalidArgument(format!(
+                "The {} character must be an ASCII character other than a line break, not byte {}",
+                name, byte
+            )));
+        }
+        if delimiter.as_bytes() == [byte] {
+            return Err(DataScienceError::InvalidArgument(format!(
+                "The {} character '{}' can't also be the delimiter",
+                name,
+                char::from(byte).escape_default()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Pick the delimiter that splits the first lines into the most consistent field counts.
+///
+/// Each candidate scores the share of sampled lines with the same field count as
+/// the header line. Confidence is the winner's score minus the runner-up's, so a
+/// tie (e.g. `a;b,c` rows) has zero confidence.
+pub fn detect_delimiter(content: &str) -> Result<DetectionResult, DataScienceError> {
+    let sample = &content[..content.len().min(10000)]; // Sample first 10KB
+    let lines: Vec<&str> = sample.lines().take(10).collect(); // Check first 10 lines
+
+    let mut scores: Vec<(&str, f64)> = Vec::new();
+    if lines.len() >= 2 {
+        for delimiter in CANDIDATE_DELIMITERS {
+            let counts: Vec<usize> = lines.iter().map(|line| line.split(delimiter).count()).collect();
+
+            // A delimiter that doesn't appear in the header line can't be the right one
+            if counts[0] > 1 {
+                let consistency = counts.iter().filter(|&&count| count == counts[0]).count();
+                scores.push((delimiter, consistency as f64 / lines.len() as f64));
+            }
+        }
+    }
+    // Stable sort keeps candidate order on ties, so ',' still wins a tie
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let best = scores.first().copied();
+    let runner_up = scores.get(1).copied();
+    Ok(DetectionResult {
+        value: best.map_or(",", |(delimiter, _)| delimiter).to_string(),
+        confidence: best.map_or(0.0, |(_, score)| score - runner_up.map_or(0.0, |(_, s)| s)),
+        runner_up: runner_up.map(|(delimiter, _)| delimiter.to_string()),
+    })
+}
+
+fn display_delimiter(delimiter: &str) -> &str {
+    if delimiter == "\t" {
+        "\\t"
+    } else {
+        delimiter
+    }
+}
+
+/// Whether the first of `lines` is a header row: `options.has_headers` if set, else `detect_header_row`.
+pub fn resolve_has_headers<S: AsRef<str>>(lines: &[S], delimiter: &str, options: &CsvParseOptions) -> bool {
+    options
+        .has_headers
+        .unwrap_or_else(|| detect_header_row(lines, delimiter))
+}
+
+/// Guess whether the first of `lines` is a header row by contrasting it with the rows below.
+///
+/// Each column votes using up to `HEADER_SAMPLE_ROWS` following rows. A cell
+/// that reappears further down its column votes against a header. Otherwise a
+/// number, date, or boolean column votes for a header when the first row's
+/// cell is text, and against one when it isn't. Text columns don't vote, and
+/// a tie (including all-text files) falls back to `is_likely_header_row`.
+pub fn detect_header_row<S: AsRef<str>>(lines: &[S], delimiter: &str) -> bool {
+    let Some(first_row) = lines.first().map(AsRef::as_ref) else {
+        return false;
+    };
+    let rows: Vec<Vec<&str>> = lines[1..]
+        .iter()
+        .take(HEADER_SAMPLE_ROWS)
+        .map(|line| line.as_ref().split(delimiter).map(str::trim).collect())
+        .collect();
+
+    let mut votes = 0i64; // Positive favours a header
+    for (i, cell) in first_row.split(delimiter).map(str::trim).enumerate() {
+        if is_null_token(cell) {
+            continue;
+        }
+        let column: Vec<String> = rows
+            .iter()
+            .filter_map(|row| row.get(i))
+            .map(|v| v.to_string())
+            .collect();
+        if column.iter().any(|v| v == cell) {
+            votes -= 1;
+        } else if matches!(infer_data_type(&column).as_str(), "number" | "date" | "boolean") {
+            votes += if classify_value(cell) == "string" { 1 } else { -1 };
+        }
+    }
+
+    match votes.cmp(&0) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => is_likely_header_row(first_row, delimiter),
+    }
+}
+
+/// Column names taken from the header row, or `column_1..column_n` for headerless files.
+pub fn header_names(first_row: &str, delimiter: &str, has_headers: bool) -> Vec<String> {
+    if has_headers {
+        first_row.split(delimiter).map(|s| s.trim().to_string()).collect()
+    } else {
+        generated_column_names(first_row.split(delimiter).count())
+    }
+}
+
+pub fn generated_column_names(count: usize) -> Vec<String> {
+    (1..=count).map(|i| format!("column_{}", i)).collect()
+}
+
+pub fn is_likely_header_row(row: &str, delimiter: &str) -> bool {
+    let values: Vec<&str> = row.split(delimiter).collect();
+    if values.len() < 2 {
+        return false;
+    }
+
+    // Simple heuristic: if most values contain letters and are reasonably short
+    let text_like_count = values
+        .iter()
+        .filter(|v| {
+            let trimmed = v.trim();
+            !trimmed.is_empty() && trimmed.chars().any(|c| c.is_alphabetic()) && trimmed.len() < 50
+        })
+        .count();
+
+    text_like_count >= (values.len() + 1) / 2 // Majority are text-like
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "xlsx")]
+    use crate::test_support::xlsx_file;
+    use crate::test_support::{change_globals, csv_file, globals, path, temp_file};
+    use std::io::Write;
+
+    const PREAMBLE_CSV: &str =
+        "Report generated 2024-01-01\nSource: sensor export\n\nid,name,score\n1,a,3\n2,b,4\n3,c,5\n";
+
+    #[test]
+    fn preamble_is_detected_from_the_majority_column_count() {
+        assert_eq!(detect_preamble(PREAMBLE_CSV), 3);
+        assert_eq!(detect_preamble("a,b\n1,2\n"), 0);
+    }
+
+    #[test]
+    fn validation_skips_preamble_rows() {
+        let _globals = globals();
+        let file = csv_file(PREAMBLE_CSV);
+        let options = CsvParseOptions {
+            skip_rows: 3,
+            ..Default::default()
+        };
+        let validation = validate_csv_file(path(&file).to_string(), &options).unwrap();
+        assert_eq!(validation.estimated_rows, 3);
+        assert_eq!(validation.column_count, 3);
+        assert!(validation.has_headers);
+    }
+
+    #[test]
+    fn header_row_index_picks_the_header_line() {
+        let _globals = globals();
+        let file = csv_file(PREAMBLE_CSV);
+        let options = CsvParseOptions {
+            header_row_index: Some(3),
+            skip_rows: 1,
+            ..Default::default()
+        };
+        let columns = analyze_csv_columns(path(&file).to_string(), &options, 5, None).unwrap();
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0].name, "id");
+        assert_eq!(columns[2].data_type, "number");
+    }
+
+    fn quality(file: &tempfile::NamedTempFile, key_columns: &[&str]) -> Result<DataQualityReport, DataScienceError> {
+        let key_columns: Vec<String> = key_columns.iter().map(|c| c.to_string()).collect();
+        validate_data_quality(
+            path(file).to_string(),
+            &CsvParseOptions::default(),
+            &key_columns,
+            &QualityWeights::default(),
+        )
+    }
+
+    #[test]
+    fn column_issues_name_the_offending_column() {
+        let _globals = globals();
+        let file = csv_file(
+            "id,amount,answer,name\n1,10,Yes,alice\n2,abc,yes,bob\n3,30,YES, carol\n4,40,No,dave\n5,NA,no,erin\n",
+        );
+        let report = quality(&file, &[]).unwrap();
+
+        assert_eq!(report.column_issues["amount"], vec!["mixed_types"]);
+        assert_eq!(report.column_issues["answer"], vec!["inconsistent_casing"]);
+        assert_eq!(report.column_issues["name"], vec!["leading_trailing_whitespace"]);
+        assert!(!report.column_issues.contains_key("id"));
+        assert!(report
+            .issues
+            .iter()
+            .any(|i| i.contains("'answer'") && i.contains("YES/Yes/yes")));
+        assert!(report
+            .issues
+            .iter()
+            .any(|i| i.contains("'amount' mixes value types (3 number, 1 string)")));
+    }
+
+    #[test]
+    fn duplicates_ignore_field_whitespace() {
+        let _globals = globals();
+        let file = csv_file("id,name\r\n1,a\r\n1, a\r\n2,b\r\n2,c\r\n3,d\r\n");
+        let report = quality(&file, &[]).unwrap();
+        assert_eq!(report.duplicate_count, 1);
+        assert_eq!(report.duplicate_examples, vec!["1, a"]);
+    }
+
+    #[test]
+    fn duplicates_by_key_columns() {
+        let _globals = globals();
+        let file = csv_file("id,name\r\n1,a\r\n1, a\r\n2,b\r\n2,c\r\n3,d\r\n");
+        let report = quality(&file, &["id"]).unwrap();
+        assert_eq!(report.duplicate_count, 2);
+        assert_eq!(report.duplicate_examples, vec!["1", "2"]);
+        assert!(report.issues[0].contains("duplicate key (id)"), "{:?}", report.issues);
+
+        assert!(quality(&file, &["missing"]).is_err());
+    }
+
+    #[test]
+    fn line_endings_are_named() {
+        assert_eq!(detect_line_ending("a\r\nb\r\n"), "CRLF");
+        assert_eq!(detect_line_ending("a\nb"), "LF");
+        assert_eq!(detect_line_ending("a\rb\r"), "CR");
+        assert_eq!(detect_line_ending("a\r\nb\n"), "mixed");
+        assert_eq!(detect_line_ending("a"), "none");
+    }
+
+    #[test]
+    fn carriage_return_only_file() {
+        let _globals = globals();
+        let file = csv_file("id,name\r1,a\r2,b\r1,a\r");
+        let validation = validate_csv_file(path(&file).to_string(), &CsvParseOptions::default()).unwrap();
+        assert_eq!(validation.line_ending, "CR");
+        assert_eq!(validation.estimated_rows, 3);
+        assert_eq!(validation.column_count, 2);
+        assert_eq!(quality(&file, &[]).unwrap().duplicate_count, 1);
+    }
+
+    #[test]
+    fn mixed_line_endings_file() {
+        let _globals = globals();
+        let file = csv_file("id,name\r\n1,a\n2,b\r3,c\r\n1,a\n");
+        let validation = validate_csv_file(path(&file).to_string(), &CsvParseOptions::default()).unwrap();
+        assert_eq!(validation.line_ending, "mixed");
+        assert_eq!(validation.estimated_rows, 4);
+        assert_eq!(quality(&file, &[]).unwrap().duplicate_count, 1);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_analysis_matches_the_serial_path() {
+        let _globals = globals();
+        let (column_count, row_count) = (200, 300);
+        let headers: Vec<String> = (0..column_count).map(|c| format!("c{}", c)).collect();
+        let columns: Vec<Vec<String>> = (0..column_count)
+            .map(|c| {
+                (0..row_count)
+                    .map(|r| match c % 4 {
+                        0 => (r * c).to_string(),
+                        1 => format!("s{}", r % 7),
+                        2 if r % 5 == 0 => "NA".to_string(),
+                        2 => "2024-01-02".to_string(),
+                        _ => "true".to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+        assert!(headers.len() > PARALLEL_COLUMN_THRESHOLD);
+
+        let parallel = analyze_columns(&headers, &columns, 5);
+        let serial: Vec<ColumnAnalysis> = headers
+            .iter()
+            .zip(&columns)
+            .map(|(header, values)| analyze_column(header, values, row_count, 5, None))
+            .collect();
+        assert_eq!(format!("{:?}", parallel), format!("{:?}", serial));
+    }
+
+    #[test]
+    fn headerless_numeric_file_with_the_override() {
+        let _globals = globals();
+        let file = csv_file("1,2,3\n4,5,6\n7,8,9\n");
+        let options = CsvParseOptions {
+            has_headers: Some(false),
+            ..Default::default()
+        };
+        let validation = validate_csv_file(path(&file).to_string(), &options).unwrap();
+        assert!(!validation.has_headers);
+        assert_eq!(validation.estimated_rows, 3);
+
+        let columns = analyze_csv_columns(path(&file).to_string(), &options, 5, None).unwrap();
+        assert_eq!(columns[0].name, "column_1");
+        assert_eq!(columns[0].sample_values, vec!["1", "4", "7"]);
+    }
+
+    #[test]
+    fn numeric_header_forced_on() {
+        let _globals = globals();
+        let file = csv_file("2020,2021\n1,2\n");
+        let options = CsvParseOptions {
+            has_headers: Some(true),
+            ..Default::default()
+        };
+        let columns = analyze_csv_columns(path(&file).to_string(), &options, 5, None).unwrap();
+        assert_eq!(columns[0].name, "2020");
+        assert_eq!(columns[0].sample_values, vec!["1"]);
+    }
+
+    #[test]
+    fn samples_skip_nulls_and_spread_over_the_file() {
+        let _globals = globals();
+        let mut contents = String::from("id,city\n");
+        for i in 0..10 {
+            contents.push_str(&format!("{},\n", i));
+        }
+        for i in 10..30 {
+            let city = ["Oslo", "NA", "Rome"][i % 3];
+            contents.push_str(&format!("{},{}\n", i, city));
+        }
+        contents.push_str("30,Lima\n");
+        let file = csv_file(&contents);
+
+        let columns = analyze_csv_columns(path(&file).to_string(), &CsvParseOptions::default(), 5, None).unwrap();
+        let city = &columns[1];
+        assert!(city.sample_values.iter().all(|v| !v.is_empty() && v != "NA"));
+        assert!(city.sample_values.contains(&"Lima".to_string()));
+        assert_eq!(city.most_common[0].1, 7);
+        assert!(city.most_common.iter().all(|(v, _)| !v.is_empty() && v != "NA"));
+
+        let columns = analyze_csv_columns(path(&file).to_string(), &CsvParseOptions::default(), 2, None).unwrap();
+        assert_eq!(columns[0].sample_values.len(), 2);
+        assert_eq!(columns[0].most_common.len(), 2);
+        let columns = analyze_csv_columns(path(&file).to_string(), &CsvParseOptions::default(), 0, None).unwrap();
+        assert!(columns[0].sample_values.is_empty());
+    }
+
+    #[test]
+    fn tied_delimiters_have_no_confidence() {
+        let detection = detect_delimiter("a;b,c\n1;2,3\n4;5,6\n").unwrap();
+        assert_eq!(detection.value, ",");
+        assert_eq!(detection.confidence, 0.0);
+        assert_eq!(detection.runner_up.as_deref(), Some(";"));
+
+        let detection = detect_delimiter("a,b\n1,2\n3,4\n").unwrap();
+        assert_eq!(detection.confidence, 1.0);
+        assert!(detection.runner_up.is_none());
+    }
+
+    #[test]
+    fn ambiguous_delimiter_is_a_validation_warning() {
+        let _globals = globals();
+        let file = csv_file("a;b,c\n1;2,3\n4;5,6\n");
+        let validation = validate_csv_file(path(&file).to_string(), &CsvParseOptions::default()).unwrap();
+        assert_eq!(validation.delimiter, ",");
+        assert_eq!(validation.delimiter_detection.runner_up.as_deref(), Some(";"));
+        assert_eq!(validation.warnings.len(), 1, "{:?}", validation.warnings);
+
+        // Naming the delimiter leaves nothing to confirm
+        let options = CsvParseOptions {
+            delimiter_override: Some(";".to_string()),
+            ..Default::default()
+        };
+        let validation = validate_csv_file(path(&file).to_string(), &options).unwrap();
+        assert_eq!(validation.delimiter, ";");
+        assert!(validation.warnings.is_empty());
+    }
+
+    #[test]
+    fn clear_files_have_full_confidence() {
+        let _globals = globals();
+        let file = csv_file("a,b\n1,2\n3,4\n");
+        let validation = validate_csv_file(path(&file).to_string(), &CsvParseOptions::default()).unwrap();
+        assert!(validation.warnings.is_empty());
+        assert_eq!(validation.encoding_detection.value, "UTF-8");
+        assert_eq!(validation.encoding_detection.confidence, 1.0);
+    }
+
+    fn thousand_rows() -> tempfile::NamedTempFile {
+        let mut contents = String::from("id,v\r\n");
+        for i in 0..1000 {
+            contents.push_str(&format!("{},{}\r\n", i, i % 7));
+        }
+        csv_file(&contents)
+    }
+
+    fn sampled(file: &tempfile::NamedTempFile, mode: &str, size: usize, seed: Option<u64>) -> SampleOutcome {
+        let spec = SampleSpec {
+            mode: mode.to_string(),
+            size,
+            seed,
+        };
+        collect_sampled_columns(
+            path(file).to_string(),
+            &CsvParseOptions::default(),
+            &spec,
+            &AtomicBool::new(false),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn head_and_every_kth_samples() {
+        let _globals = globals();
+        let file = thousand_rows();
+        let head = sampled(&file, "head", 10, None);
+        assert_eq!(head.headers, vec!["id", "v"]);
+        assert_eq!(head.columns[0].len(), 10);
+        assert_eq!(head.columns[0][9], "9");
+        assert_eq!(head.rows_scanned, 10);
+
+        let every_kth = sampled(&file, "every_kth", 100, None);
+        let expected: Vec<String> = (0..10).map(|i| (i * 100).to_string()).collect();
+        assert_eq!(every_kth.columns[0], expected);
+        assert_eq!(every_kth.rows_scanned, 1000);
+    }
+
+    #[test]
+    fn random_samples_are_sized_and_seeded() {
+        let _globals = globals();
+        let file = thousand_rows();
+        let first = sampled(&file, "random", 50, Some(7));
+        assert_eq!(first.columns[0].len(), 50);
+        assert_eq!(first.columns, sampled(&file, "random", 50, Some(7)).columns);
+        assert_ne!(first.columns, sampled(&file, "random", 50, Some(8)).columns);
+
+        // Sampled rows keep file order and reach past the start of the file
+        let ids: Vec<usize> = first.columns[0].iter().map(|v| v.parse().unwrap()).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(*ids.last().unwrap() > 500);
+
+        assert_eq!(sampled(&file, "random", 5000, None).columns[0].len(), 1000);
+    }
+
+    #[test]
+    fn invalid_sample_specs_are_rejected() {
+        let _globals = globals();
+        let file = thousand_rows();
+        for (mode, size) in [("random", 0), ("nope", 3)] {
+            let spec = SampleSpec {
+                mode: mode.to_string(),
+                size,
+                seed: None,
+            };
+            let result = collect_sampled_columns(
+                path(&file).to_string(),
+                &CsvParseOptions::default(),
+                &spec,
+                &AtomicBool::new(false),
+            );
+            assert!(result.is_err(), "{}", mode);
+        }
+    }
+
+    #[test]
+    fn schema_of_a_mixed_file() {
+        let _globals = globals();
+        let file = csv_file(
+            "id,email,joined,score,active,note,site,stamp\n\
+             1,a@x.io,2024-01-02,1.5,true,hi,https://a.b,2024-01-02T10:00:00Z\n\
+             2,b@y.org,2024-02-03,,false,,http://c.d,2024-01-03T10:00:00Z\n\
+             3,c@z.com,2024-03-04,2,true,yo,https://e.f/g,2024-01-04T10:00:00+02:00\n",
+        );
+        let schema = infer_schema(path(&file).to_string(), &CsvParseOptions::default()).unwrap();
+
+        let properties = &schema["properties"];
+        assert_eq!(properties["id"]["type"], "number");
+        assert_eq!(properties["score"]["type"], serde_json::json!(["number", "null"]));
+        assert_eq!(properties["active"]["type"], "boolean");
+        assert_eq!(properties["note"]["type"], serde_json::json!(["string", "null"]));
+        assert!(properties["note"].get("format").is_none());
+        assert_eq!(properties["email"]["format"], "email");
+        assert_eq!(properties["joined"]["format"], "date");
+        assert_eq!(properties["site"]["format"], "uri");
+        assert_eq!(properties["stamp"]["format"], "date-time");
+
+        let columns = schema["x-columns"].as_array().unwrap();
+        assert_eq!(columns[0]["nullable"], false);
+        assert_eq!(columns[3]["name"], "score");
+        assert_eq!(columns[3]["nullable"], true);
+        assert_eq!(columns[1]["pattern"], "email");
+        assert_eq!(columns[2]["date_format"], "%Y-%m-%d");
+    }
+
+    fn reference_schema() -> serde_json::Value {
+        let file = csv_file("id,email,joined,score\n1,a@x.io,2024-01-02,1.5\n2,b@y.org,2024-02-03,\n");
+        infer_schema(path(&file).to_string(), &CsvParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn conforming_files_pass_schema_validation() {
+        let _globals = globals();
+        let schema = reference_schema();
+        let file = csv_file("id,email,joined,score\n3,c@z.io,2024-03-04,2\n4,d@e.fr,2024-03-05,\n");
+        let report =
+            validate_against_schema(path(&file).to_string(), &CsvParseOptions::default(), &schema, 100).unwrap();
+        assert!(report.is_valid, "{:?}", report);
+        assert_eq!(report.rows_checked, 2);
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn schema_violations_are_reported_by_line() {
+        let _globals = globals();
+        let schema = reference_schema();
+        let file = csv_file("id,email,score,extra\nx,a@x.io,1,q\n,not-an-email,2,q\n3,c@z.io\n4,d@e.fr,,q\n");
+        let report =
+            validate_against_schema(path(&file).to_string(), &CsvParseOptions::default(), &schema, 100).unwrap();
+        assert!(!report.is_valid);
+        assert_eq!(report.missing_columns, vec!["joined"]);
+        assert_eq!(report.extra_columns, vec!["extra"]);
+        let kinds: Vec<(Option<u64>, &str)> = report.violations.iter().map(|v| (v.line, v.kind.as_str())).collect();
+        assert_eq!(
+            kinds,
+            [
+                (None, "missing_column"),
+                (None, "extra_column"),
+                (Some(2), "wrong_type"),
+                (Some(3), "unexpected_null"),
+                (Some(3), "wrong_type"),
+                (Some(4), "field_count"),
+            ]
+        );
+
+        let capped = validate_against_schema(path(&file).to_string(), &CsvParseOptions::default(), &schema, 2).unwrap();
+        assert_eq!(capped.violations.len(), 2);
+        assert_eq!(capped.violation_count, 6);
+        assert!(capped.truncated);
+    }
+
+    #[test]
+    fn schema_without_properties_is_rejected() {
+        let _globals = globals();
+        let file = csv_file("id\n1\n");
+        let result = validate_against_schema(
+            path(&file).to_string(),
+            &CsvParseOptions::default(),
+            &serde_json::json!({}),
+            10,
+        );
+        assert!(result.is_err());
+    }
+
+    const FIXED_WIDTH: &str = "NAME      AGE CITY\nAlice      30 Paris\nBob        4\n\nZoë        25 Köln  extra\n";
+
+    #[test]
+    fn fixed_width_fields_are_sliced_by_character() {
+        let file = temp_file(".txt", FIXED_WIDTH.as_bytes());
+        let (headers, rows) = read_fixed_width(path(&file).to_string(), &[10, 4, 5], None).unwrap();
+        assert_eq!(headers, vec!["NAME", "AGE", "CITY"]);
+        // Short lines are padded, blank lines skipped, and text past the last field dropped
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Alice", "30", "Paris"],
+                vec!["Bob", "4", ""],
+                vec!["Zoë", "25", "Köln"]
+            ]
+        );
+    }
+
+    #[test]
+    fn fixed_width_rejects_bad_layouts() {
+        let file = temp_file(".txt", FIXED_WIDTH.as_bytes());
+        assert!(read_fixed_width(path(&file).to_string(), &[10, 0], None).is_err());
+        assert!(read_fixed_width(path(&file).to_string(), &[10, 4], Some(vec!["a".to_string()])).is_err());
+    }
+
+    #[test]
+    fn unsupported_creation_time_falls_back_to_modified() {
+        let modified: DateTime<Utc> = SystemTime::UNIX_EPOCH.into();
+        let unsupported = std::io::Error::new(ErrorKind::Unsupported, "creation time is not available");
+        assert_eq!(creation_time(Err(unsupported), modified).unwrap(), modified);
+
+        let created = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(60);
+        assert_eq!(
+            creation_time(Ok(created), modified).unwrap(),
+            DateTime::<Utc>::from(created)
+        );
+
+        let denied = std::io::Error::new(ErrorKind::PermissionDenied, "denied");
+        assert!(creation_time(Err(denied), modified).is_err());
+    }
+
+    #[test]
+    fn reading_a_file_succeeds_whether_or_not_creation_time_is_recorded() {
+        let _globals = globals();
+        let file = csv_file("a,b\n1,2\n");
+        let data = read_csv_file(path(&file).to_string(), &CsvParseOptions::default()).unwrap();
+        assert!(!data.metadata.created.is_empty());
+        assert!(read_csv_file("/no/such/file.csv".to_string(), &CsvParseOptions::default()).is_err());
+    }
+
+    fn quality_score(contents: &str) -> f64 {
+        quality(&csv_file(contents), &[]).unwrap().overall_score
+    }
+
+    #[test]
+    fn quality_score_drops_as_problems_accumulate() {
+        let _globals = globals();
+        let clean = quality_score("id,name\n1,a\n2,b\n3,c\n4,d\n");
+        let some_duplicates = quality_score("id,name\n1,a\n1,a\n3,c\n4,d\n");
+        let all_duplicates = quality_score("id,name\n1,a\n1,a\n1,a\n1,a\n");
+        let sparse = quality_score("id,name\n1,\n,\n3,\n,d\n");
+
+        assert_eq!(clean, 100.0);
+        assert!(
+            all_duplicates < some_duplicates && some_duplicates < clean,
+            "{} {}",
+            all_duplicates,
+            some_duplicates
+        );
+        assert!(sparse < 80.0, "{}", sparse);
+    }
+
+    #[test]
+    fn quality_weights() {
+        let _globals = globals();
+        let file = csv_file("id,name\n1,a\n2,b\n");
+        let zero = QualityWeights {
+            completeness: 0.0,
+            duplicates: 0.0,
+            column_issues: 0.0,
+        };
+        let result = validate_data_quality(path(&file).to_string(), &CsvParseOptions::default(), &[], &zero);
+        assert!(result.is_err());
+
+        // Weights left out of a request keep their defaults
+        let weights: QualityWeights = serde_json::from_value(serde_json::json!({"duplicates": 1.0})).unwrap();
+        assert_eq!(weights.duplicates, 1.0);
+        assert_eq!(weights.completeness, QualityWeights::default().completeness);
+    }
+
+    fn key_columns(contents: &str) -> Vec<Vec<String>> {
+        let file = csv_file(contents);
+        detect_key_columns(path(&file).to_string(), &CsvParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn an_id_column_is_a_key() {
+        let _globals = globals();
+        assert_eq!(
+            key_columns("id,first,last\n1,Ann,Lee\n2,Ann,Kim\n3,Bob,Lee\n"),
+            vec![vec!["id"]]
+        );
+    }
+
+    #[test]
+    fn composite_keys_when_no_single_column_is_unique() {
+        let _globals = globals();
+        assert_eq!(
+            key_columns("first,last,city\nAnn,Lee,X\nAnn,Kim,X\nBob,Lee,Y\nBob,Kim,Y\n"),
+            vec![vec!["first", "last"], vec!["last", "city"]]
+        );
+    }
+
+    #[test]
+    fn sql_identifiers_are_sanitized() {
+        assert_eq!(sanitize_sql_identifier("unit price"), "unit_price");
+        assert_eq!(sanitize_sql_identifier("1st col"), "_1st_col");
+    }
+
+    #[test]
+    fn streaming_profile_matches_exact_analysis() {
+        let _globals = globals();
+        let mut contents = String::from("id,value,cat,when\n");
+        for i in 0..5000 {
+            let value = if i % 10 == 0 {
+                "NA".to_string()
+            } else {
+                format!("{}.5", i % 97)
+            };
+            contents.push_str(&format!("{},{},{},2024-01-{:02}\n", i, value, i % 37, i % 28 + 1));
+        }
+        let file = csv_file(&contents);
+        let file_path = path(&file).to_string();
+
+        let profile = profile_csv_streaming(
+            file_path.clone(),
+            &CsvParseOptions::default(),
+            None,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        let exact = analyze_csv_columns(file_path, &CsvParseOptions::default(), 5, None).unwrap();
+        assert_eq!(profile.rows_scanned, 5000);
+        for (streamed, exact) in profile.columns.iter().zip(&exact) {
+            assert_eq!(streamed.analysis.name, exact.name);
+            assert_eq!(streamed.analysis.data_type, exact.data_type);
+            assert_eq!(streamed.analysis.null_percentage, exact.null_percentage);
+            let error =
+                (streamed.analysis.unique_count as f64 - exact.unique_count as f64).abs() / exact.unique_count as f64;
+            assert!(
+                error < 0.05,
+                "{}: {} vs {}",
+                exact.name,
+                streamed.analysis.unique_count,
+                exact.unique_count
+            );
+        }
+        assert_eq!(profile.columns[3].analysis.date_format.as_deref(), Some("%Y-%m-%d"));
+    }
+
+    #[test]
+    fn streaming_numeric_stats() {
+        let _globals = globals();
+        let mut contents = String::from("value\n");
+        for i in 0..5000 {
+            if i % 10 == 0 {
+                contents.push_str("NA\n");
+            } else {
+                contents.push_str(&format!("{}.5\n", i % 97));
+            }
+        }
+        let file = csv_file(&contents);
+        let profile = profile_csv_streaming(
+            path(&file).to_string(),
+            &CsvParseOptions::default(),
+            None,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        let numbers: Vec<f64> = (0..5000)
+            .filter(|i| i % 10 != 0)
+            .map(|i| (i % 97) as f64 + 0.5)
+            .collect();
+        let mean = numbers.iter().sum::<f64>() / numbers.len() as f64;
+        let variance = numbers.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (numbers.len() - 1) as f64;
+        let stats = &profile.columns[0].stats;
+        assert_eq!(stats.numeric_count, numbers.len());
+        assert_eq!(stats.null_count, 500);
+        assert!((stats.mean.unwrap() - mean).abs() < 1e-9);
+        assert!((stats.variance.unwrap() - variance).abs() < 1e-6);
+        assert_eq!(stats.min, Some(0.5));
+        assert_eq!(stats.max, Some(96.5));
+        assert_eq!(stats.reservoir_sample.len(), STREAMING_RESERVOIR_SIZE);
+    }
+
+    fn validate(file: &tempfile::NamedTempFile) -> CsvValidationResult {
+        validate_csv_file(path(file).to_string(), &CsvParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn binary_file_named_csv_is_invalid() {
+        let _globals = globals();
+        let mut bytes = b"PK\x03\x04".to_vec();
+        bytes.extend((0..2000u32).map(|i| (i * 7 % 256) as u8));
+        let file = temp_file(".csv", &bytes);
+        let validation = validate(&file);
+        assert!(!validation.is_valid);
+        assert!(validation.invalid_reason.unwrap().contains("binary"));
+    }
+
+    #[test]
+    fn ragged_rows_and_unsplittable_lines_are_invalid() {
+        let _globals = globals();
+        let validation = validate(&csv_file("a,b,c\n1\n2\n3,4,5\n6\n7\n"));
+        assert!(!validation.is_valid, "{:?}", validation);
+        assert!(validation.invalid_reason.unwrap().contains("Only 1 of the first 5"));
+
+        assert!(!validate(&csv_file(&"x".repeat(10_000))).is_valid);
+    }
+
+    #[test]
+    fn quoted_delimiters_are_still_valid() {
+        let _globals = globals();
+        let validation = validate(&csv_file("a,b\n1,\"x,y\"\n2,3\n"));
+        assert!(validation.is_valid);
+        assert!(validation.invalid_reason.is_none());
+    }
+
+    #[test]
+    fn delimiters_must_be_one_byte() {
+        assert_eq!(delimiter_byte("\t").unwrap(), b'\t');
+        assert_eq!(delimiter_byte("|").unwrap(), b'|');
+        assert!(matches!(delimiter_byte(""), Err(DataScienceError::InvalidDelimiter(_))));
+        assert!(matches!(
+            delimiter_byte("::"),
+            Err(DataScienceError::InvalidDelimiter(_))
+        ));
+        assert!(matches!(
+            delimiter_byte("§"),
+            Err(DataScienceError::InvalidDelimiter(_))
+        ));
+
+        assert_eq!(resolve_delimiter("", Some("\\t")).unwrap(), "\t");
+        assert!(resolve_delimiter("", Some("::")).is_err());
+    }
+
+    #[test]
+    fn missing_files_and_directories_are_typed() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.csv");
+        let options = CsvParseOptions::default();
+
+        let error = read_csv_file(missing.to_str().unwrap().to_string(), &options).unwrap_err();
+        assert!(matches!(error, DataScienceError::FileNotFound { .. }), "{error:?}");
+        assert_eq!(error.kind(), "file_not_found");
+
+        let error = read_csv_file(dir.path().to_str().unwrap().to_string(), &options).unwrap_err();
+        assert_eq!(error.kind(), "not_a_file");
+    }
+
+    #[test]
+    fn constant_and_nearly_constant_columns() {
+        let _globals = globals();
+        let mut contents = String::from("id,status,flag\n");
+        for i in 0..100 {
+            contents.push_str(&format!("{},active,{}\n", i, if i == 0 { "b" } else { "a" }));
+        }
+        let file = csv_file(&contents);
+        let columns = analyze_csv_columns(path(&file).to_string(), &CsvParseOptions::default(), 5, None).unwrap();
+        let column = |name: &str| columns.iter().find(|c| c.name == name).unwrap();
+
+        assert!(column("status").is_constant);
+        assert_eq!(column("status").dominant_value_ratio, 1.0);
+        assert!(!column("flag").is_constant);
+        assert!((column("flag").dominant_value_ratio - 0.99).abs() < 1e-9);
+        assert!(!column("id").is_constant);
+        assert!((column("id").dominant_value_ratio - 0.01).abs() < 1e-9);
+
+        let report = quality(&file, &[]).unwrap();
+        let recommendations = &report.recommendations;
+        assert!(
+            recommendations.contains(&"Column 'status' is constant; consider removing".to_string()),
+            "{:?}",
+            recommendations
+        );
+        assert!(recommendations
+            .iter()
+            .any(|r| r.starts_with("Column 'flag' is nearly constant (99.0%")));
+        assert!(!recommendations.iter().any(|r| r.contains("'id'")));
+        assert!(!report.column_issues.contains_key("status"));
+    }
+
+    #[test]
+    fn dropped_file_names_are_cut_to_their_base_name() {
+        let csv = b"a,b\n1,2\n";
+        assert_eq!(
+            check_dropped_file(csv, "../../etc/passwd.csv", 1024).unwrap(),
+            "passwd.csv"
+        );
+        assert_eq!(check_dropped_file(csv, "..\\..\\win\\x.csv", 1024).unwrap(), "x.csv");
+        assert_eq!(check_dropped_file(csv, "a:b?.csv", 1024).unwrap(), "a_b_.csv");
+        assert!(check_dropped_file(csv, "..", 1024).is_err());
+        assert!(check_dropped_file(csv, "dir/", 1024).is_err());
+    }
+
+    #[test]
+    fn oversized_empty_and_binary_drops_are_rejected() {
+        let error = check_dropped_file(&[b'a'; 2048], "big.csv", 1024).unwrap_err();
+        assert!(error.to_string().contains("over the 1024 byte limit"), "{}", error);
+        assert_eq!(check_dropped_file(&[b'a'; 1024], "edge.csv", 1024).unwrap(), "edge.csv");
+        assert!(check_dropped_file(b"", "empty.csv", 1024).is_err());
+        assert!(check_dropped_file(&[0, 1, 2, 3, 0, 0], "binary.csv", 1024).is_err());
+        assert!(check_dropped_file(&[0x1f, 0x8b, 8, 0, 0, 0], "data.csv.gz", 1024).is_ok());
+    }
+
+    #[test]
+    fn byte_order_marks_are_reported() {
+        let _globals = globals();
+        let text = "id,name\n1,Zoë\n2,Ann\n3,Bob\n";
+        let mut utf8_bom = vec![0xEF, 0xBB, 0xBF];
+        utf8_bom.extend_from_slice(text.as_bytes());
+        let mut utf16_bom = vec![0xFF, 0xFE];
+        for unit in text.encode_utf16() {
+            utf16_bom.extend_from_slice(&unit.to_le_bytes());
+        }
+        let cases = [
+            (text.as_bytes().to_vec(), "UTF-8", None),
+            (utf8_bom, "UTF-8", Some("UTF-8")),
+            (utf16_bom, "UTF-16LE", Some("UTF-16LE")),
+        ];
+
+        for (bytes, encoding, bom) in cases {
+            let file = temp_file(".csv", &bytes);
+            let validation = validate(&file);
+            assert!(validation.is_valid, "{:?}", validation.invalid_reason);
+            assert_eq!(validation.encoding, encoding);
+            assert_eq!(validation.encoding_report.encoding, encoding);
+            assert_eq!(validation.encoding_report.bom.as_deref(), bom);
+            assert_eq!(validation.column_count, 2);
+            if bom.is_some() {
+                // A BOM wins over the detector's guess
+                assert_eq!(validation.encoding_report.confidence, 1.0);
+            }
+
+            let content = read_csv_file(path(&file).to_string(), &CsvParseOptions::default()).unwrap();
+            assert_eq!(content.encoding, encoding);
+            assert_eq!(content.encoding_report.bom.as_deref(), bom);
+            assert!(content.content.starts_with("id,name\n1,Zoë"), "{:?}", bom);
+        }
+    }
+
+    #[test]
+    fn three_ranges_match_a_full_read() {
+        let file = csv_file("\u{feff}id,note\r\n1,\"multi\nline, \"\"quoted\"\"\"\n2,plain\n3,\"x\ny\"\n4,z\n5,last\n");
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_path(path(&file))
+            .unwrap();
+        let mut expected: Vec<Vec<String>> = reader
+            .records()
+            .map(|record| record.unwrap().iter().map(String::from).collect())
+            .collect();
+        expected[0][0] = "id".to_string();
+
+        let first = read_csv_range(path(&file), 0, 2, &CsvParseOptions::default()).unwrap();
+        let second = read_csv_range(path(&file), first.next_offset, 2, &CsvParseOptions::default()).unwrap();
+        let third = read_csv_range(path(&file), second.next_offset, 2, &CsvParseOptions::default()).unwrap();
+        let end = read_csv_range(path(&file), third.next_offset, 2, &CsvParseOptions::default()).unwrap();
+
+        assert!(!first.done);
+        assert_eq!(second.start_offset, first.next_offset);
+        assert!(second.headers.is_none());
+        assert!(third.done);
+        let mut rows = vec![first.headers.clone().unwrap()];
+        rows.extend(first.rows);
+        rows.extend(second.rows);
+        rows.extend(third.rows);
+        assert_eq!(rows, expected);
+        assert!(end.rows.is_empty() && end.done);
+    }
+
+    #[test]
+    fn ranges_align_on_unquoted_newlines() {
+        let text = "a,b\n1,\"q\nr\"\n2,s\n";
+        let file = csv_file(text);
+
+        // Starting inside the first row, its quoted newline isn't a row boundary
+        let inside = read_csv_range(path(&file), 5, 10, &CsvParseOptions::default()).unwrap();
+        assert_eq!(inside.start_offset as usize, text.find("2,s").unwrap());
+        assert_eq!(inside.rows, vec![vec!["2".to_string(), "s".to_string()]]);
+
+        let at_line_start = read_csv_range(path(&file), 4, 10, &CsvParseOptions::default()).unwrap();
+        assert_eq!(at_line_start.start_offset, 4);
+        assert_eq!(at_line_start.rows.len(), 2);
+        let pipe = CsvParseOptions {
+            delimiter_override: Some("|".to_string()),
+            ..Default::default()
+        };
+        assert!(read_csv_range(path(&file), 0, 0, &pipe).unwrap().rows.is_empty());
+
+        // Just past a quoted newline looks like a line start, but is inside the record
+        let after_quoted_newline = read_csv_range(path(&file), 9, 10, &CsvParseOptions::default()).unwrap();
+        assert_eq!(after_quoted_newline.start_offset, inside.start_offset);
+        assert_eq!(after_quoted_newline.rows, inside.rows);
+    }
+
+    #[test]
+    fn ranges_follow_custom_quoting() {
+        let text = "a,b\n1,'it\\'s\n2,x'\n3,y\n";
+        let file = csv_file(text);
+        let options = CsvParseOptions {
+            quote_char: Some(b'\''),
+            escape_char: Some(b'\\'),
+            ..Default::default()
+        };
+
+        // The quoted newline is followed by what looks like a row, but it's part of row 1
+        let range = read_csv_range(path(&file), text.find("2,x").unwrap() as u64, 10, &options).unwrap();
+        assert_eq!(range.start_offset as usize, text.find("3,y").unwrap());
+        assert_eq!(range.rows, vec![vec!["3".to_string(), "y".to_string()]]);
+
+        let all = read_csv_range(path(&file), 0, 10, &options).unwrap();
+        assert_eq!(all.rows[0], vec!["1".to_string(), "it's\n2,x".to_string()]);
+    }
+
+    const PRICES_AND_COLORS: &str =
+        "id,price,color,note\n1,10,red,a\n2,20,blue,b\n3,,red,c\n4,40,red,d\n5,30,green,e\n";
+
+    #[test]
+    fn single_numeric_and_categorical_columns() {
+        let _globals = globals();
+        let file = csv_file(PRICES_AND_COLORS);
+        let options = CsvParseOptions::default();
+
+        let price = analyze_single_column_file(path(&file), "price", &options).unwrap();
+        assert_eq!(price.analysis.data_type, "number");
+        assert!(price.value_counts.is_none());
+        let stats = price.numeric_stats.unwrap();
+        assert_eq!(
+            (stats.count, stats.min, stats.max, stats.sum, stats.mean, stats.median),
+            (4, 10.0, 40.0, 100.0, 25.0, 25.0)
+        );
+        assert!((stats.std_dev.unwrap() - 12.909944).abs() < 1e-5);
+
+        // The single column's analysis is the one profiling the whole file gives
+        let all_columns = analyze_csv_columns(path(&file).to_string(), &options, 5, None).unwrap();
+        let color = analyze_single_column_file(path(&file), "color", &options).unwrap();
+        assert_eq!(
+            serde_json::to_value(&color.analysis).unwrap(),
+            serde_json::to_value(&all_columns[2]).unwrap()
+        );
+        assert!(color.numeric_stats.is_none());
+        assert_eq!(
+            color.value_counts.unwrap(),
+            vec![
+                ("red".to_string(), 3),
+                ("blue".to_string(), 1),
+                ("green".to_string(), 1)
+            ]
+        );
+
+        assert!(matches!(
+            analyze_single_column_file(path(&file), "nope", &options),
+            Err(DataScienceError::ColumnNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn high_cardinality_columns_are_estimated() {
+        let _globals = globals();
+        let mut contents = String::from("id,cat\n");
+        for i in 0..50_000 {
+            contents.push_str(&format!("user-{},{}\n", i, ["a", "b", "c"][i % 3]));
+        }
+        let file = csv_file(&contents);
+        let options = CsvParseOptions::default();
+        let exact = analyze_csv_columns(path(&file).to_string(), &options, 5, None).unwrap();
+        let approximate = analyze_csv_columns(path(&file).to_string(), &options, 5, Some(1_000)).unwrap();
+
+        assert_eq!(exact[0].unique_count, 50_000);
+        assert!(!exact[0].is_estimated);
+        assert!(approximate[0].is_estimated);
+        let error = (approximate[0].unique_count as f64 - 50_000.0).abs() / 50_000.0;
+        assert!(error < 0.05, "{}", approximate[0].unique_count);
+        assert!(approximate[0].most_common.is_empty());
+        assert_eq!(approximate[0].sample_values.len(), 5);
+        assert!(approximate[0].dominant_value_ratio < 0.01);
+
+        // Columns under the threshold are counted exactly
+        assert!(!approximate[1].is_estimated);
+        assert_eq!(approximate[1].unique_count, 3);
+        assert_eq!(
+            serde_json::to_value(&approximate[1]).unwrap(),
+            serde_json::to_value(&exact[1]).unwrap()
+        );
+    }
+
+    #[test]
+    fn headers_are_told_apart_by_type_contrast() {
+        assert!(detect_header_row(
+            &["name,age,city", "Alice,30,Paris", "Bob,41,Rome"],
+            ","
+        ));
+        // A mostly-text first row can still be data
+        assert!(!detect_header_row(
+            &["Alice,30,Paris", "Bob,41,Rome", "Carol,29,Oslo"],
+            ","
+        ));
+        assert!(detect_header_row(&["when;what", "2024-01-02;x", "2024-02-03;y"], ";"));
+        assert!(detect_header_row(&["score", "1", "2"], ","));
+        assert!(!detect_header_row(&["1,2", "3,4"], ","));
+        assert!(!detect_header_row(&[] as &[&str], ","));
+    }
+
+    #[test]
+    fn all_text_files_with_and_without_headers() {
+        assert!(detect_header_row(&["city,country", "Paris,France", "Rome,Italy"], ","));
+        // Values repeating down a column mark the first row as data
+        assert!(!detect_header_row(&["Paris,France", "Lyon,France", "Rome,Italy"], ","));
+
+        let _globals = globals();
+        let file = csv_file("Alice,30,Paris\nBob,41,Rome\nCarol,29,Oslo\n");
+        let (headers, columns) = collect_columns(path(&file).to_string(), &CsvParseOptions::default()).unwrap();
+        assert_eq!(headers, vec!["column_1", "column_2", "column_3"]);
+        assert_eq!(columns[0].len(), 3);
+    }
+
+    fn reservoir(file: &tempfile::NamedTempFile, seed: Option<u64>) -> (u64, Vec<String>) {
+        let profile = profile_csv_streaming(
+            path(file).to_string(),
+            &CsvParseOptions::default(),
+            seed,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        (profile.seed, profile.columns[0].stats.reservoir_sample.clone())
+    }
+
+    #[test]
+    fn the_default_seed_makes_samples_repeatable() {
+        // Changes the default seed that every other sampling test relies on
+        let _globals = change_globals();
+        // More rows than the streaming reservoir holds, so it has to choose
+        let mut contents = String::from("id,v\n");
+        for i in 0..5000 {
+            contents.push_str(&format!("{},{}\n", i, (i * 37) % 1001));
+        }
+        let file = csv_file(&contents);
+        let default_seed = crate::utils::get_default_sample_seed();
+        let first = sampled(&file, "random", 20, None);
+        let second = sampled(&file, "random", 20, None);
+        let default_reservoir = reservoir(&file, None);
+
+        crate::utils::set_default_sample_seed(42);
+        let reseeded = sampled(&file, "random", 20, None);
+        let explicit = sampled(&file, "random", 20, Some(42));
+        let reseeded_reservoir = reservoir(&file, None);
+        let overridden_reservoir = reservoir(&file, Some(DEFAULT_SAMPLE_SEED));
+        crate::utils::set_default_sample_seed(DEFAULT_SAMPLE_SEED);
+
+        assert_eq!(default_seed, DEFAULT_SAMPLE_SEED);
+        assert_eq!(first.columns, second.columns);
+        assert_ne!(first.columns, reseeded.columns);
+        assert_eq!(reseeded.columns, explicit.columns);
+        assert_eq!(default_reservoir.0, DEFAULT_SAMPLE_SEED);
+        assert_eq!(reseeded_reservoir.0, 42);
+        assert_ne!(default_reservoir.1, reseeded_reservoir.1);
+        assert_eq!(default_reservoir, overridden_reservoir);
+    }
+
+    #[test]
+    fn null_tokens_count_towards_the_null_percentage() {
+        let _globals = globals();
+        let mut contents = String::from("id,score\n1,7\n");
+        for i in 2..=10 {
+            contents.push_str(&format!("{},N/A\n", i));
+        }
+        let file = csv_file(&contents);
+        let analyses = analyze_csv_columns(path(&file).to_string(), &CsvParseOptions::default(), 100, None).unwrap();
+
+        let score = &analyses[1];
+        assert!((score.null_percentage - 90.0).abs() < 1e-9, "{}", score.null_percentage);
+        assert_eq!(score.data_type, "number");
+        assert!(score.nullable);
+    }
+
+    #[test]
+    fn custom_null_tokens_change_completeness_and_inference() {
+        let _globals = change_globals();
+        let file = csv_file("id,score\n1,7\n2,missing\n3,missing\n4,8\n");
+        let score_analysis =
+            || analyze_csv_columns(path(&file).to_string(), &CsvParseOptions::default(), 100, None).unwrap()[1].clone();
+        let default_completeness = quality(&file, &[]).unwrap().completeness_score;
+        let default_score = score_analysis();
+
+        crate::utils::set_null_tokens(vec![" missing ".to_string()]).unwrap();
+        let custom_completeness = quality(&file, &[]).unwrap().completeness_score;
+        let custom_score = score_analysis();
+        crate::utils::set_null_tokens(
+            crate::utils::DEFAULT_NULL_TOKENS
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+        )
+        .unwrap();
+
+        assert_eq!(default_completeness, 100.0);
+        assert_eq!(default_score.data_type, "string");
+        assert_eq!(default_score.null_percentage, 0.0);
+        assert!(custom_completeness < default_completeness, "{}", custom_completeness);
+        assert_eq!(custom_score.data_type, "number");
+        assert_eq!(custom_score.null_percentage, 50.0);
+    }
+
+    // Dates and date-times as serial numbers with date styles, plus a number and a text column
+    #[cfg(feature = "xlsx")]
+    fn sales_workbook() -> tempfile::NamedTempFile {
+        let text = |cell: &str, value: &str| format!(r#"<c r="{}" t="inlineStr"><is><t>{}</t></is></c>"#, cell, value);
+        let sales = format!(
+            r#"<row r="1">{}{}{}{}</row><row r="2"><c r="A2" s="1"><v>45292</v></c><c r="B2"><v>12.5</v></c><c r="C2" s="2"><v>45292.5</v></c>{}</row><row r="3"><c r="A3" s="1"><v>45323</v></c><c r="B3"><v>7</v></c><c r="C3" s="2"><v>45323.25</v></c></row>"#,
+            text("A1", "date"),
+            text("B1", "amount"),
+            text("C1", "when"),
+            text("D1", "name"),
+            text("D2", "alpha"),
+        );
+        let other = format!(
+            r#"<row r="1">{}{}</row><row r="2"><c r="A2"><v>1</v></c><c r="B2" t="b"><v>1</v></c></row>"#,
+            text("A1", "x"),
+            text("B1", "name"),
+        );
+        xlsx_file(&[("Sales", &sales), ("Other", &other)])
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn xlsx_sheets_read_as_text() {
+        let file = sales_workbook();
+        let (headers, rows) = read_xlsx(path(&file), None).unwrap();
+        assert_eq!(headers, vec!["date", "amount", "when", "name"]);
+        assert_eq!(rows[0], vec!["2024-01-01", "12.5", "2024-01-01T12:00:00", "alpha"]);
+        assert_eq!(rows[1], vec!["2024-02-01", "7", "2024-02-01T06:00:00", ""]);
+
+        let (headers, rows) = read_xlsx(path(&file), Some("Other")).unwrap();
+        assert_eq!(headers, vec!["x", "name"]);
+        assert_eq!(rows, vec![vec!["1", "true"]]);
+
+        let missing_sheet = read_xlsx(path(&file), Some("Nope")).unwrap_err();
+        assert!(missing_sheet.to_string().contains("Nope"));
+        assert!(read_xlsx("/nonexistent/book.xlsx", None).is_err());
+    }
+
+    #[test]
+    fn quoted_newlines_are_not_counted_as_rows() {
+        let _globals = globals();
+        let file = csv_file("id,note\n1,\"line one\nline two\"\n2,plain\n\n3,\"a \"\"quoted\"\"\r\nbreak\"\r\n4,last");
+        let options = CsvParseOptions::default();
+
+        // Header plus four records; the blank line isn't one
+        assert_eq!(count_rows_streaming(path(&file)).unwrap(), 5);
+        assert_eq!(
+            validate_csv_file(path(&file).to_string(), &options)
+                .unwrap()
+                .estimated_rows,
+            4
+        );
+        assert_eq!(
+            read_csv_file(path(&file).to_string(), &options).unwrap().estimated_rows,
+            4
+        );
+        assert!(count_rows_streaming("/nonexistent/rows.csv").is_err());
+    }
+
+    #[test]
+    fn rows_are_counted_in_gzip_and_large_files() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(b"a,b\r1,\"x\ry\"\r2,z\r").unwrap();
+        let file = temp_file(".csv.gz", &gzip.finish().unwrap());
+        assert_eq!(count_rows_streaming(path(&file)).unwrap(), 3);
+
+        // Enough rows to span many read buffers, each with a quoted newline
+        let mut contents = String::from("a,b\n");
+        for i in 0..20_000 {
+            contents.push_str(&format!("{},\"x\ny\"\n", i));
+        }
+        let file = csv_file(&contents);
+        assert_eq!(count_rows_streaming(path(&file)).unwrap(), 20_001);
+    }
+
+    #[test]
+    fn row_estimates_follow_custom_quoting() {
+        let _globals = globals();
+        let file = csv_file("a,b\n1,'it\\'s\nmultiline'\n2,x\n");
+        let options = CsvParseOptions {
+            quote_char: Some(b'\''),
+            escape_char: Some(b'\\'),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_csv_file(path(&file).to_string(), &options)
+                .unwrap()
+                .estimated_rows,
+            2
+        );
+    }
+
+    #[test]
+    fn analyses_keep_quoted_delimiters_in_one_field() {
+        let _globals = globals();
+        let file = csv_file("name,city\n'Smith, J',Paris\n'Doe, A',Lyon\n'Roe, B',\n");
+        let file_path = path(&file).to_string();
+        let options = CsvParseOptions {
+            quote_char: Some(b'\''),
+            ..Default::default()
+        };
+
+        let columns = analyze_csv_columns(file_path.clone(), &options, 5, None).unwrap();
+        assert_eq!(columns.len(), 2);
+        assert!(columns[0].sample_values.contains(&"Smith, J".to_string()));
+        assert!((columns[1].null_percentage - 100.0 / 3.0).abs() < 1e-9);
+
+        let profile = profile_csv_streaming(file_path.clone(), &options, None, &AtomicBool::new(false)).unwrap();
+        assert_eq!(profile.rows_scanned, 3);
+        assert_eq!(profile.columns[1].analysis.null_percentage, columns[1].null_percentage);
+
+        let report = validate_data_quality(file_path.clone(), &options, &[], &QualityWeights::default()).unwrap();
+        assert!((report.completeness_score - 500.0 / 6.0).abs() < 1e-9);
+
+        let schema = infer_schema(file_path.clone(), &options).unwrap();
+        let report = validate_against_schema(file_path, &options, &schema, 100).unwrap();
+        assert!(report.is_valid, "{:?}", report);
+        assert_eq!(report.rows_checked, 3);
+    }
+
+    #[test]
+    fn directories_are_not_files_anywhere() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().to_str().unwrap();
+        let options = CsvParseOptions::default();
+
+        let validation = validate_csv_file(dir_path.to_string(), &options).unwrap_err();
+        assert_eq!(validation.kind(), "not_a_file");
+        assert_eq!(
+            validation.to_string(),
+            format!("{} is a directory, not a file", dir_path)
+        );
+        assert_eq!(count_rows_streaming(dir_path).unwrap_err().kind(), "not_a_file");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_files_are_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = csv_file("a,b\n1,2\n");
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o000)).unwrap();
+        // Root can open the file regardless of its mode
+        if std::fs::File::open(file.path()).is_ok() {
+            return;
+        }
+        let error = read_csv_file(path(&file).to_string(), &CsvParseOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), "permission_denied");
+        assert_eq!(
+            error.to_string(),
+            format!("Permission denied: can't read {}", path(&file))
+        );
+    }
+
+    fn described_row(description: &serde_json::Value, statistic: &str) -> serde_json::Value {
+        let index = description["index"].as_array().unwrap();
+        let position = index.iter().position(|s| s == statistic).unwrap();
+        description["data"][position].clone()
+    }
+
+    #[test]
+    fn describe_matches_hand_computed_values() {
+        let _globals = globals();
+        let file = csv_file("age,city,score\n10,Paris,1.5\n20,Rome,2.5\n30,Paris,NA\n40,Oslo,4.5\n");
+        let description = describe_csv(path(&file), &CsvParseOptions::default()).unwrap();
+        let row = |statistic| described_row(&description, statistic);
+
+        assert_eq!(description["columns"], serde_json::json!(["age", "city", "score"]));
+        assert_eq!(
+            description["index"],
+            serde_json::json!(["count", "unique", "top", "freq", "mean", "std", "min", "25%", "50%", "75%", "max"])
+        );
+        assert_eq!(row("count"), serde_json::json!([4, 4, 3]));
+        assert_eq!(row("unique"), serde_json::json!([null, 3, null]));
+        assert_eq!(row("top"), serde_json::json!([null, "Paris", null]));
+        assert_eq!(row("freq"), serde_json::json!([null, 2, null]));
+        assert_eq!(row("mean")[0], 25.0);
+        assert_eq!(row("mean")[2], 17.0 / 6.0);
+        // Sample standard deviation of 10, 20, 30, 40
+        assert!((row("std")[0].as_f64().unwrap() - (500.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        // Quartiles interpolate linearly, as pandas does
+        assert_eq!(row("25%")[0], 17.5);
+        assert_eq!(row("50%")[0], 25.0);
+        assert_eq!(row("75%")[0], 32.5);
+        assert_eq!(row("25%")[2], 2.0);
+        assert_eq!(row("50%")[2], 2.5);
+        assert_eq!(row("75%")[2], 3.5);
+        assert_eq!(row("min")[0], 10.0);
+        assert_eq!(row("max")[2], 4.5);
+    }
+
+    #[test]
+    fn describe_without_categorical_columns_has_no_frequency_rows() {
+        let _globals = globals();
+        let file = csv_file("x,y\n1,2\n3,4\n");
+        let description = describe_csv(path(&file), &CsvParseOptions::default()).unwrap();
+        assert_eq!(
+            description["index"],
+            serde_json::json!(["count", "mean", "std", "min", "25%", "50%", "75%", "max"])
+        );
+        assert_eq!(described_row(&description, "mean"), serde_json::json!([2.0, 3.0]));
+    }
 }
@@ -1,26 +1,371 @@
-use crate::parser::{read_csv_file, validate_csv_file};
+use crate::error::DataScienceError;
+#[cfg(feature = "sqlite")]
+use crate::parser::read_sqlite_table;
+#[cfg(feature = "xlsx")]
+use crate::parser::read_xlsx;
+use crate::parser::{
+    check_input_file, column_detail, csv_reader_builder, delimiter_byte, generated_column_names, preamble_line_count,
+    read_csv_file, read_fixed_width, validate_csv_file, DEFAULT_SAMPLE_SIZE,
+};
 use crate::types::*;
-use crate::utils::{apply_filters, apply_sorting, calculate_memory_usage, infer_data_types};
+use crate::utils::{
+    aggregate_rows, apply_filters, apply_sorting, calculate_memory_usage, check_cancelled, check_column_types,
+    compare_values, count_distinct_values, finish_task, infer_data_types, is_null_value, new_file_id, register_task,
+};
 use chrono::Utc;
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::AtomicBool;
 
 lazy_static::lazy_static! {
     static ref CSV_DATA_STORE: std::sync::Mutex<HashMap<String, CSVDataStore>> = std::sync::Mutex::new(HashMap::new());
 }
 
+// Synthetic column recording which file each merged row came from
+pub const SOURCE_FILE_COLUMN: &str = "__source_file";
+
+#[cfg(not(feature = "sqlite"))]
+fn read_sqlite_table(_db_path: &str, _table_name: &str) -> Result<(Vec<String>, Vec<Vec<String>>), DataScienceError> {
+    Err(
+        "SQLite support isn't available in this build (enable the `sqlite` feature)"
+            .to_string()
+            .into(),
+    )
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn read_xlsx(_file_path: &str, _sheet: Option<&str>) -> Result<(Vec<String>, Vec<Vec<String>>), DataScienceError> {
+    Err(
+        "Excel support isn't available in this build (enable the `xlsx` feature)"
+            .to_string()
+            .into(),
+    )
+}
+
+// Rows parsed between progress reports while loading or analyzing a file
+pub const PROGRESS_INTERVAL_ROWS: usize = 10_000;
+
 // Backend-centric CSV data management functions
-pub fn load_csv_into_store(file_path: &str) -> Result<CSVLoadResult, String> {
-    let file_path_string = file_path.to_string();
+pub fn load_csv_into_store(file_path: &str, options: &CSVLoadOptions) -> Result<CSVLoadResult, DataScienceError> {
+    load_csv_into_store_with_progress(file_path, options, None, |_| {})
+}
+
+/// `load_csv_into_store`, reporting progress every `PROGRESS_INTERVAL_ROWS` rows and once at the end.
+///
+/// The callback runs on the loading thread, so it should return quickly. The
+/// load can be stopped with `cancel_task` using `task_id`, or without one the
+/// `file_id` from a progress report, in which case it fails with
+/// `CANCELLED_ERROR` and stores nothing.
+pub fn load_csv_into_store_with_progress(
+    file_path: &str,
+    options: &CSVLoadOptions,
+    task_id: Option<&str>,
+    mut on_progress: impl FnMut(LoadProgress),
+) -> Result<CSVLoadResult, DataScienceError> {
+    let file_id = new_file_id();
+
+    // Cancellable through `cancel_task`; nothing is stored unless parsing finishes
+    let task_id = task_id.unwrap_or(&file_id).to_string();
+    let cancel = register_task(&task_id);
+    let parsed = parse_csv_for_store(file_id.clone(), file_path, options, &mut on_progress, &cancel);
+    finish_task(&task_id);
+    let (result, data_store) = parsed?;
+
+    // Store in global data store
+    if let Some(data_store) = data_store {
+        let mut store = CSV_DATA_STORE
+            .lock()
+            .map_err(|e| format!("Failed to lock data store: {}", e))?;
+        store.insert(data_store.file_id.clone(), data_store);
+    }
+
+    Ok(result)
+}
+
+/// Load a fixed-width file into the store, typed the same way as a CSV load.
+///
+/// See `read_fixed_width` for how lines are sliced. The widths and headers are
+/// kept in the entry's load options, so `reload_csv_in_store` works as usual.
+pub fn load_fixed_width_into_store(
+    file_path: &str,
+    field_widths: Vec<usize>,
+    headers: Option<Vec<String>>,
+) -> Result<CSVLoadResult, DataScienceError> {
+    let options = CSVLoadOptions {
+        fixed_width: Some(FixedWidthSpec { field_widths, headers }),
+        ..Default::default()
+    };
+    load_csv_into_store(file_path, &options)
+}
+
+/// Load a table from a SQLite database into the store, as written by `export_to_sqlite`.
+///
+/// The table is kept in the entry's load options, so `reload_csv_in_store` re-reads it.
+#[cfg(feature = "sqlite")]
+pub fn import_from_sqlite(db_path: &str, table_name: &str) -> Result<CSVLoadResult, DataScienceError> {
+    let options = CSVLoadOptions {
+        sqlite_table: Some(table_name.to_string()),
+        ..Default::default()
+    };
+    load_csv_into_store(db_path, &options)
+}
+
+/// Load a worksheet of an Excel workbook into the store: the named sheet, or the first one.
+///
+/// See `read_xlsx` for how cells become text; from there the rows are typed
+/// like a CSV's. The sheet is kept in the entry's load options, so
+/// `reload_csv_in_store` re-reads it.
+#[cfg(feature = "xlsx")]
+pub fn load_xlsx_into_store(file_path: &str, sheet: Option<String>) -> Result<CSVLoadResult, DataScienceError> {
+    let options = CSVLoadOptions {
+        xlsx: Some(XlsxSpec { sheet }),
+        ..Default::default()
+    };
+    load_csv_into_store(file_path, &options)
+}
+
+/// Load several CSV files into a single store entry, concatenating their rows.
+///
+/// Every file must have the same set of headers, in any order. With
+/// `union_columns` the headers are combined instead and columns a file lacks
+/// are null for its rows. Each row records its path in `SOURCE_FILE_COLUMN`.
+pub fn load_and_merge_csvs(
+    file_paths: &[String],
+    options: &CSVLoadOptions,
+    union_columns: bool,
+) -> Result<CSVLoadResult, DataScienceError> {
+    if file_paths.is_empty() {
+        return Err(DataScienceError::InvalidArgument("No files to merge".to_string()));
+    }
+
+    let file_id = new_file_id();
+
+    let mut parts = Vec::with_capacity(file_paths.len());
+    let mut parse_warnings = Vec::new();
+    for file_path in file_paths {
+        let (result, part) = parse_csv_for_store(
+            file_id.clone(),
+            file_path,
+            options,
+            &mut |_| {},
+            &AtomicBool::new(false),
+        )?;
+        let part = part.ok_or_else(|| {
+            format!(
+                "Failed to load {}: {}",
+                file_path,
+                result.error_message.unwrap_or_default()
+            )
+        })?;
+        parse_warnings.extend(result.parse_warnings.into_iter().map(|mut warning| {
+            warning.message = format!("{}: {}", file_path, warning.message);
+            warning
+        }));
+        parts.push(part);
+    }
+
+    let mut headers = parts[0].headers.clone();
+    for part in &parts[1..] {
+        let extra: Vec<String> = part.headers.iter().filter(|h| !headers.contains(h)).cloned().collect();
+        if union_columns {
+            headers.extend(extra);
+            continue;
+        }
+
+        let missing: Vec<String> = headers.iter().filter(|h| !part.headers.contains(h)).cloned().collect();
+        if !missing.is_empty() || !extra.is_empty() {
+            return Err(DataScienceError::InvalidArgument(format!(
+                "Headers of {} don't match {}: missing [{}], unexpected [{}]",
+                part.file_path,
+                parts[0].file_path,
+                missing.join(", "),
+                extra.join(", ")
+            )));
+        }
+    }
+
+    if headers.iter().any(|h| h == SOURCE_FILE_COLUMN) {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Column '{}' is reserved for merged datasets",
+            SOURCE_FILE_COLUMN
+        )));
+    }
+
+    let mut raw_data = Vec::new();
+    let mut processed_data = Vec::new();
+    let mut file_size = 0;
+    for part in &mut parts {
+        // Where each merged column sits in this file, if it has it at all
+        let positions: Vec<Option<usize>> = headers
+            .iter()
+            .map(|header| part.headers.iter().position(|h| h == header))
+            .collect();
+
+        let rows = std::mem::take(&mut part.raw_data);
+        let typed_rows = std::mem::take(&mut part.processed_data);
+        for (row, mut typed_row) in rows.into_iter().zip(typed_rows) {
+            let mut merged_row: Vec<String> = positions
+                .iter()
+                .map(|position| position.and_then(|i| row.get(i).cloned()).unwrap_or_default())
+                .collect();
+            merged_row.push(part.file_path.clone());
+
+            for (header, position) in headers.iter().zip(&positions) {
+                if position.is_none() {
+                    typed_row.insert(header.clone(), serde_json::Value::Null);
+                }
+            }
+            typed_row.insert(
+                SOURCE_FILE_COLUMN.to_string(),
+                serde_json::Value::String(part.file_path.clone()),
+            );
+
+            raw_data.push(merged_row);
+            processed_data.push(typed_row);
+        }
+        file_size += part.metadata.file_size;
+    }
+    headers.push(SOURCE_FILE_COLUMN.to_string());
+
+    let metadata = DataMetadata {
+        row_count: raw_data.len(),
+        column_count: headers.len(),
+        file_size,
+        estimated_memory_usage: calculate_memory_usage(&processed_data),
+        ..parts[0].metadata.clone()
+    };
+
+    let data_store = CSVDataStore {
+        file_id: file_id.clone(),
+        file_path: file_paths[0].clone(),
+        headers: headers.clone(),
+        raw_data,
+        processed_data,
+        metadata: metadata.clone(),
+        filters: HashMap::new(),
+        sort_config: Vec::new(),
+        load_options: options.clone(),
+        source_files: file_paths.to_vec(),
+        query_cache: None,
+        created_at: Utc::now().to_rfc3339(),
+        last_accessed: Utc::now().to_rfc3339(),
+    };
+
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    store.insert(file_id.clone(), data_store);
+
+    Ok(CSVLoadResult {
+        file_id,
+        success: true,
+        metadata,
+        headers,
+        error_message: None,
+        parse_warnings,
+    })
+}
+
+/// Re-read a loaded file from disk, keeping its `file_id`, filters, and sort config.
+///
+/// The file is re-parsed with the options it was originally loaded with.
+pub fn reload_csv_in_store(file_id: &str) -> Result<DataMetadata, DataScienceError> {
+    let (file_path, options) = {
+        let store = CSV_DATA_STORE
+            .lock()
+            .map_err(|e| format!("Failed to lock data store: {}", e))?;
+        let data_store = store
+            .get(file_id)
+            .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+        if !data_store.source_files.is_empty() {
+            return Err(DataScienceError::InvalidArgument(
+                "Merged or joined datasets can't be reloaded; rebuild them from their source files".to_string(),
+            ));
+        }
+        (data_store.file_path.clone(), data_store.load_options.clone())
+    };
+
+    check_input_file(&file_path)?;
+
+    // Parse outside the lock so other queries aren't blocked by disk I/O
+    let (result, fresh) = parse_csv_for_store(
+        file_id.to_string(),
+        &file_path,
+        &options,
+        &mut |_| {},
+        &AtomicBool::new(false),
+    )?;
+    let fresh = fresh.ok_or_else(|| {
+        result
+            .error_message
+            .unwrap_or_else(|| "Failed to reload CSV file".to_string())
+    })?;
+
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let data_store = store
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    data_store.headers = fresh.headers;
+    data_store.raw_data = fresh.raw_data;
+    data_store.processed_data = fresh.processed_data;
+    data_store.metadata = fresh.metadata;
+    data_store.query_cache = None;
+    data_store.last_accessed = Utc::now().to_rfc3339();
+
+    Ok(data_store.metadata.clone())
+}
+
+/// Read, validate, and type a CSV file into a store entry under `file_id`.
+///
+/// The entry is `None` when the load result reports a failure.
+fn parse_csv_for_store(
+    file_id: String,
+    file_path: &str,
+    options: &CSVLoadOptions,
+    on_progress: &mut dyn FnMut(LoadProgress),
+    cancel: &AtomicBool,
+) -> Result<(CSVLoadResult, Option<CSVDataStore>), DataScienceError> {
+    if let Some(column_types) = &options.column_types {
+        check_column_types(column_types)?;
+    }
+
+    // Fixed-width files, SQLite tables, and worksheets arrive already split into fields
+    if let Some(spec) = &options.fixed_width {
+        let (headers, raw_data) = read_fixed_width(file_path.to_string(), &spec.field_widths, spec.headers.clone())?;
+        check_cancelled(cancel)?;
+        return rows_for_store(
+            file_id,
+            file_path,
+            options,
+            headers,
+            raw_data,
+            spec.headers.is_none(),
+            on_progress,
+        );
+    }
+    if let Some(table_name) = &options.sqlite_table {
+        let (headers, raw_data) = read_sqlite_table(file_path, table_name)?;
+        check_cancelled(cancel)?;
+        return rows_for_store(file_id, file_path, options, headers, raw_data, true, on_progress);
+    }
+    if let Some(spec) = &options.xlsx {
+        let (headers, raw_data) = read_xlsx(file_path, spec.sheet.as_deref())?;
+        check_cancelled(cancel)?;
+        return rows_for_store(file_id, file_path, options, headers, raw_data, true, on_progress);
+    }
 
-    // Generate unique file ID
-    let file_id = format!("csv_{}", chrono::Utc::now().timestamp_millis());
+    let file_path_string = file_path.to_string();
 
     // Read and validate CSV file
-    let csv_content = read_csv_file(file_path_string.clone())?;
-    let validation = validate_csv_file(file_path_string.clone())?;
+    let csv_content = read_csv_file(file_path_string.clone(), &options.parse)?;
+    let validation = validate_csv_file(file_path_string.clone(), &options.parse)?;
 
     if !validation.is_valid {
-        return Ok(CSVLoadResult {
+        let result = CSVLoadResult {
             file_id,
             success: false,
             metadata: DataMetadata {
@@ -33,14 +378,24 @@ pub fn load_csv_into_store(file_path: &str) -> Result<CSVLoadResult, String> {
                 estimated_memory_usage: 0,
             },
             headers: Vec::new(),
-            error_message: Some("CSV file validation failed".to_string()),
-        });
+            error_message: Some(format!(
+                "CSV file validation failed: {}",
+                validation.invalid_reason.unwrap_or_default()
+            )),
+            parse_warnings: Vec::new(),
+        };
+        return Ok((result, None));
     }
 
-    // Parse CSV data
-    let mut reader = csv::ReaderBuilder::new()
-        .delimiter(validation.delimiter.as_bytes()[0])
+    // Parse CSV data; flexible so ragged rows can be reported rather than erroring out
+    let mut reader = csv_reader_builder(delimiter_byte(&validation.delimiter)?, &options.parse)
         .has_headers(validation.has_headers)
+        .flexible(true)
+        .trim(if options.trim_fields {
+            csv::Trim::Fields
+        } else {
+            csv::Trim::None
+        })
         .from_reader(csv_content.content.as_bytes());
 
     let mut raw_data = Vec::new();
@@ -51,33 +406,99 @@ pub fn load_csv_into_store(file_path: &str) -> Result<CSVLoadResult, String> {
             header_record.iter().map(|s| s.to_string()).collect()
         } else {
             // Fallback: generate column names
-            (0..validation.column_count)
-                .map(|i| format!("Column {}", i + 1))
-                .collect()
+            generated_column_names(validation.column_count)
         }
     } else {
         // No headers, generate column names
-        (0..validation.column_count)
-            .map(|i| format!("Column {}", i + 1))
-            .collect()
+        generated_column_names(validation.column_count)
+    };
+
+    // Read data rows, collecting malformed ones as warnings
+    let source_lines: Vec<&str> = csv_content.content.lines().collect();
+    let raw_line = |line: u64| {
+        source_lines
+            .get((line as usize).saturating_sub(1))
+            .map(|l| l.to_string())
+            .unwrap_or_default()
     };
+    // Reader positions are relative to the content after the preamble was skipped
+    let skipped_lines = preamble_line_count(&options.parse) as u64;
+    let mut parse_warnings = Vec::new();
+
+    let total_bytes = csv_content.content.len() as u64;
+    let mut report = |rows_processed: usize, bytes_processed: u64| {
+        on_progress(LoadProgress {
+            file_id: file_id.clone(),
+            rows_processed,
+            bytes_processed,
+            total_bytes,
+        })
+    };
+    let mut rows_processed = 0;
 
-    // Read data rows
     for result in reader.records() {
+        check_cancelled(cancel)?;
+        rows_processed += 1;
+        if rows_processed % PROGRESS_INTERVAL_ROWS == 0 {
+            let position = match &result {
+                Ok(record) => record.position(),
+                Err(e) => e.position(),
+            };
+            report(rows_processed, position.map_or(0, |p| p.byte()));
+        }
+
         match result {
-            Ok(record) => {
+            Ok(record) if record.len() == headers.len() => {
                 let row: Vec<String> = record.iter().map(|s| s.to_string()).collect();
                 raw_data.push(row);
             }
+            Ok(record) => {
+                let line = record.position().map(|p| p.line()).unwrap_or(0);
+                parse_warnings.push(ParseWarning {
+                    line: line + skipped_lines,
+                    expected_columns: headers.len(),
+                    actual_columns: record.len(),
+                    raw: raw_line(line),
+                    message: format!("Expected {} columns but found {}", headers.len(), record.len()),
+                });
+            }
             Err(e) => {
-                eprintln!("Error reading CSV record: {}", e);
-                // Continue processing other records
+                let line = e.position().map(|p| p.line()).unwrap_or(0);
+                parse_warnings.push(ParseWarning {
+                    line: line + skipped_lines,
+                    expected_columns: headers.len(),
+                    actual_columns: 0,
+                    raw: raw_line(line),
+                    message: e.to_string(),
+                });
             }
         }
     }
 
+    report(rows_processed, total_bytes);
+
+    if options.strict && !parse_warnings.is_empty() {
+        let result = CSVLoadResult {
+            file_id,
+            success: false,
+            metadata: DataMetadata {
+                row_count: 0,
+                column_count: headers.len(),
+                file_size: csv_content.file_size as u64,
+                encoding: csv_content.encoding,
+                delimiter: validation.delimiter,
+                has_headers: validation.has_headers,
+                estimated_memory_usage: 0,
+            },
+            headers,
+            error_message: Some(format!("Found {} malformed rows (strict mode)", parse_warnings.len())),
+            parse_warnings,
+        };
+        return Ok((result, None));
+    }
+
     // Process data with type inference
-    let processed_data = infer_data_types(&raw_data, &headers);
+    let processed_data = infer_data_types(&raw_data, &headers, options.column_types.as_ref());
 
     // Calculate memory usage estimate
     let estimated_memory = calculate_memory_usage(&processed_data);
@@ -101,93 +522,2172 @@ pub fn load_csv_into_store(file_path: &str) -> Result<CSVLoadResult, String> {
         processed_data,
         metadata: metadata.clone(),
         filters: HashMap::new(),
-        sort_config: None,
+        sort_config: Vec::new(),
+        load_options: options.clone(),
+        source_files: Vec::new(),
+        query_cache: None,
         created_at: Utc::now().to_rfc3339(),
         last_accessed: Utc::now().to_rfc3339(),
     };
 
-    // Store in global data store
-    {
-        let mut store = CSV_DATA_STORE
-            .lock()
-            .map_err(|e| format!("Failed to lock data store: {}", e))?;
-        store.insert(file_id.clone(), data_store);
-    }
+    let result = CSVLoadResult {
+        file_id,
+        success: true,
+        metadata,
+        headers,
+        error_message: None,
+        parse_warnings,
+    };
 
-    Ok(CSVLoadResult {
+    Ok((result, Some(data_store)))
+}
+
+/// Type rows that were split into fields without the CSV reader into a store entry under `file_id`.
+fn rows_for_store(
+    file_id: String,
+    file_path: &str,
+    options: &CSVLoadOptions,
+    headers: Vec<String>,
+    raw_data: Vec<Vec<String>>,
+    has_headers: bool,
+    on_progress: &mut dyn FnMut(LoadProgress),
+) -> Result<(CSVLoadResult, Option<CSVDataStore>), DataScienceError> {
+    let file_size = std::fs::metadata(file_path)
+        .map_err(|e| DataScienceError::io("read metadata of", file_path, e))?
+        .len();
+    on_progress(LoadProgress {
+        file_id: file_id.clone(),
+        rows_processed: raw_data.len(),
+        bytes_processed: file_size,
+        total_bytes: file_size,
+    });
+
+    let processed_data = infer_data_types(&raw_data, &headers, options.column_types.as_ref());
+
+    let metadata = DataMetadata {
+        row_count: raw_data.len(),
+        column_count: headers.len(),
+        file_size,
+        encoding: "UTF-8".to_string(),
+        delimiter: String::new(), // Not a delimited file
+        has_headers,
+        estimated_memory_usage: calculate_memory_usage(&processed_data),
+    };
+
+    let data_store = CSVDataStore {
+        file_id: file_id.clone(),
+        file_path: file_path.to_string(),
+        headers: headers.clone(),
+        raw_data,
+        processed_data,
+        metadata: metadata.clone(),
+        filters: HashMap::new(),
+        sort_config: Vec::new(),
+        load_options: options.clone(),
+        source_files: Vec::new(),
+        query_cache: None,
+        created_at: Utc::now().to_rfc3339(),
+        last_accessed: Utc::now().to_rfc3339(),
+    };
+
+    let result = CSVLoadResult {
         file_id,
         success: true,
         metadata,
         headers,
         error_message: None,
-    })
+        parse_warnings: Vec::new(),
+    };
+
+    Ok((result, Some(data_store)))
 }
 
-pub fn query_csv_data(query: &DataQuery) -> Result<DataPage, String> {
+/// Append rows to a loaded file and return its new row count.
+///
+/// Each row must have one value per header, computed columns included. Values
+/// are cleaned up like the file's own: trimmed when the load trimmed fields, and
+/// null tokens typed as null. The whole dataset is re-typed afterwards so a
+/// column's type reflects the new rows too.
+pub fn append_rows_to_store(file_id: &str, rows: Vec<Vec<String>>) -> Result<usize, DataScienceError> {
     let mut store = CSV_DATA_STORE
         .lock()
         .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let data_store = store
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    // Check every row before touching the store so a bad batch appends nothing
+    if let Some((index, row)) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, row)| row.len() != data_store.headers.len())
+    {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Row {} has {} values but the dataset has {} columns",
+            index + 1,
+            row.len(),
+            data_store.headers.len()
+        )));
+    }
+
+    // Fixed-width fields are always trimmed when they are sliced
+    let trim = data_store.load_options.trim_fields || data_store.load_options.fixed_width.is_some();
+    data_store.raw_data.extend(rows.into_iter().map(|row| {
+        if trim {
+            row.iter().map(|value| value.trim().to_string()).collect()
+        } else {
+            row
+        }
+    }));
+    data_store.processed_data = infer_data_types(
+        &data_store.raw_data,
+        &data_store.headers,
+        data_store.load_options.column_types.as_ref(),
+    );
+    data_store.metadata.row_count = data_store.raw_data.len();
+    data_store.metadata.estimated_memory_usage = calculate_memory_usage(&data_store.processed_data);
+    data_store.query_cache = None;
+    data_store.last_accessed = Utc::now().to_rfc3339();
+
+    Ok(data_store.metadata.row_count)
+}
 
+/// Strip leading and trailing whitespace from the values of a loaded file.
+///
+/// Applies to `columns`, or to every column when `None`. Column types are
+/// re-inferred afterwards, since trimming can turn `" 42"` into a number.
+/// Returns the number of values that changed.
+pub fn trim_store_columns(file_id: &str, columns: Option<Vec<String>>) -> Result<usize, DataScienceError> {
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
     let data_store = store
-        .get_mut(&query.file_id)
-        .ok_or_else(|| format!("CSV file with ID '{}' not found", query.file_id))?;
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
 
-    // Update last accessed time
-    data_store.last_accessed = chrono::Utc::now().to_rfc3339();
+    let indices: Vec<usize> = match &columns {
+        Some(columns) => columns
+            .iter()
+            .map(|column| {
+                data_store
+                    .headers
+                    .iter()
+                    .position(|h| h == column)
+                    .ok_or_else(|| DataScienceError::ColumnNotFound(column.clone()))
+            })
+            .collect::<Result<_, DataScienceError>>()?,
+        None => (0..data_store.headers.len()).collect(),
+    };
 
-    // Apply filters
-    data_store.filters = query.filters.clone();
-    let filtered_indices = apply_filters(&data_store.processed_data, &query.filters);
+    let mut changed = 0;
+    for row in &mut data_store.raw_data {
+        for &index in &indices {
+            if let Some(value) = row.get_mut(index) {
+                let trimmed = value.trim();
+                if trimmed.len() != value.len() {
+                    *value = trimmed.to_string();
+                    changed += 1;
+                }
+            }
+        }
+    }
 
-    // Apply sorting
-    let mut sorted_indices = filtered_indices;
-    if let Some(sort_spec) = &query.sort {
-        sorted_indices = apply_sorting(&sorted_indices, &data_store.processed_data, sort_spec);
+    if changed > 0 {
+        data_store.processed_data = infer_data_types(
+            &data_store.raw_data,
+            &data_store.headers,
+            data_store.load_options.column_types.as_ref(),
+        );
+        data_store.metadata.estimated_memory_usage = calculate_memory_usage(&data_store.processed_data);
+        data_store.query_cache = None;
     }
+    data_store.last_accessed = Utc::now().to_rfc3339();
 
-    // Apply pagination
-    let total_filtered = sorted_indices.len();
-    let start_idx = (query.pagination.page - 1) * query.pagination.page_size;
-    let end_idx = std::cmp::min(start_idx + query.pagination.page_size, total_filtered);
+    Ok(changed)
+}
 
-    let page_indices: Vec<usize> = sorted_indices[start_idx..end_idx].to_vec();
+/// Remove duplicate rows from a loaded file and return how many were (or, with `dry_run`, would be) removed.
+///
+/// Rows are duplicates when their trimmed values match, as in
+/// `validate_data_quality`, across `key_columns` or every column when `None`.
+/// `keep` is "first" or "last": which copy of each duplicated row survives.
+/// Surviving rows keep their order. With `dry_run` the store is left unchanged.
+pub fn deduplicate_store(
+    file_id: &str,
+    key_columns: Option<Vec<String>>,
+    keep: &str,
+    dry_run: bool,
+) -> Result<usize, DataScienceError> {
+    if !matches!(keep, "first" | "last") {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Unknown keep mode: {}",
+            keep
+        )));
+    }
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let data_store = store
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
 
-    // Extract data for current page
-    let page_data: Vec<HashMap<String, serde_json::Value>> = page_indices
-        .iter()
-        .map(|&idx| data_store.processed_data[idx].clone())
+    let indices: Vec<usize> = match &key_columns {
+        Some(columns) => columns
+            .iter()
+            .map(|column| {
+                data_store
+                    .headers
+                    .iter()
+                    .position(|h| h == column)
+                    .ok_or_else(|| DataScienceError::ColumnNotFound(column.clone()))
+            })
+            .collect::<Result<_, DataScienceError>>()?,
+        None => (0..data_store.headers.len()).collect(),
+    };
+
+    let row_key = |row: &Vec<String>| -> Vec<String> {
+        indices
+            .iter()
+            .map(|&i| row.get(i).map_or("", |v| v.trim()).to_string())
+            .collect()
+    };
+    let mut seen = HashSet::new();
+    let mut keep_row = vec![false; data_store.raw_data.len()];
+    let mut order: Vec<usize> = (0..data_store.raw_data.len()).collect();
+    if keep == "last" {
+        order.reverse();
+    }
+    for index in order {
+        keep_row[index] = seen.insert(row_key(&data_store.raw_data[index]));
+    }
+
+    let removed = keep_row.iter().filter(|&&kept| !kept).count();
+    if dry_run || removed == 0 {
+        return Ok(removed);
+    }
+
+    let mut kept = keep_row.iter();
+    data_store.raw_data.retain(|_| *kept.next().unwrap_or(&true));
+    let mut kept = keep_row.iter();
+    data_store.processed_data.retain(|_| *kept.next().unwrap_or(&true));
+    data_store.metadata.row_count = data_store.raw_data.len();
+    data_store.metadata.estimated_memory_usage = calculate_memory_usage(&data_store.processed_data);
+    data_store.query_cache = None;
+    data_store.last_accessed = Utc::now().to_rfc3339();
+
+    Ok(removed)
+}
+
+/// Rename columns of a loaded file and return its new headers.
+///
+/// `mapping` goes from current to new name. Every source must exist, and the
+/// renamed headers must stay unique, so swapping two names in one call is fine.
+/// Stored filters and sort specs follow their columns. Reloading the file from
+/// disk brings back the original names.
+pub fn rename_columns(file_id: &str, mapping: HashMap<String, String>) -> Result<Vec<String>, DataScienceError> {
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let data_store = store
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    for (from, to) in &mapping {
+        if !data_store.headers.contains(from) {
+            return Err(DataScienceError::ColumnNotFound(from.clone()));
+        }
+        if to.trim().is_empty() {
+            return Err(DataScienceError::InvalidArgument(format!(
+                "New name for column '{}' cannot be empty",
+                from
+            )));
+        }
+    }
+
+    let renamed = |name: &String| mapping.get(name).unwrap_or(name).clone();
+    let headers: Vec<String> = data_store.headers.iter().map(renamed).collect();
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = headers.iter().find(|h| !seen.insert(*h)) {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Renaming would create a duplicate column '{}'",
+            duplicate
+        )));
+    }
+
+    for row in &mut data_store.processed_data {
+        *row = row.drain().map(|(name, value)| (renamed(&name), value)).collect();
+    }
+    data_store.filters = data_store
+        .filters
+        .drain()
+        .map(|(key, mut filter)| {
+            filter.column = renamed(&filter.column);
+            (renamed(&key), filter)
+        })
         .collect();
+    for sort in &mut data_store.sort_config {
+        sort.column = renamed(&sort.column);
+    }
+    data_store.headers = headers;
+    data_store.query_cache = None;
+    data_store.last_accessed = Utc::now().to_rfc3339();
 
-    let total_pages = (total_filtered + query.pagination.page_size - 1) / query.pagination.page_size;
+    Ok(data_store.headers.clone())
+}
 
-    Ok(DataPage {
-        data: page_data,
-        total_rows: data_store.processed_data.len(),
-        filtered_rows: total_filtered,
-        current_page: query.pagination.page,
-        total_pages,
-        page_size: query.pagination.page_size,
-        has_next: query.pagination.page < total_pages,
-        has_prev: query.pagination.page > 1,
+/// A loaded file's filtered rows split into columns, and how many filters selected them.
+pub struct LoadedColumns {
+    pub headers: Vec<String>,
+    pub columns: Vec<Vec<String>>,
+    pub filter_count: usize,
+}
+
+/// Split a loaded file's rows that pass its current filters into per-column text values,
+/// as `collect_columns` would for the file on disk.
+pub fn collect_loaded_columns(file_id: &str) -> Result<LoadedColumns, DataScienceError> {
+    let store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let data_store = store
+        .get(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    let indices = apply_filters(&data_store.processed_data, &data_store.filters)?;
+    let mut columns: Vec<Vec<String>> = vec![Vec::with_capacity(indices.len()); data_store.headers.len()];
+    for &index in &indices {
+        for (column, value) in columns.iter_mut().zip(&data_store.raw_data[index]) {
+            column.push(value.clone());
+        }
+    }
+
+    Ok(LoadedColumns {
+        headers: data_store.headers.clone(),
+        columns,
+        filter_count: data_store.filters.len(),
     })
 }
 
-pub fn get_csv_headers(file_id: &str) -> Result<Vec<String>, String> {
+/// Write a loaded file to CSV in column order and return the path written.
+///
+/// Takes the same `ExportOptions` as `export_to_csv`, compression included, and
+/// streams rows straight from the store into the (compressed) file.
+pub fn export_dataset_to_csv(
+    file_id: &str,
+    file_path: &str,
+    options: &ExportOptions,
+) -> Result<String, DataScienceError> {
     let store = CSV_DATA_STORE
         .lock()
         .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let data_store = store
+        .get(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
 
+    crate::export::write_dataset_csv(&data_store.headers, &data_store.processed_data, file_path, options)
+}
+
+/// Write a loaded file to a JSON array of row objects and return the path written.
+///
+/// Only `options.compression` applies; the output is UTF-8 like `export_to_json`.
+pub fn export_dataset_to_json(
+    file_id: &str,
+    file_path: &str,
+    options: &ExportOptions,
+) -> Result<String, DataScienceError> {
+    let store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
     let data_store = store
         .get(file_id)
-        .ok_or_else(|| format!("CSV file with ID '{}' not found", file_id))?;
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
 
-    Ok(data_store.headers.clone())
+    crate::export::write_dataset_json(&data_store.headers, &data_store.processed_data, file_path, options)
 }
 
-pub fn unload_csv_data(file_id: &str) -> Result<(), String> {
-    let mut store = CSV_DATA_STORE
+/// Write a loaded file to Parquet, with each column typed the way the load typed it.
+///
+/// See `export_to_parquet` for how types map to Parquet columns.
+#[cfg(feature = "parquet")]
+pub fn export_dataset_to_parquet(file_id: &str, file_path: &str) -> Result<(), DataScienceError> {
+    let store = CSV_DATA_STORE
         .lock()
         .map_err(|e| format!("Failed to lock data store: {}", e))?;
-    store.remove(file_id);
-    Ok(())
+    let data_store = store
+        .get(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    let column_types = crate::utils::infer_column_types(&data_store.raw_data, &data_store.headers);
+    crate::export::write_parquet(
+        &data_store.headers,
+        &column_types,
+        data_store.processed_data.len(),
+        |row, header| data_store.processed_data[row].get(header),
+        file_path,
+    )
+}
+
+/// Write a loaded file to a SQLite table and return the number of rows written.
+///
+/// The table and column names are sanitized with `sanitize_sql_identifier`,
+/// and an existing table of the same name is replaced. Number columns become
+/// INTEGER (when every value is whole) or REAL, booleans INTEGER 0/1, and
+/// everything else TEXT. Rows are inserted in a single transaction.
+#[cfg(feature = "sqlite")]
+pub fn export_to_sqlite(file_id: &str, db_path: &str, table_name: &str) -> Result<usize, DataScienceError> {
+    let store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let data_store = store
+        .get(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    let column_types = crate::utils::infer_column_types(&data_store.raw_data, &data_store.headers);
+    crate::export::write_sqlite(
+        &data_store.headers,
+        &column_types,
+        &data_store.processed_data,
+        db_path,
+        table_name,
+    )
+}
+
+/// Append a column computed from an expression over each row, e.g. `price * quantity`.
+///
+/// Columns are referenced by name and must be valid identifiers. Numbers are
+/// evaluated as floats. A row gets null when an operand is missing, null, or
+/// non-numeric, or when the result isn't a finite number (such as dividing by zero).
+pub fn add_computed_column(
+    file_id: &str,
+    new_column: &str,
+    expression: &str,
+) -> Result<DataMetadata, DataScienceError> {
+    let tree = evalexpr::build_operator_tree(expression)
+        .map_err(|e| DataScienceError::Parse(format!("Invalid expression: {}", e)))?;
+
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let data_store = store
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    if new_column.trim().is_empty() {
+        return Err(DataScienceError::InvalidArgument(
+            "Column name cannot be empty".to_string(),
+        ));
+    }
+    if data_store.headers.iter().any(|h| h == new_column) {
+        return Err(DataScienceError::InvalidArgument(format!(
+            "Column '{}' already exists",
+            new_column
+        )));
+    }
+
+    let mut operands: Vec<String> = tree.iter_variable_identifiers().map(str::to_string).collect();
+    operands.sort();
+    operands.dedup();
+    if let Some(unknown) = operands.iter().find(|name| !data_store.headers.contains(name)) {
+        return Err(DataScienceError::InvalidQuery(format!(
+            "Unknown column '{}' in expression",
+            unknown
+        )));
+    }
+
+    for (row, raw_row) in data_store.processed_data.iter_mut().zip(data_store.raw_data.iter_mut()) {
+        let value = evaluate_row(&tree, &operands, row);
+        raw_row.push(match &value {
+            serde_json::Value::Null => String::new(),
+            other => other.to_string(),
+        });
+        row.insert(new_column.to_string(), value);
+    }
+
+    data_store.headers.push(new_column.to_string());
+    data_store.metadata.column_count = data_store.headers.len();
+    data_store.metadata.estimated_memory_usage = calculate_memory_usage(&data_store.processed_data);
+    data_store.query_cache = None;
+    data_store.last_accessed = Utc::now().to_rfc3339();
+
+    Ok(data_store.metadata.clone())
+}
+
+// Evaluate a computed-column expression against one row; null when it can't produce a finite number
+fn evaluate_row(
+    tree: &evalexpr::Node,
+    operands: &[String],
+    row: &HashMap<String, serde_json::Value>,
+) -> serde_json::Value {
+    use evalexpr::ContextWithMutableVariables;
+
+    let mut context = evalexpr::HashMapContext::new();
+    for name in operands {
+        let Some(number) = row.get(name).and_then(serde_json::Value::as_f64) else {
+            return serde_json::Value::Null;
+        };
+        if context.set_value(name.clone(), evalexpr::Value::Float(number)).is_err() {
+            return serde_json::Value::Null;
+        }
+    }
+
+    match tree.eval_with_context(&context) {
+        Ok(evalexpr::Value::Float(result)) => serde_json::Number::from_f64(result)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Ok(evalexpr::Value::Int(result)) => serde_json::Value::from(result),
+        Ok(evalexpr::Value::Boolean(result)) => serde_json::Value::Bool(result),
+        Ok(evalexpr::Value::String(result)) => serde_json::Value::String(result),
+        _ => serde_json::Value::Null,
+    }
+}
+
+/// Join two loaded datasets on a key column into a new store entry.
+///
+/// `join_type` is "inner" or "left". Each left row is emitted once per
+/// matching right row, so one-to-many keys multiply rows. Under a left join,
+/// left rows without a match are kept once with every right column null.
+/// Keys match on their typed value; null keys never match. Right columns
+/// whose names collide with a left column are prefixed with `right_`.
+pub fn join_datasets(
+    left_file_id: &str,
+    right_file_id: &str,
+    left_key: &str,
+    right_key: &str,
+    join_type: &str,
+) -> Result<CSVLoadResult, DataScienceError> {
+    let keep_unmatched = match join_type {
+        "inner" => false,
+        "left" => true,
+        _ => {
+            return Err(DataScienceError::InvalidArgument(format!(
+                "Unsupported join type: {}",
+                join_type
+            )))
+        }
+    };
+
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let left = store
+        .get(left_file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(left_file_id.to_string()))?;
+    let right = store
+        .get(right_file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(right_file_id.to_string()))?;
+
+    if !left.headers.iter().any(|h| h == left_key) {
+        return Err(DataScienceError::ColumnNotFound(left_key.to_string()));
+    }
+    if !right.headers.iter().any(|h| h == right_key) {
+        return Err(DataScienceError::ColumnNotFound(right_key.to_string()));
+    }
+
+    // Output names for the right-hand columns, prefixed until they no longer collide
+    let mut headers = left.headers.clone();
+    let mut right_names = Vec::with_capacity(right.headers.len());
+    for header in &right.headers {
+        let mut name = header.clone();
+        while headers.contains(&name) {
+            name = format!("right_{}", name);
+        }
+        headers.push(name.clone());
+        right_names.push(name);
+    }
+
+    let mut right_index: HashMap<String, Vec<usize>> = HashMap::new();
+    for (idx, row) in right.processed_data.iter().enumerate() {
+        if let Some(key) = join_key(row, right_key) {
+            right_index.entry(key).or_default().push(idx);
+        }
+    }
+
+    let mut raw_data = Vec::new();
+    let mut processed_data = Vec::new();
+    for (left_idx, left_row) in left.processed_data.iter().enumerate() {
+        let matches = join_key(left_row, left_key)
+            .and_then(|key| right_index.get(&key))
+            .map_or(&[][..], Vec::as_slice);
+
+        for &right_idx in matches {
+            let mut raw_row = left.raw_data[left_idx].clone();
+            raw_row.extend(right.raw_data[right_idx].iter().cloned());
+            raw_data.push(raw_row);
+
+            let mut row = left_row.clone();
+            for (header, name) in right.headers.iter().zip(&right_names) {
+                let value = right.processed_data[right_idx].get(header).cloned();
+                row.insert(name.clone(), value.unwrap_or(serde_json::Value::Null));
+            }
+            processed_data.push(row);
+        }
+
+        if matches.is_empty() && keep_unmatched {
+            let mut raw_row = left.raw_data[left_idx].clone();
+            raw_row.resize(headers.len(), String::new());
+            raw_data.push(raw_row);
+
+            let mut row = left_row.clone();
+            for name in &right_names {
+                row.insert(name.clone(), serde_json::Value::Null);
+            }
+            processed_data.push(row);
+        }
+    }
+
+    let file_id = new_file_id();
+    let metadata = DataMetadata {
+        row_count: raw_data.len(),
+        column_count: headers.len(),
+        file_size: 0,
+        estimated_memory_usage: calculate_memory_usage(&processed_data),
+        ..left.metadata.clone()
+    };
+
+    let data_store = CSVDataStore {
+        file_id: file_id.clone(),
+        file_path: left.file_path.clone(),
+        headers: headers.clone(),
+        raw_data,
+        processed_data,
+        metadata: metadata.clone(),
+        filters: HashMap::new(),
+        sort_config: Vec::new(),
+        load_options: left.load_options.clone(),
+        source_files: vec![left.file_path.clone(), right.file_path.clone()],
+        query_cache: None,
+        created_at: Utc::now().to_rfc3339(),
+        last_accessed: Utc::now().to_rfc3339(),
+    };
+    store.insert(file_id.clone(), data_store);
+
+    Ok(CSVLoadResult {
+        file_id,
+        success: true,
+        metadata,
+        headers,
+        error_message: None,
+        parse_warnings: Vec::new(),
+    })
+}
+
+// Hashable form of a row's join key; None for missing or null keys
+fn join_key(row: &HashMap<String, serde_json::Value>, column: &str) -> Option<String> {
+    row.get(column)
+        .filter(|value| !is_null_value(value))
+        .map(|value| value.to_string())
+}
+
+pub fn query_csv_data(query: &DataQuery) -> Result<DataPage, DataScienceError> {
+    if query.pagination.page == 0 {
+        return Err(DataScienceError::InvalidQuery("Page numbers start at 1".to_string()));
+    }
+    if query.pagination.page_size == 0 {
+        return Err(DataScienceError::InvalidQuery(
+            "Page size must be greater than zero".to_string(),
+        ));
+    }
+
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+
+    let data_store = store
+        .get_mut(&query.file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(query.file_id.to_string()))?;
+
+    // Validate the projection before doing any work
+    let columns = match &query.columns {
+        Some(columns) => {
+            if let Some(unknown) = columns.iter().find(|c| !data_store.headers.contains(c)) {
+                return Err(DataScienceError::ColumnNotFound(unknown.clone()));
+            }
+            columns.clone()
+        }
+        None => data_store.headers.clone(),
+    };
+
+    // Update last accessed time
+    data_store.last_accessed = chrono::Utc::now().to_rfc3339();
+
+    data_store.filters = query.filters.clone();
+    data_store.sort_config = query.sort.clone();
+
+    // Filter and sort only when the view changed; paging through the same view reuses the cached order
+    let signature = query_signature(&query.filters, &query.sort);
+    if data_store
+        .query_cache
+        .as_ref()
+        .is_none_or(|(cached, _)| *cached != signature)
+    {
+        let mut indices = apply_filters(&data_store.processed_data, &query.filters)?;
+        if !query.sort.is_empty() {
+            indices = apply_sorting(&indices, &data_store.processed_data, &query.sort);
+        }
+        data_store.query_cache = Some((signature, indices));
+    }
+    let sorted_indices: &[usize] = data_store.query_cache.as_ref().map_or(&[], |(_, indices)| indices);
+
+    // Apply pagination
+    let total_filtered = sorted_indices.len();
+    // A page past the end is empty rather than out of bounds
+    let start_idx = ((query.pagination.page - 1).saturating_mul(query.pagination.page_size)).min(total_filtered);
+    let end_idx = start_idx.saturating_add(query.pagination.page_size).min(total_filtered);
+
+    let page_indices: Vec<usize> = sorted_indices[start_idx..end_idx].to_vec();
+
+    // Extract data for current page
+    let page_data: Vec<HashMap<String, serde_json::Value>> = page_indices
+        .iter()
+        .map(|&idx| {
+            let row = &data_store.processed_data[idx];
+            if query.columns.is_some() {
+                columns
+                    .iter()
+                    .filter_map(|c| row.get(c).map(|v| (c.clone(), v.clone())))
+                    .collect()
+            } else {
+                row.clone()
+            }
+        })
+        .collect();
+
+    let total_pages = total_filtered.div_ceil(query.pagination.page_size);
+
+    Ok(DataPage {
+        data: page_data,
+        columns,
+        total_rows: data_store.processed_data.len(),
+        filtered_rows: total_filtered,
+        current_page: query.pagination.page,
+        total_pages,
+        page_size: query.pagination.page_size,
+        has_next: query.pagination.page < total_pages,
+        has_prev: query.pagination.page > 1,
+    })
+}
+
+/// Number of rows matching `filters`, the `filtered_rows` a query with them would report.
+///
+/// Skips sorting and pagination, and leaves the file's stored filters and query cache alone.
+pub fn count_csv_data(file_id: &str, filters: &HashMap<String, FilterSpec>) -> Result<usize, DataScienceError> {
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+
+    let data_store = store
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    data_store.last_accessed = chrono::Utc::now().to_rfc3339();
+
+    Ok(apply_filters(&data_store.processed_data, filters)?.len())
+}
+
+/// Hash of a query's filters and sort specs, independent of filter map iteration order.
+fn query_signature(filters: &HashMap<String, FilterSpec>, sort: &[SortSpec]) -> u64 {
+    let ordered_filters: BTreeMap<&String, &FilterSpec> = filters.iter().collect();
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(&(ordered_filters, sort))
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn aggregate_csv_data(
+    file_id: &str,
+    spec: &GroupByAgg,
+) -> Result<Vec<HashMap<String, serde_json::Value>>, DataScienceError> {
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+
+    let data_store = store
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    data_store.last_accessed = chrono::Utc::now().to_rfc3339();
+
+    aggregate_rows(&data_store.processed_data, &data_store.headers, spec)
+}
+
+/// Distinct values of `column`, sorted ascending and capped at `limit`.
+///
+/// With `use_filters` only rows matching the filters stored by the last query are scanned.
+pub fn get_column_distinct_values(
+    file_id: &str,
+    column: &str,
+    limit: Option<usize>,
+    use_filters: bool,
+) -> Result<Vec<serde_json::Value>, DataScienceError> {
+    let mut values: Vec<serde_json::Value> = column_value_counts(file_id, column, use_filters)?
+        .into_iter()
+        .map(|(value, _)| value)
+        .collect();
+    values.sort_by(|a, b| compare_values(a, b, "asc", true));
+    values.truncate(limit.unwrap_or(usize::MAX));
+    Ok(values)
+}
+
+/// Occurrence count per distinct value of `column`, most frequent first and capped at `limit`.
+pub fn get_column_value_counts(
+    file_id: &str,
+    column: &str,
+    limit: Option<usize>,
+    use_filters: bool,
+) -> Result<Vec<ValueCount>, DataScienceError> {
+    let mut counts = column_value_counts(file_id, column, use_filters)?;
+    counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| compare_values(a, b, "asc", true)));
+    Ok(counts
+        .into_iter()
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|(value, count)| ValueCount { value, count })
+        .collect())
+}
+
+/// Analyze one column of a loaded file, over the rows that pass its current filters.
+pub fn analyze_single_column(file_id: &str, column: &str) -> Result<ColumnDetail, DataScienceError> {
+    let values: Vec<String> = {
+        let store = CSV_DATA_STORE
+            .lock()
+            .map_err(|e| format!("Failed to lock data store: {}", e))?;
+        let data_store = store
+            .get(file_id)
+            .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+        let index = data_store
+            .headers
+            .iter()
+            .position(|h| h == column)
+            .ok_or_else(|| DataScienceError::ColumnNotFound(column.to_string()))?;
+
+        apply_filters(&data_store.processed_data, &data_store.filters)?
+            .into_iter()
+            .map(|i| data_store.raw_data[i].get(index).cloned().unwrap_or_default())
+            .collect()
+    };
+    Ok(column_detail(column, &values, DEFAULT_SAMPLE_SIZE))
+}
+
+fn column_value_counts(
+    file_id: &str,
+    column: &str,
+    use_filters: bool,
+) -> Result<Vec<(serde_json::Value, usize)>, DataScienceError> {
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+
+    let data_store = store
+        .get_mut(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    if !data_store.headers.iter().any(|h| h == column) {
+        return Err(DataScienceError::ColumnNotFound(column.to_string()));
+    }
+
+    data_store.last_accessed = chrono::Utc::now().to_rfc3339();
+
+    let indices = if use_filters {
+        apply_filters(&data_store.processed_data, &data_store.filters)?
+    } else {
+        (0..data_store.processed_data.len()).collect()
+    };
+
+    Ok(count_distinct_values(&data_store.processed_data, &indices, column))
+}
+
+pub fn get_csv_headers(file_id: &str) -> Result<Vec<String>, DataScienceError> {
+    let store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+
+    let data_store = store
+        .get(file_id)
+        .ok_or_else(|| DataScienceError::DatasetNotFound(file_id.to_string()))?;
+
+    Ok(data_store.headers.clone())
+}
+
+pub fn unload_csv_data(file_id: &str) -> Result<(), DataScienceError> {
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    store.remove(file_id);
+    Ok(())
+}
+
+/// Every file in the store, oldest load first.
+pub fn list_loaded_csvs() -> Result<Vec<DatasetSummary>, DataScienceError> {
+    let store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let mut summaries: Vec<DatasetSummary> = store
+        .values()
+        .map(|data_store| DatasetSummary {
+            file_id: data_store.file_id.clone(),
+            file_path: data_store.file_path.clone(),
+            row_count: data_store.processed_data.len(),
+            column_count: data_store.headers.len(),
+            estimated_memory_usage: data_store.metadata.estimated_memory_usage,
+            created_at: data_store.created_at.clone(),
+            last_accessed: data_store.last_accessed.clone(),
+        })
+        .collect();
+    summaries.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.file_id.cmp(&b.file_id)));
+    Ok(summaries)
+}
+
+/// Remove every file from the store, returning how many were unloaded.
+pub fn unload_all_csv_data() -> Result<usize, DataScienceError> {
+    let mut store = CSV_DATA_STORE
+        .lock()
+        .map_err(|e| format!("Failed to lock data store: {}", e))?;
+    let count = store.len();
+    store.clear();
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "xlsx")]
+    use crate::test_support::xlsx_file;
+    use crate::test_support::{change_globals, csv_file, globals, path, temp_file};
+    use crate::utils::{cancel_task, CANCELLED_ERROR};
+
+    fn numbered_rows(count: usize) -> String {
+        let mut text = String::from("a,b\n");
+        for i in 0..count {
+            text.push_str(&format!("{},{}\n", i, i * 2));
+        }
+        text
+    }
+
+    #[test]
+    fn load_progress_is_reported_every_interval_and_at_the_end() {
+        let _globals = globals();
+        let file = csv_file(&numbered_rows(25_000));
+        let mut reports = Vec::new();
+        let result = load_csv_into_store_with_progress(path(&file), &CSVLoadOptions::default(), None, |progress| {
+            reports.push(progress)
+        })
+        .unwrap();
+        unload_csv_data(&result.file_id).unwrap();
+
+        let rows: Vec<usize> = reports.iter().map(|p| p.rows_processed).collect();
+        assert_eq!(rows, [10_000, 20_000, 25_000]);
+        assert!(reports.iter().all(|p| p.file_id == result.file_id));
+        let last = reports.last().unwrap();
+        assert_eq!(last.bytes_processed, last.total_bytes);
+        assert!(reports[0].bytes_processed < reports[1].bytes_processed);
+    }
+
+    #[test]
+    fn cancelling_by_file_id_stores_nothing() {
+        let _globals = globals();
+        let file = csv_file(&numbered_rows(30_000));
+        let mut file_id = None;
+        let error = load_csv_into_store_with_progress(path(&file), &CSVLoadOptions::default(), None, |progress| {
+            assert!(cancel_task(&progress.file_id));
+            file_id = Some(progress.file_id);
+        })
+        .unwrap_err();
+
+        assert!(matches!(error, DataScienceError::Cancelled));
+        assert_eq!(error.to_string(), CANCELLED_ERROR);
+        let file_id = file_id.unwrap();
+        assert!(get_csv_headers(&file_id).is_err());
+        assert!(!cancel_task(&file_id));
+    }
+
+    #[test]
+    fn cancelling_by_caller_task_id() {
+        let _globals = globals();
+        let file = csv_file(&numbered_rows(30_000));
+        let error = load_csv_into_store_with_progress(
+            path(&file),
+            &CSVLoadOptions::default(),
+            Some("load-by-task-id"),
+            |progress| {
+                // The load is registered under the caller's id, not its file id
+                assert!(!cancel_task(&progress.file_id));
+                assert!(cancel_task("load-by-task-id"));
+            },
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, DataScienceError::Cancelled));
+        assert!(!cancel_task("load-by-task-id"));
+    }
+
+    // A copy of a stored entry, so assertions don't hold (and poison) the store lock
+    fn stored(file_id: &str) -> CSVDataStore {
+        CSV_DATA_STORE.lock().unwrap()[file_id].clone()
+    }
+
+    #[test]
+    fn appended_rows_are_trimmed_like_the_load() {
+        let _globals = globals();
+        let file = csv_file("name,score\n alice , 1\n");
+        let options = CSVLoadOptions {
+            trim_fields: true,
+            ..Default::default()
+        };
+        let file_id = load_csv_into_store(path(&file), &options).unwrap().file_id;
+
+        let rows = vec![
+            vec!["  bob".to_string(), " 2 ".to_string()],
+            vec!["carol ".to_string(), "NA".to_string()],
+        ];
+        assert_eq!(append_rows_to_store(&file_id, rows).unwrap(), 3);
+
+        let data_store = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+        assert_eq!(data_store.raw_data[1], vec!["bob", "2"]);
+        assert_eq!(data_store.raw_data[2], vec!["carol", "NA"]);
+        assert_eq!(data_store.processed_data[1]["score"], serde_json::json!(2));
+        assert_eq!(data_store.processed_data[2]["score"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn appended_rows_keep_whitespace_without_trim_fields() {
+        let _globals = globals();
+        let file = csv_file("name,score\nalice,1\n");
+        let file_id = load_csv_into_store(path(&file), &CSVLoadOptions::default())
+            .unwrap()
+            .file_id;
+        append_rows_to_store(&file_id, vec![vec![" bob ".to_string(), "2".to_string()]]).unwrap();
+
+        let data_store = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+        assert_eq!(data_store.raw_data[1], vec![" bob ", "2"]);
+    }
+
+    #[test]
+    fn loads_get_distinct_file_ids() {
+        let _globals = globals();
+        let file = csv_file("a\n1\n");
+        let ids: HashSet<String> = (0..20)
+            .map(|_| {
+                load_csv_into_store(path(&file), &CSVLoadOptions::default())
+                    .unwrap()
+                    .file_id
+            })
+            .collect();
+        assert_eq!(ids.len(), 20);
+        for id in &ids {
+            unload_csv_data(id).unwrap();
+        }
+    }
+
+    #[test]
+    fn loads_skip_preamble_rows() {
+        let _globals = globals();
+        let file =
+            csv_file("Report generated 2024-01-01\nSource: sensor export\n\nid,name,score\n1,a,3\n2,b,4\n3,c,5\n");
+        let options = CSVLoadOptions {
+            parse: CsvParseOptions {
+                skip_rows: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = load_csv_into_store(path(&file), &options).unwrap();
+        unload_csv_data(&result.file_id).unwrap();
+        assert_eq!(result.headers, vec!["id", "name", "score"]);
+        assert_eq!(result.metadata.row_count, 3);
+    }
+
+    #[test]
+    fn loads_carriage_return_only_files() {
+        let _globals = globals();
+        let file = csv_file("id,name\r1,a\r2,b\r1,a\r");
+        let result = load_csv_into_store(path(&file), &CSVLoadOptions::default()).unwrap();
+        unload_csv_data(&result.file_id).unwrap();
+        assert_eq!(result.metadata.row_count, 3);
+    }
+
+    fn page_query(file_id: &str, page: usize, sort: serde_json::Value) -> DataQuery {
+        serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {"name": {"column": "name", "operator": "starts_with", "value": "n1", "case_sensitive": true}},
+            "sort": sort,
+            "pagination": {"page": page, "page_size": 2},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn paging_reuses_the_cached_view() {
+        let _globals = globals();
+        let file = csv_file("id,name\n1,n10\n2,n2\n3,n11\n4,n12\n5,n13\n6,n14\n");
+        let file_id = load_csv_into_store(path(&file), &CSVLoadOptions::default())
+            .unwrap()
+            .file_id;
+        let by_name = serde_json::json!([{"column": "name", "direction": "desc", "case_sensitive": false}]);
+        let names = |result: &DataPage| -> Vec<serde_json::Value> {
+            result.data.iter().map(|row| row["name"].clone()).collect()
+        };
+
+        let first = query_csv_data(&page_query(&file_id, 1, by_name.clone())).unwrap();
+        assert_eq!(names(&first), [serde_json::json!("n14"), serde_json::json!("n13")]);
+        assert_eq!(first.filtered_rows, 5);
+
+        // Swap in a different cached order: later pages of the same view must use it rather than re-sort
+        let cached = stored(&file_id).query_cache.unwrap();
+        assert_eq!(cached.1, vec![5, 4, 3, 2, 0]);
+        CSV_DATA_STORE.lock().unwrap().get_mut(&file_id).unwrap().query_cache = Some((cached.0, vec![0, 2, 3, 4, 5]));
+        let second = query_csv_data(&page_query(&file_id, 2, by_name.clone())).unwrap();
+        let third = query_csv_data(&page_query(&file_id, 3, by_name)).unwrap();
+
+        // A different sort is a different view and is computed afresh
+        let unsorted = query_csv_data(&page_query(&file_id, 1, serde_json::json!([]))).unwrap();
+
+        reload_csv_in_store(&file_id).unwrap();
+        let reloaded_cache = stored(&file_id).query_cache;
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(names(&second), [serde_json::json!("n12"), serde_json::json!("n13")]);
+        assert_eq!(names(&third), [serde_json::json!("n14")]);
+        assert_eq!(names(&unsorted), [serde_json::json!("n10"), serde_json::json!("n11")]);
+        assert!(reloaded_cache.is_none());
+    }
+
+    #[test]
+    fn reloading_keeps_the_id_and_view_settings() {
+        let _globals = globals();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("names.csv");
+        std::fs::write(&file, "id,name\n1,n10\n2,n2\n3,n11\n").unwrap();
+        let file_id = load_csv_into_store(file.to_str().unwrap(), &CSVLoadOptions::default())
+            .unwrap()
+            .file_id;
+        let by_name = serde_json::json!([{"column": "name", "direction": "desc", "case_sensitive": false}]);
+        query_csv_data(&page_query(&file_id, 1, by_name)).unwrap();
+        let before = stored(&file_id);
+
+        std::fs::write(&file, "id,name\n1,n10\n2,n2\n3,n11\n4,n12\n5,x\n").unwrap();
+        let metadata = reload_csv_in_store(&file_id).unwrap();
+        let after = stored(&file_id);
+        let page = query_csv_data(&page_query(
+            &file_id,
+            1,
+            serde_json::to_value(&after.sort_config).unwrap(),
+        ))
+        .unwrap();
+
+        std::fs::remove_file(&file).unwrap();
+        let missing = reload_csv_in_store(&file_id).unwrap_err();
+        let kept = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(metadata.row_count, 5);
+        assert_eq!(after.processed_data.len(), 5);
+        assert_eq!(
+            serde_json::to_value(&after.filters).unwrap(),
+            serde_json::to_value(&before.filters).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&after.sort_config).unwrap(),
+            serde_json::to_value(&before.sort_config).unwrap()
+        );
+        assert_eq!(after.filters["name"].value, "n1");
+        // The kept filters apply to the new rows
+        assert_eq!(page.filtered_rows, 3);
+        assert_eq!(page.data[0]["name"], "n12");
+
+        assert_eq!(missing.kind(), "file_not_found");
+        // A failed reload leaves the loaded data alone
+        assert_eq!(kept.processed_data.len(), 5);
+    }
+
+    #[test]
+    fn header_override_names_or_keeps_columns() {
+        let _globals = globals();
+        let load = |contents: &str, has_headers: bool| {
+            let file = csv_file(contents);
+            let options = CSVLoadOptions {
+                parse: CsvParseOptions {
+                    has_headers: Some(has_headers),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let result = load_csv_into_store(path(&file), &options).unwrap();
+            unload_csv_data(&result.file_id).unwrap();
+            result
+        };
+
+        let headerless = load("1,2,3\n4,5,6\n7,8,9\n", false);
+        assert_eq!(headerless.headers, vec!["column_1", "column_2", "column_3"]);
+        assert_eq!(headerless.metadata.row_count, 3);
+
+        let numeric_header = load("2020,2021\n1,2\n", true);
+        assert_eq!(numeric_header.headers, vec!["2020", "2021"]);
+        assert_eq!(numeric_header.metadata.row_count, 1);
+    }
+
+    #[test]
+    fn merging_files_with_the_same_columns() {
+        let _globals = globals();
+        let first = csv_file("id,name\n1,ann\n2,bob\n");
+        let second = csv_file("name,id\ncat,3\n");
+        let files = [path(&first).to_string(), path(&second).to_string()];
+        let result = load_and_merge_csvs(&files, &CSVLoadOptions::default(), false).unwrap();
+        let data_store = stored(&result.file_id);
+        let reload = reload_csv_in_store(&result.file_id);
+        unload_csv_data(&result.file_id).unwrap();
+
+        assert_eq!(result.headers, vec!["id", "name", SOURCE_FILE_COLUMN]);
+        assert_eq!(result.metadata.row_count, 3);
+        let row = &data_store.processed_data[2];
+        assert_eq!(row["name"], "cat");
+        assert_eq!(row["id"], 3);
+        assert_eq!(row[SOURCE_FILE_COLUMN], files[1].as_str());
+        assert!(reload.is_err());
+    }
+
+    #[test]
+    fn merging_mismatched_columns() {
+        let _globals = globals();
+        let first = csv_file("id,name\n1,ann\n2,bob\n");
+        let second = csv_file("id,city\n4,oslo\n");
+        let files = [path(&first).to_string(), path(&second).to_string()];
+
+        let error = load_and_merge_csvs(&files, &CSVLoadOptions::default(), false)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("missing [name]") && error.contains("unexpected [city]"),
+            "{}",
+            error
+        );
+
+        let result = load_and_merge_csvs(&files, &CSVLoadOptions::default(), true).unwrap();
+        let data_store = stored(&result.file_id);
+        unload_csv_data(&result.file_id).unwrap();
+        assert_eq!(result.headers, vec!["id", "name", "city", SOURCE_FILE_COLUMN]);
+        assert!(data_store.processed_data[0]["city"].is_null());
+        assert!(data_store.processed_data[2]["name"].is_null());
+        assert_eq!(data_store.processed_data[2]["city"], "oslo");
+    }
+
+    fn loaded(contents: &str) -> String {
+        let file = csv_file(contents);
+        load_csv_into_store(path(&file), &CSVLoadOptions::default())
+            .unwrap()
+            .file_id
+    }
+
+    #[test]
+    fn inner_and_left_joins() {
+        let _globals = globals();
+        let transactions = loaded("tid,cust,amount\n1,10,5\n2,20,6\n3,10,7\n4,99,8\n5,,9\n");
+        let customers = loaded("cust,name,amount\n10,ann,x\n20,bob,y\n20,bob2,z\n");
+        let inner = join_datasets(&transactions, &customers, "cust", "cust", "inner").unwrap();
+        let left = join_datasets(&transactions, &customers, "cust", "cust", "left").unwrap();
+        let unknown_key = join_datasets(&transactions, &customers, "cust", "nope", "left");
+        let unknown_type = join_datasets(&transactions, &customers, "cust", "cust", "outer");
+        let inner_rows = stored(&inner.file_id).processed_data;
+        let left_rows = stored(&left.file_id).processed_data;
+        for file_id in [&transactions, &customers, &inner.file_id, &left.file_id] {
+            unload_csv_data(file_id).unwrap();
+        }
+
+        // Colliding right-side columns are prefixed; one-to-many matches give a row each
+        assert_eq!(
+            inner.headers,
+            vec!["tid", "cust", "amount", "right_cust", "name", "right_amount"]
+        );
+        assert_eq!(inner.metadata.row_count, 4);
+        assert_eq!(inner_rows[1]["name"], "bob");
+        assert_eq!(inner_rows[2]["name"], "bob2");
+        assert_eq!(inner_rows[3]["tid"], 3);
+
+        // Unmatched left rows are kept with nulls for the right-side columns
+        assert_eq!(left.metadata.row_count, 6);
+        assert_eq!(left_rows[4]["tid"], 4);
+        assert!(left_rows[4]["name"].is_null());
+        assert!(left_rows[5]["right_cust"].is_null());
+
+        assert!(unknown_key.is_err());
+        assert_eq!(unknown_type.unwrap_err().kind(), "invalid_argument");
+    }
+
+    #[test]
+    fn computed_columns() {
+        let _globals = globals();
+        let file_id = loaded("price,quantity,label\n2.5,4,a\n3,0,b\nx,2,c\n1,,d\n4,1,e\n5,1,f\n");
+        let metadata = add_computed_column(&file_id, "total", "price * quantity").unwrap();
+        add_computed_column(&file_id, "per", "price / quantity").unwrap();
+        let duplicate = add_computed_column(&file_id, "total", "1");
+        let unknown_column = add_computed_column(&file_id, "z", "nope + 1");
+        let malformed = add_computed_column(&file_id, "z", "(price");
+        let data_store = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(metadata.column_count, 4);
+        assert_eq!(data_store.headers, vec!["price", "quantity", "label", "total", "per"]);
+        let rows = &data_store.processed_data;
+        assert_eq!(rows[0]["total"], 10.0);
+        assert_eq!(rows[0]["per"], 0.625);
+        // Dividing by zero, non-numeric, and missing operands give null rather than failing
+        assert_eq!(rows[1]["total"], 0.0);
+        assert!(rows[1]["per"].is_null());
+        assert!(rows[2]["total"].is_null());
+        assert!(rows[3]["total"].is_null());
+
+        assert!(duplicate.is_err());
+        assert!(unknown_column.is_err());
+        assert!(malformed.is_err());
+    }
+
+    fn aggregations(group_by: &[&str], aggregations: &[(&str, &str)]) -> GroupByAgg {
+        GroupByAgg {
+            group_by: group_by.iter().map(|c| c.to_string()).collect(),
+            aggregations: aggregations
+                .iter()
+                .map(|(column, function)| AggregationSpec {
+                    column: column.to_string(),
+                    function: function.to_string(),
+                    alias: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn grouped_aggregations_skip_nulls() {
+        let _globals = globals();
+        let file_id = loaded("region,sales,rep\nnorth,10,a\nsouth,5,b\nnorth,,c\nnorth,20,d\nsouth,NA,e\n");
+        let measures = [
+            ("*", "count"),
+            ("sales", "count"),
+            ("sales", "sum"),
+            ("sales", "avg"),
+            ("sales", "min"),
+            ("sales", "max"),
+        ];
+        let grouped = aggregate_csv_data(&file_id, &aggregations(&["region"], &measures)).unwrap();
+        let total = aggregate_csv_data(&file_id, &aggregations(&[], &measures)).unwrap();
+        let summed_text = aggregate_csv_data(&file_id, &aggregations(&["region"], &[("rep", "sum")]));
+        unload_csv_data(&file_id).unwrap();
+
+        // Groups come back in order of first appearance
+        assert_eq!(grouped.len(), 2);
+        let north = &grouped[0];
+        assert_eq!(north["region"], "north");
+        assert_eq!(north["count_all"], 3);
+        assert_eq!(north["count_sales"], 2);
+        assert_eq!(north["sum_sales"], 30.0);
+        assert_eq!(north["avg_sales"], 15.0);
+        assert_eq!(north["min_sales"], 10);
+        assert_eq!(north["max_sales"], 20);
+        let south = &grouped[1];
+        assert_eq!(south["region"], "south");
+        assert_eq!(south["count_all"], 2);
+        assert_eq!(south["count_sales"], 1);
+        assert_eq!(south["sum_sales"], 5.0);
+        assert_eq!(south["avg_sales"], 5.0);
+
+        // Without group-by columns there is exactly one summary row
+        assert_eq!(total.len(), 1);
+        assert_eq!(total[0]["count_all"], 5);
+        assert_eq!(total[0]["count_sales"], 3);
+        assert_eq!(total[0]["sum_sales"], 35.0);
+        assert!(!total[0].contains_key("region"));
+
+        assert_eq!(summed_text.unwrap_err().kind(), "invalid_query");
+    }
+
+    #[test]
+    fn gzipped_files_read_like_plain_ones() {
+        use std::io::Write;
+        let _globals = globals();
+        let contents = "city,temp,day\nOslo,3.5,2024-01-02\nLima,21,2024-01-03\n\"Rio, BR\",30,2024-01-04\n";
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(contents.as_bytes()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("weather.csv");
+        let gzipped = dir.path().join("weather.csv.gz");
+        std::fs::write(&plain, contents).unwrap();
+        std::fs::write(&gzipped, gzip.finish().unwrap()).unwrap();
+        let (plain, gzipped) = (plain.to_str().unwrap(), gzipped.to_str().unwrap());
+        let options = CsvParseOptions::default();
+
+        let read = |file: &str| read_csv_file(file.to_string(), &options).unwrap();
+        assert_eq!(read(gzipped).content, read(plain).content);
+        assert_eq!(read(gzipped).estimated_rows, read(plain).estimated_rows);
+
+        let validate = |file: &str| validate_csv_file(file.to_string(), &options).unwrap();
+        let (plain_check, gzip_check) = (validate(plain), validate(gzipped));
+        assert!(gzip_check.is_valid);
+        assert_eq!(gzip_check.delimiter, plain_check.delimiter);
+        assert_eq!(gzip_check.column_count, plain_check.column_count);
+        assert_eq!(gzip_check.estimated_rows, plain_check.estimated_rows);
+
+        let analyze = |file: &str| {
+            serde_json::to_value(crate::parser::analyze_csv_columns(file.to_string(), &options, 100, None).unwrap())
+                .unwrap()
+        };
+        assert_eq!(analyze(gzipped), analyze(plain));
+
+        let plain_id = load_csv_into_store(plain, &CSVLoadOptions::default()).unwrap().file_id;
+        let gzip_id = load_csv_into_store(gzipped, &CSVLoadOptions::default())
+            .unwrap()
+            .file_id;
+        let (plain_store, gzip_store) = (stored(&plain_id), stored(&gzip_id));
+        unload_csv_data(&plain_id).unwrap();
+        unload_csv_data(&gzip_id).unwrap();
+        assert_eq!(gzip_store.headers, plain_store.headers);
+        assert_eq!(gzip_store.processed_data, plain_store.processed_data);
+
+        let mut scanned: Vec<String> = crate::parser::scan_directory_for_csvs(dir.path().to_str().unwrap().to_string())
+            .unwrap()
+            .into_iter()
+            .map(|info| info.name)
+            .collect();
+        scanned.sort();
+        assert_eq!(scanned, vec!["weather.csv", "weather.csv.gz"]);
+    }
+
+    #[test]
+    fn formatted_numbers_load_as_numbers() {
+        let _globals = globals();
+        let file = csv_file("item;price;share\na;1.234,56;10%\nb;€ 99,90;5,5%\n");
+        let result = load_csv_into_store(path(&file), &CSVLoadOptions::default()).unwrap();
+        let data_store = stored(&result.file_id);
+        unload_csv_data(&result.file_id).unwrap();
+
+        assert_eq!(data_store.processed_data[1]["price"], 99.9);
+        assert_eq!(data_store.processed_data[1]["share"], 0.055);
+        // The text form is kept alongside the parsed value
+        assert_eq!(data_store.raw_data[0][1], "1.234,56");
+    }
+
+    #[test]
+    fn fixed_width_files_load_and_reload() {
+        let _globals = globals();
+        let file = temp_file(
+            ".txt",
+            "NAME      AGE CITY\nAlice      30 Paris\nBob        4\n\nZoë        25 Köln\n".as_bytes(),
+        );
+        let result = load_fixed_width_into_store(path(&file), vec![10, 4, 5], None).unwrap();
+        let data_store = stored(&result.file_id);
+        let reloaded = reload_csv_in_store(&result.file_id);
+        unload_csv_data(&result.file_id).unwrap();
+        assert_eq!(result.metadata.row_count, 3);
+        assert_eq!(data_store.processed_data[0]["AGE"], serde_json::json!(30));
+        assert_eq!(reloaded.unwrap().row_count, 3);
+
+        // With caller-supplied headers the first line is data
+        let headers = vec!["n".to_string(), "a".to_string(), "c".to_string()];
+        let result = load_fixed_width_into_store(path(&file), vec![10, 4, 5], Some(headers)).unwrap();
+        unload_csv_data(&result.file_id).unwrap();
+        assert_eq!(result.metadata.row_count, 4);
+    }
+
+    #[test]
+    fn cells_are_coerced_to_their_column_type() {
+        let _globals = globals();
+        let file_id =
+            loaded("name,amount,flag\na,1,true\nb,2.5,false\nc,oops,true\nd,4,maybe\ne,5,true\nf,,NA\ng,7,false\n");
+        let rows = stored(&file_id).processed_data;
+        unload_csv_data(&file_id).unwrap();
+
+        let amounts: Vec<&serde_json::Value> = rows.iter().map(|row| &row["amount"]).collect();
+        let expected = serde_json::json!([1, 2.5, null, 4, 5, null, 7]);
+        assert_eq!(amounts, expected.as_array().unwrap().iter().collect::<Vec<_>>());
+        assert_eq!(rows[0]["flag"], serde_json::json!(true));
+        assert_eq!(rows[3]["flag"], serde_json::Value::Null);
+        assert_eq!(rows[0]["name"], "a");
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn datasets_export_to_parquet_in_column_order() {
+        use arrow::array::StringArray;
+
+        let _globals = globals();
+        let file_id = loaded("id,price,ok,when,name\n1,2.5,true,2024-01-02,a\n2,3,false,2024-01-03,b\n");
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("dataset.parquet");
+        let exported = export_dataset_to_parquet(&file_id, out.to_str().unwrap());
+        unload_csv_data(&file_id).unwrap();
+        exported.unwrap();
+
+        let file = std::fs::File::open(&out).unwrap();
+        let batch = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        let schema = batch.schema();
+        let fields: Vec<(&str, String)> = schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), format!("{:?}", f.data_type())))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("id", "Int64".to_string()),
+                ("price", "Float64".to_string()),
+                ("ok", "Boolean".to_string()),
+                ("when", "Timestamp(Millisecond, None)".to_string()),
+                ("name", "Utf8".to_string()),
+            ]
+        );
+        let names = batch.column(4).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(names.value(1), "b");
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_round_trip() {
+        let _globals = globals();
+        let file_id = loaded("id,unit price,ok,name,Name\n1,2.5,true,a,x\n2,3,false,,y\n3,4,true,c,z\n");
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("export.db");
+        let db_path = db_path.to_str().unwrap();
+        let exported = export_to_sqlite(&file_id, db_path, "my table");
+        // Exporting again replaces the table rather than appending to it
+        let exported_again = export_to_sqlite(&file_id, db_path, "my table");
+        unload_csv_data(&file_id).unwrap();
+        assert_eq!(exported.unwrap(), 3);
+        assert_eq!(exported_again.unwrap(), 3);
+
+        assert!(import_from_sqlite(db_path, "my table").is_err());
+        let imported = import_from_sqlite(db_path, "my_table").unwrap();
+        let data_store = stored(&imported.file_id);
+        let reloaded = reload_csv_in_store(&imported.file_id);
+        unload_csv_data(&imported.file_id).unwrap();
+
+        assert_eq!(imported.headers, vec!["id", "unit_price", "ok", "name", "Name_2"]);
+        assert_eq!(imported.metadata.row_count, 3);
+        let rows = &data_store.processed_data;
+        assert_eq!(rows[0]["unit_price"], serde_json::json!(2.5));
+        assert_eq!(rows[2]["id"], serde_json::json!(3));
+        assert_eq!(rows[1]["name"], serde_json::json!(""));
+        assert_eq!(rows[0]["ok"], serde_json::json!(1));
+        assert_eq!(reloaded.unwrap().row_count, 3);
+    }
+
+    #[test]
+    fn binary_files_are_not_loaded() {
+        let _globals = globals();
+        let mut bytes = b"PK\x03\x04".to_vec();
+        bytes.extend((0..2000u32).map(|i| (i * 7 % 256) as u8));
+        let file = temp_file(".csv", &bytes);
+        let result = load_csv_into_store(path(&file), &CSVLoadOptions::default());
+        assert!(result.map(|r| !r.success).unwrap_or(true));
+    }
+
+    #[test]
+    fn tab_pipe_semicolon_and_comma_files_load() {
+        let _globals = globals();
+        for delimiter in ["\t", "|", ";", ","] {
+            let name = if delimiter == "," { "\"Ann, Lee\"" } else { "Ann, Lee" };
+            let d = delimiter;
+            let file = csv_file(&format!(
+                "id{d}name{d}score\n1{d}{name}{d}3.5\n2{d}Bob{d}4\n3{d}Cy{d}5\n"
+            ));
+            let result = load_csv_into_store(path(&file), &CSVLoadOptions::default()).unwrap();
+            let data_store = stored(&result.file_id);
+            unload_csv_data(&result.file_id).unwrap();
+
+            assert!(result.success, "{:?}: {:?}", delimiter, result.error_message);
+            assert_eq!(result.metadata.delimiter, delimiter);
+            assert_eq!(result.headers, vec!["id", "name", "score"], "{:?}", delimiter);
+            assert_eq!(result.metadata.row_count, 3, "{:?}", delimiter);
+            assert_eq!(data_store.processed_data[0]["name"], "Ann, Lee", "{:?}", delimiter);
+        }
+    }
+
+    fn renames(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renames_that_collide_or_miss_are_rejected() {
+        let _globals = globals();
+        let file_id = loaded("price,qty,name\n1,2,a\n3,4,b\n");
+        let collision = rename_columns(&file_id, renames(&[("qty", "name")]));
+        let missing = rename_columns(&file_id, renames(&[("nope", "x")]));
+        let headers = get_csv_headers(&file_id).unwrap();
+        unload_csv_data(&file_id).unwrap();
+
+        assert!(collision.unwrap_err().to_string().contains("duplicate"));
+        assert!(missing.is_err());
+        assert_eq!(headers, vec!["price", "qty", "name"]);
+    }
+
+    #[test]
+    fn renames_rekey_rows_and_active_filters() {
+        let _globals = globals();
+        let file_id = loaded(" Price ($) ,qty,name\n1,2,a\n3,4,b\n");
+        let query: DataQuery = serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {"qty": {"column": "qty", "operator": "greater_than", "value": 2, "case_sensitive": false}},
+            "sort": {"column": "qty", "direction": "desc", "case_sensitive": false},
+            "pagination": {"page": 1, "page_size": 10},
+        }))
+        .unwrap();
+        query_csv_data(&query).unwrap();
+
+        let messy = get_csv_headers(&file_id).unwrap()[0].clone();
+        let cleaned = rename_columns(&file_id, renames(&[(&messy, "price"), ("qty", "quantity")]));
+        // Swapping two names in one call is not a collision
+        let swapped = rename_columns(&file_id, renames(&[("price", "name"), ("name", "price")]));
+        let columns = collect_loaded_columns(&file_id).unwrap();
+        let data_store = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(cleaned.unwrap(), vec!["price", "quantity", "name"]);
+        assert_eq!(swapped.unwrap(), vec!["name", "quantity", "price"]);
+        assert_eq!(columns.filter_count, 1);
+        assert_eq!(columns.columns[1], vec!["4"]);
+        assert_eq!(data_store.processed_data[0]["price"], "a");
+        assert_eq!(data_store.processed_data[0]["quantity"], 2);
+        assert_eq!(data_store.sort_config[0].column, "quantity");
+    }
+
+    fn new_york_count(file_id: &str) -> usize {
+        let query: DataQuery = serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {"city": {"column": "city", "operator": "equals", "value": "New York", "case_sensitive": true}},
+            "pagination": {"page": 1, "page_size": 10},
+        }))
+        .unwrap();
+        query_csv_data(&query).unwrap().filtered_rows
+    }
+
+    const PADDED_CITIES: &str = "city,n\n New York , 1\nNew York,2\n  New York,3\n";
+
+    #[test]
+    fn trimming_stored_columns() {
+        let _globals = globals();
+        let file_id = loaded(PADDED_CITIES);
+        let before = new_york_count(&file_id);
+        let unknown = trim_store_columns(&file_id, Some(vec!["nope".into()]));
+        let city_cells = trim_store_columns(&file_id, Some(vec!["city".into()])).unwrap();
+        let after = new_york_count(&file_id);
+        let remaining = trim_store_columns(&file_id, None).unwrap();
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(before, 1);
+        assert!(unknown.is_err());
+        assert_eq!(city_cells, 2);
+        assert_eq!(after, 3);
+        assert_eq!(remaining, 1);
+    }
+
+    #[test]
+    fn trim_fields_trims_on_load() {
+        let _globals = globals();
+        let file = csv_file(PADDED_CITIES);
+        let options = CSVLoadOptions {
+            trim_fields: true,
+            ..Default::default()
+        };
+        let file_id = load_csv_into_store(path(&file), &options).unwrap().file_id;
+        let count = new_york_count(&file_id);
+        let data_store = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(count, 3);
+        assert_eq!(data_store.processed_data[0]["n"], serde_json::json!(1));
+    }
+
+    #[test]
+    fn unknown_datasets_and_columns_are_typed() {
+        let _globals = globals();
+        let file_id = loaded("a,b\n1,2\n3,4\n");
+        let column = get_column_distinct_values(&file_id, "zzz", None, false).unwrap_err();
+        unload_csv_data(&file_id).unwrap();
+        let dataset = get_csv_headers(&file_id).unwrap_err();
+
+        assert!(
+            matches!(column, DataScienceError::ColumnNotFound(ref c) if c == "zzz"),
+            "{column:?}"
+        );
+        assert!(matches!(dataset, DataScienceError::DatasetNotFound(ref id) if *id == file_id));
+    }
+
+    #[test]
+    fn projected_queries_return_only_the_requested_columns() {
+        let _globals = globals();
+        let file_id = loaded("id,name,city\n1,ann,Oslo\n2,bob,Lima\n");
+        let projection = |columns: serde_json::Value| -> DataQuery {
+            serde_json::from_value(serde_json::json!({
+                "file_id": file_id,
+                "filters": {},
+                "columns": columns,
+                "pagination": {"page": 1, "page_size": 10},
+            }))
+            .unwrap()
+        };
+        let page = query_csv_data(&projection(serde_json::json!(["city", "id"]))).unwrap();
+        let unknown = query_csv_data(&projection(serde_json::json!(["id", "nope"]))).unwrap_err();
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(page.columns, vec!["city", "id"]);
+        assert_eq!(page.data.len(), 2);
+        for row in &page.data {
+            let mut keys: Vec<&String> = row.keys().collect();
+            keys.sort();
+            assert_eq!(keys, vec!["city", "id"]);
+        }
+        assert_eq!(page.data[1]["city"], "Lima");
+        assert!(
+            matches!(unknown, DataScienceError::ColumnNotFound(ref c) if c == "nope"),
+            "{unknown:?}"
+        );
+    }
+
+    #[test]
+    fn distinct_values_and_value_counts() {
+        let _globals = globals();
+        let file_id = loaded("fruit,qty\npear,1\napple,2\npear,3\nfig,4\npear,5\napple,6\n");
+        let distinct = get_column_distinct_values(&file_id, "fruit", None, false).unwrap();
+        let first_two = get_column_distinct_values(&file_id, "fruit", Some(2), false).unwrap();
+        let counts = get_column_value_counts(&file_id, "fruit", None, false).unwrap();
+        let top = get_column_value_counts(&file_id, "fruit", Some(1), false).unwrap();
+        // `use_filters` scans only the rows matching the last query's filters
+        let query: DataQuery = serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {"qty": {"column": "qty", "operator": "less_than", "value": 3, "case_sensitive": false}},
+            "pagination": {"page": 1, "page_size": 10},
+        }))
+        .unwrap();
+        query_csv_data(&query).unwrap();
+        let filtered = get_column_distinct_values(&file_id, "fruit", None, true).unwrap();
+        let filtered_counts = get_column_value_counts(&file_id, "fruit", None, true).unwrap();
+        let unfiltered = get_column_distinct_values(&file_id, "fruit", None, false).unwrap();
+        unload_csv_data(&file_id).unwrap();
+
+        let pairs = |counts: &[ValueCount]| -> Vec<(serde_json::Value, usize)> {
+            counts.iter().map(|c| (c.value.clone(), c.count)).collect()
+        };
+        assert_eq!(distinct, vec!["apple", "fig", "pear"]);
+        assert_eq!(first_two, vec!["apple", "fig"]);
+        assert_eq!(
+            pairs(&counts),
+            vec![("pear".into(), 3), ("apple".into(), 2), ("fig".into(), 1)]
+        );
+        assert_eq!(pairs(&top), vec![("pear".into(), 3)]);
+        assert_eq!(filtered, vec!["apple", "pear"]);
+        // Equal counts fall back to ascending value order
+        assert_eq!(pairs(&filtered_counts), vec![("apple".into(), 1), ("pear".into(), 1)]);
+        assert_eq!(unfiltered, distinct);
+    }
+
+    #[test]
+    fn counts_match_the_query_total() {
+        let _globals = globals();
+        let file_id = loaded("id,name,score\n1,a,5\n2,b,7\n3,c,9\n4,d,2\n");
+        let filters: HashMap<String, FilterSpec> = serde_json::from_value(serde_json::json!({
+            "score": {"column": "score", "operator": "greater_than", "value": 4, "case_sensitive": false}
+        }))
+        .unwrap();
+        let query: DataQuery = serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": filters,
+            "pagination": {"page": 1, "page_size": 1},
+        }))
+        .unwrap();
+        let page = query_csv_data(&query).unwrap();
+        let filtered = count_csv_data(&file_id, &filters).unwrap();
+        let unfiltered = count_csv_data(&file_id, &HashMap::new()).unwrap();
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(page.filtered_rows, 3);
+        assert_eq!(filtered, page.filtered_rows);
+        assert_eq!(unfiltered, 4);
+        assert!(matches!(
+            count_csv_data(&file_id, &filters),
+            Err(DataScienceError::DatasetNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn listing_and_unloading_everything() {
+        // Unloading everything would pull datasets out from under other tests
+        let _globals = change_globals();
+        unload_all_csv_data().unwrap();
+        let first = loaded("x,y\n1,2\n3,4\n");
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let file = csv_file("p,q,r\n1,2,3\n");
+        let second = load_csv_into_store(path(&file), &CSVLoadOptions::default())
+            .unwrap()
+            .file_id;
+
+        let list = list_loaded_csvs().unwrap();
+        let unloaded = unload_all_csv_data().unwrap();
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].file_id, first);
+        assert_eq!(list[1].file_id, second);
+        assert_eq!((list[0].row_count, list[0].column_count), (2, 2));
+        assert_eq!((list[1].row_count, list[1].column_count), (1, 3));
+        assert_eq!(list[1].file_path, path(&file));
+        assert!(list[0].estimated_memory_usage > 0);
+        assert_eq!(unloaded, 2);
+        assert!(list_loaded_csvs().unwrap().is_empty());
+        assert!(get_csv_headers(&first).is_err());
+    }
+
+    #[test]
+    fn single_columns_of_a_loaded_dataset() {
+        let _globals = globals();
+        let file_id = loaded("id,price,color\n1,10,red\n2,20,blue\n3,,red\n4,40,red\n5,30,green\n");
+        let color = analyze_single_column(&file_id, "color");
+        let price = analyze_single_column(&file_id, "price");
+        let missing = analyze_single_column(&file_id, "nope");
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(color.unwrap().value_counts.unwrap()[0], ("red".to_string(), 3));
+        let price = serde_json::to_value(price.unwrap()).unwrap();
+        assert_eq!(price["name"], "price");
+        assert_eq!(price["numeric_stats"]["count"], 4);
+        assert!(matches!(missing, Err(DataScienceError::ColumnNotFound(_))));
+    }
+
+    fn unfiltered_page(file_id: &str, page: usize, page_size: usize) -> DataQuery {
+        serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {},
+            "pagination": {"page": page, "page_size": page_size},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn pages_past_the_end_are_empty() {
+        let _globals = globals();
+        let file_id = loaded("name,v\nx,1\ny,2\nz,3\nw,4\nu,5\n");
+        let last = query_csv_data(&unfiltered_page(&file_id, 3, 2)).unwrap();
+        let past = query_csv_data(&unfiltered_page(&file_id, 7, 2)).unwrap();
+        let huge = query_csv_data(&unfiltered_page(&file_id, usize::MAX, usize::MAX)).unwrap();
+        let page_zero = query_csv_data(&unfiltered_page(&file_id, 0, 2));
+        let size_zero = query_csv_data(&unfiltered_page(&file_id, 1, 0));
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(
+            (last.data.len(), last.total_pages, last.has_next, last.has_prev),
+            (1, 3, false, true)
+        );
+        assert!(past.data.is_empty());
+        assert_eq!(past.current_page, 7);
+        assert!(!past.has_next && past.has_prev);
+        assert!(huge.data.is_empty());
+        assert_eq!(page_zero.unwrap_err().kind(), "invalid_query");
+        assert_eq!(size_zero.unwrap_err().kind(), "invalid_query");
+    }
+
+    #[test]
+    fn invalid_regex_filters_fail_the_query() {
+        let _globals = globals();
+        let file_id = loaded("name,v\nx,1\ny,2\n");
+        let query: DataQuery = serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {"name": {"column": "name", "operator": "regex", "value": "(unclosed", "case_sensitive": false}},
+            "pagination": {"page": 1, "page_size": 10},
+        }))
+        .unwrap();
+        let result = query_csv_data(&query);
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(result.unwrap_err().kind(), "invalid_query");
+    }
+
+    fn null_check_count(file_id: &str, filter: serde_json::Value) -> usize {
+        let query: DataQuery = serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {"note": filter},
+            "pagination": {"page": 1, "page_size": 10},
+        }))
+        .unwrap();
+        query_csv_data(&query).unwrap().filtered_rows
+    }
+
+    #[test]
+    fn null_checks_on_blank_and_token_cells() {
+        let _globals = globals();
+        let file_id = loaded("id,note\n1,\n2,   \n3,NA\n4,hello\n5,null\n6,n/a and more\n");
+        let is_null = null_check_count(
+            &file_id,
+            serde_json::json!({"column": "note", "operator": "is_null", "value": null, "case_sensitive": false}),
+        );
+        let is_not_null = null_check_count(
+            &file_id,
+            serde_json::json!({"column": "note", "operator": "is_not_null", "value": null, "case_sensitive": false}),
+        );
+        // `value` is ignored by both operators and may be left out
+        let without_value = null_check_count(
+            &file_id,
+            serde_json::json!({"column": "note", "operator": "is_null", "case_sensitive": false}),
+        );
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(is_null, 4);
+        assert_eq!(is_not_null, 2);
+        assert_eq!(without_value, 4);
+    }
+
+    #[test]
+    fn range_filters_on_a_text_typed_column() {
+        let _globals = globals();
+        // Leading zeros keep the column text-typed
+        let file_id = loaded("code,name\n007,a\n010,b\n003,c\nn/a-x,d\n012,e\n");
+        let count = |operator: &str, value: serde_json::Value| {
+            let filters: HashMap<String, FilterSpec> = serde_json::from_value(serde_json::json!({
+                "code": {"column": "code", "operator": operator, "value": value, "case_sensitive": false}
+            }))
+            .unwrap();
+            count_csv_data(&file_id, &filters).unwrap()
+        };
+        let counts = [
+            count("greater_than", serde_json::json!(5)),
+            count("greater_than", serde_json::json!("5")),
+            count("less_than_or_equal", serde_json::json!(10)),
+            count("less_than", serde_json::json!("abc")),
+            count("between", serde_json::json!([5, 11])),
+            count("between", serde_json::json!(["008", "011"])),
+            count("greater_than_or_equal", serde_json::json!(12)),
+        ];
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(counts, [3, 3, 3, 0, 2, 1, 1]);
+    }
+
+    fn load_with(contents: &str, options: serde_json::Value) -> Result<Vec<Vec<String>>, DataScienceError> {
+        let file = csv_file(contents);
+        let options: CSVLoadOptions = serde_json::from_value(options).unwrap();
+        let file_id = load_csv_into_store(path(&file), &options)?.file_id;
+        let data_store = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+        Ok(data_store.raw_data)
+    }
+
+    #[test]
+    fn custom_quote_and_escape_characters() {
+        let _globals = globals();
+        let single_quoted = load_with(
+            "name,note\n'Smith, J',a\n'O''Brien',b\n",
+            serde_json::json!({"quote_char": b'\''}),
+        );
+        let backslashed = load_with(
+            "name,note\n\"say \\\"hi\\\", ok\",a\n\"x\",b\n",
+            serde_json::json!({"escape_char": b'\\'}),
+        );
+        let doubled = load_with("name,note\n\"a \"\"b\"\"\",c\n", serde_json::json!({}));
+
+        assert_eq!(
+            single_quoted.unwrap(),
+            vec![vec!["Smith, J", "a"], vec!["O'Brien", "b"]]
+        );
+        assert_eq!(backslashed.unwrap(), vec![vec!["say \"hi\", ok", "a"], vec!["x", "b"]]);
+        assert_eq!(doubled.unwrap(), vec![vec!["a \"b\"", "c"]]);
+    }
+
+    #[test]
+    fn quote_characters_must_differ_from_the_delimiter() {
+        let _globals = globals();
+        let clash = load_with("a,b\n1,2\n", serde_json::json!({"quote_char": b','}));
+        assert!(clash.unwrap_err().to_string().contains("delimiter"));
+        assert!(load_with("a,b\n1,2\n", serde_json::json!({"escape_char": b'\n'})).is_err());
+        assert!(load_with("a,b\n1,2\n", serde_json::json!({"quote_char": 200})).is_err());
+    }
+
+    const DUPLICATED_KEYS: &str = "n,k,v\n1,a,x\n2,b,y\n3,a, x\n4,a,z\n5,b,y\n";
+
+    fn deduplicated(
+        contents: &str,
+        key_columns: Option<&[&str]>,
+        keep: &str,
+    ) -> (Result<usize, DataScienceError>, Vec<String>) {
+        let file_id = loaded(contents);
+        let key_columns = key_columns.map(|columns| columns.iter().map(|c| c.to_string()).collect());
+        let removed = deduplicate_store(&file_id, key_columns, keep, false);
+        let data_store = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+        let kept = data_store.raw_data.iter().map(|row| row[0].clone()).collect();
+        (removed, kept)
+    }
+
+    #[test]
+    fn key_based_dedup_keeps_first_or_last() {
+        let _globals = globals();
+        let (removed, kept) = deduplicated(DUPLICATED_KEYS, Some(&["k", "v"]), "first");
+        assert_eq!(removed.unwrap(), 2);
+        assert_eq!(kept, vec!["1", "2", "4"]);
+        let (removed, kept) = deduplicated(DUPLICATED_KEYS, Some(&["k", "v"]), "last");
+        assert_eq!(removed.unwrap(), 2);
+        assert_eq!(kept, vec!["3", "4", "5"]);
+        let (removed, kept) = deduplicated(DUPLICATED_KEYS, Some(&["k"]), "last");
+        assert_eq!(removed.unwrap(), 3);
+        assert_eq!(kept, vec!["4", "5"]);
+    }
+
+    #[test]
+    fn full_row_dedup() {
+        let _globals = globals();
+        // Every row has a distinct `n`, so no full row repeats
+        let (removed, _) = deduplicated(DUPLICATED_KEYS, None, "first");
+        assert_eq!(removed.unwrap(), 0);
+        let (removed, kept) = deduplicated("n,v\n1,x\n1,x\n2,y\n1,x\n", None, "first");
+        assert_eq!(removed.unwrap(), 2);
+        assert_eq!(kept, vec!["1", "2"]);
+        let (removed, kept) = deduplicated("n,v\n1,x\n1,x\n2,y\n1,x\n", None, "last");
+        assert_eq!(removed.unwrap(), 2);
+        assert_eq!(kept, vec!["2", "1"]);
+    }
+
+    #[test]
+    fn dry_run_dedup_changes_nothing() {
+        let _globals = globals();
+        let file_id = loaded(DUPLICATED_KEYS);
+        let key_columns = || Some(vec!["k".to_string(), "v".to_string()]);
+        let dry_run = deduplicate_store(&file_id, key_columns(), "first", true);
+        let before = stored(&file_id);
+        let removed = deduplicate_store(&file_id, key_columns(), "first", false);
+        let after = stored(&file_id);
+        let listed = list_loaded_csvs().unwrap().into_iter().find(|d| d.file_id == file_id);
+        let bad_keep = deduplicate_store(&file_id, None, "middle", false);
+        let bad_column = deduplicate_store(&file_id, Some(vec!["zz".to_string()]), "first", false);
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(dry_run.unwrap(), 2);
+        assert_eq!(before.processed_data.len(), 5);
+        assert_eq!(removed.unwrap(), 2);
+        assert_eq!(after.processed_data.len(), 3);
+        assert_eq!(after.headers.len(), 3);
+        assert_eq!(listed.unwrap().row_count, 3);
+        assert!(bad_keep.is_err());
+        assert!(matches!(bad_column, Err(DataScienceError::ColumnNotFound(_))));
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn xlsx_sheets_load_like_csv_files() {
+        let _globals = globals();
+        let file = xlsx_file(&[(
+            "Sheet1",
+            r#"<row r="1"><c r="A1" t="inlineStr"><is><t>date</t></is></c><c r="B1" t="inlineStr"><is><t>amount</t></is></c></row><row r="2"><c r="A2" s="1"><v>45292</v></c><c r="B2"><v>12.5</v></c></row><row r="3"><c r="A3" s="1"><v>45323</v></c><c r="B3"><v>7</v></c></row>"#,
+        )]);
+        let result = load_xlsx_into_store(path(&file), None).unwrap();
+        let data_store = stored(&result.file_id);
+        let reloaded = reload_csv_in_store(&result.file_id);
+        unload_csv_data(&result.file_id).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.headers, vec!["date", "amount"]);
+        assert_eq!(result.metadata.row_count, 2);
+        assert_eq!(data_store.processed_data[0]["date"], "2024-01-01");
+        assert_eq!(data_store.processed_data[0]["amount"], serde_json::json!(12.5));
+        assert_eq!(data_store.processed_data[1]["amount"], serde_json::json!(7));
+        assert_eq!(reloaded.unwrap().row_count, 2);
+    }
+
+    #[test]
+    fn loading_a_directory_is_not_a_file() {
+        let _globals = globals();
+        let dir = tempfile::tempdir().unwrap();
+        let error = load_csv_into_store(dir.path().to_str().unwrap(), &CSVLoadOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), "not_a_file");
+    }
+
+    const OVERRIDABLE: &str =
+        "zip,flag,amount,when\n12345,true,10,2024-01-01\n90210,false,007,2024-02-01\n10001,maybe,NA,soon\n";
+
+    fn load_typed(file: &tempfile::NamedTempFile, types: &[(&str, &str)]) -> Result<String, DataScienceError> {
+        let column_types = types.iter().map(|(c, t)| (c.to_string(), t.to_string())).collect();
+        let options = CSVLoadOptions {
+            column_types: Some(column_types),
+            ..Default::default()
+        };
+        Ok(load_csv_into_store(path(file), &options)?.file_id)
+    }
+
+    #[test]
+    fn numeric_looking_columns_can_be_forced_to_strings() {
+        let _globals = globals();
+        let file = csv_file(OVERRIDABLE);
+        let inferred = loaded(OVERRIDABLE);
+        let inferred_store = stored(&inferred);
+        unload_csv_data(&inferred).unwrap();
+        // Types for columns the file doesn't have are ignored
+        let overrides = [
+            ("zip", "string"),
+            ("flag", "string"),
+            ("amount", "number"),
+            ("when", "date"),
+            ("ghost", "boolean"),
+        ];
+        let file_id = load_typed(&file, &overrides).unwrap();
+        let rows = stored(&file_id).processed_data;
+        reload_csv_in_store(&file_id).unwrap();
+        let reloaded = stored(&file_id).processed_data;
+        unload_csv_data(&file_id).unwrap();
+
+        assert!(inferred_store.processed_data[0]["zip"].is_number());
+        assert_eq!(rows[0]["zip"], "12345");
+        assert_eq!(rows[1]["zip"], "90210");
+        assert_eq!(rows[0]["flag"], "true");
+        assert_eq!(rows[1]["amount"], 7);
+        assert!(rows[2]["amount"].is_null());
+        assert_eq!(rows[0]["when"], "2024-01-01");
+        assert!(rows[2]["when"].is_null());
+        assert_eq!(reloaded[0]["zip"], "12345");
+    }
+
+    #[test]
+    fn boolean_overrides_and_unknown_types() {
+        let _globals = globals();
+        let file = csv_file(OVERRIDABLE);
+        let file_id = load_typed(&file, &[("flag", "boolean")]).unwrap();
+        let rows = stored(&file_id).processed_data;
+        unload_csv_data(&file_id).unwrap();
+        let unknown = load_typed(&file, &[("zip", "integer")]).unwrap_err();
+
+        assert_eq!(rows[0]["flag"], true);
+        assert!(rows[2]["flag"].is_null());
+        assert!(unknown.to_string().contains("integer"), "{}", unknown);
+    }
+
+    #[test]
+    fn appending_two_batches() {
+        let _globals = globals();
+        let file_id = loaded("name,score\nalice,3\n");
+        let row = |name: &str, score: &str| vec![name.to_string(), score.to_string()];
+        let sort = serde_json::json!([{"column": "score", "direction": "desc", "case_sensitive": false}]);
+        let query: DataQuery = serde_json::from_value(serde_json::json!({
+            "file_id": file_id,
+            "filters": {},
+            "sort": sort,
+            "pagination": {"page": 1, "page_size": 10},
+        }))
+        .unwrap();
+        query_csv_data(&query).unwrap();
+
+        let first = append_rows_to_store(&file_id, vec![row("bob", "1")]);
+        let second = append_rows_to_store(&file_id, vec![row("carol", "5"), row("dan", "2")]);
+        let mismatched = append_rows_to_store(&file_id, vec![vec!["erin".to_string()]]);
+        // The cached sort from before the appends must not hide the new rows
+        let page = query_csv_data(&query).unwrap();
+        let data_store = stored(&file_id);
+        unload_csv_data(&file_id).unwrap();
+
+        assert_eq!(first.unwrap(), 2);
+        assert_eq!(second.unwrap(), 4);
+        assert!(mismatched.is_err());
+        assert_eq!(data_store.metadata.row_count, 4);
+        let names: Vec<&serde_json::Value> = page.data.iter().map(|r| &r["name"]).collect();
+        assert_eq!(names, ["carol", "alice", "dan", "bob"]);
+    }
 }
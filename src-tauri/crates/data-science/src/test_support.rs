@@ -0,0 +1,105 @@
+//! Fixtures shared by the unit tests.
+
+use std::io::Write;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+// Serializes tests that read process-wide settings (null tokens, numeric inference,
+// the default sample seed, the CSV store) against the tests that change them
+static GLOBALS: RwLock<()> = RwLock::new(());
+
+/// Hold while a test depends on process-wide settings or the CSV store.
+pub(crate) fn globals() -> RwLockReadGuard<'static, ()> {
+    GLOBALS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Hold while a test changes process-wide settings or clears the CSV store.
+pub(crate) fn change_globals() -> RwLockWriteGuard<'static, ()> {
+    GLOBALS.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A temporary file ending in `suffix` holding `bytes`, removed when the handle is dropped.
+pub(crate) fn temp_file(suffix: &str, bytes: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+    file.write_all(bytes).unwrap();
+    file
+}
+
+/// A temporary CSV file with `contents`.
+pub(crate) fn csv_file(contents: &str) -> tempfile::NamedTempFile {
+    temp_file(".csv", contents.as_bytes())
+}
+
+pub(crate) fn path(file: &tempfile::NamedTempFile) -> &str {
+    file.path().to_str().unwrap()
+}
+
+/// A temporary `.xlsx` workbook holding one worksheet per `(name, sheet_data)` pair.
+///
+/// `sheet_data` is the worksheet's `<row>` elements. Cell style 1 is a date format
+/// and style 2 a date-time format.
+#[cfg(feature = "xlsx")]
+pub(crate) fn xlsx_file(sheets: &[(&str, &str)]) -> tempfile::NamedTempFile {
+    const MAIN: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+    const RELATIONSHIPS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+    const DOCUMENT: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+    let mut overrides = String::new();
+    let mut sheet_list = String::new();
+    let mut sheet_rels = String::new();
+    for (i, (name, _)) in sheets.iter().enumerate() {
+        let n = i + 1;
+        overrides.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{n}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#
+        ));
+        sheet_list.push_str(&format!(r#"<sheet name="{name}" sheetId="{n}" r:id="rId{n}"/>"#));
+        sheet_rels.push_str(&format!(
+            r#"<Relationship Id="rId{n}" Type="{DOCUMENT}/worksheet" Target="worksheets/sheet{n}.xml"/>"#
+        ));
+    }
+    let styles_id = sheets.len() + 1;
+    let mut parts = vec![
+        (
+            "[Content_Types].xml".to_string(),
+            format!(
+                r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>{overrides}</Types>"#
+            ),
+        ),
+        (
+            "_rels/.rels".to_string(),
+            format!(
+                r#"<Relationships xmlns="{RELATIONSHIPS}"><Relationship Id="rId1" Type="{DOCUMENT}/officeDocument" Target="xl/workbook.xml"/></Relationships>"#
+            ),
+        ),
+        (
+            "xl/workbook.xml".to_string(),
+            format!(r#"<workbook xmlns="{MAIN}" xmlns:r="{DOCUMENT}"><sheets>{sheet_list}</sheets></workbook>"#),
+        ),
+        (
+            "xl/_rels/workbook.xml.rels".to_string(),
+            format!(
+                r#"<Relationships xmlns="{RELATIONSHIPS}">{sheet_rels}<Relationship Id="rId{styles_id}" Type="{DOCUMENT}/styles" Target="styles.xml"/></Relationships>"#
+            ),
+        ),
+        (
+            "xl/styles.xml".to_string(),
+            format!(
+                r#"<styleSheet xmlns="{MAIN}"><numFmts count="1"><numFmt numFmtId="164" formatCode="yyyy-mm-dd hh:mm"/></numFmts><cellXfs count="3"><xf numFmtId="0"/><xf numFmtId="14" applyNumberFormat="1"/><xf numFmtId="164" applyNumberFormat="1"/></cellXfs></styleSheet>"#
+            ),
+        ),
+    ];
+    for (i, (_, sheet_data)) in sheets.iter().enumerate() {
+        parts.push((
+            format!("xl/worksheets/sheet{}.xml", i + 1),
+            format!(r#"<worksheet xmlns="{MAIN}"><sheetData>{sheet_data}</sheetData></worksheet>"#),
+        ));
+    }
+
+    let file = tempfile::Builder::new().suffix(".xlsx").tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file.reopen().unwrap());
+    for (name, xml) in parts {
+        zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(xml.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+    file
+}
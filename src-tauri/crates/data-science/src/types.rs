@@ -1,5 +1,5 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 // Data structures for CSV processing
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -11,6 +11,7 @@ pub struct CSVContent {
     pub can_process: bool,
     pub file_size: usize,
     pub delimiter: Option<String>,
+    pub encoding_report: EncodingReport,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -26,11 +27,31 @@ pub struct FileMetadata {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CsvValidationResult {
     pub is_valid: bool,
+    pub invalid_reason: Option<String>, // Why is_valid is false
     pub delimiter: String,
     pub estimated_rows: u64,
     pub encoding: String,
     pub has_headers: bool,
     pub column_count: usize,
+    pub line_ending: String, // "LF", "CRLF", "CR", "mixed", or "none"
+    pub delimiter_detection: DetectionResult,
+    pub encoding_detection: DetectionResult,
+    pub encoding_report: EncodingReport,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DetectionResult {
+    pub value: String,
+    pub confidence: f64,           // 0.0 (a guess) to 1.0 (unambiguous)
+    pub runner_up: Option<String>, // Next-best candidate, if any scored
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct EncodingReport {
+    pub encoding: String,
+    pub bom: Option<String>, // "UTF-8", "UTF-16LE", or "UTF-16BE" when the file starts with a byte order mark
+    pub confidence: f64,     // 1.0 with a byte order mark or when chardetng is sure, 0.3 otherwise
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -49,7 +70,71 @@ pub struct ColumnAnalysis {
     pub nullable: bool,
     pub unique_count: usize,
     pub null_percentage: f64,
-    pub sample_values: Vec<String>,
+    pub sample_values: Vec<String>, // Distinct non-null values spread across the column
+    pub most_common: Vec<(String, usize)>, // Top non-null values with their frequencies
+    pub date_format: Option<String>, // chrono format string when data_type is "date"
+    pub is_constant: bool,          // unique_count <= 1
+    pub dominant_value_ratio: f64,  // Share of non-null values equal to the most common one; 0 with none
+    #[serde(default)]
+    pub is_estimated: bool, // unique_count is a HyperLogLog estimate rather than an exact count
+}
+
+/// A single column's analysis for drill-down, from `analyze_single_column`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ColumnDetail {
+    #[serde(flatten)]
+    pub analysis: ColumnAnalysis,
+    pub numeric_stats: Option<NumericStats>, // Number columns with at least one numeric value
+    pub value_counts: Option<Vec<(String, usize)>>, // Other columns: most frequent values first, up to 100
+}
+
+/// One file's columns, statistics, quality report, and schema, as written by `export_data_dictionary`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DataDictionary {
+    pub file_path: String,
+    pub row_count: usize,
+    pub columns: Vec<ColumnDetail>,
+    pub quality: DataQualityReport,
+    pub schema: serde_json::Value, // As returned by `infer_schema`
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NumericStats {
+    pub count: usize, // Values that parsed as numbers; the rest of the fields cover only these
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub q1: f64,              // 25th percentile, interpolated between the two nearest values
+    pub q3: f64,              // 75th percentile, interpolated the same way
+    pub std_dev: Option<f64>, // Sample standard deviation; None with fewer than two numbers
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StreamingProfile {
+    pub rows_scanned: usize, // Well-formed data rows; rows with the wrong field count are skipped
+    pub seed: u64,           // Seed the reservoir samples were drawn with; pass it back to reproduce them
+    pub columns: Vec<StreamingColumnProfile>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StreamingColumnProfile {
+    #[serde(flatten)]
+    pub analysis: ColumnAnalysis, // unique_count and dominant_value_ratio are approximate, most_common is empty
+    pub stats: StreamingStats,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StreamingStats {
+    pub null_count: usize,
+    pub numeric_count: usize, // Values that parsed as numbers; min through variance cover only these
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub sum: f64,
+    pub mean: Option<f64>,
+    pub variance: Option<f64>,         // Sample variance; None with fewer than two numbers
+    pub reservoir_sample: Vec<String>, // Uniform random sample of non-null values
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -58,24 +143,237 @@ pub struct DataQualityReport {
     pub issues: Vec<String>,
     pub recommendations: Vec<String>,
     pub duplicate_count: usize,
+    pub duplicate_examples: Vec<String>, // A few duplicated rows (or key values), fields joined by ", "
     pub completeness_score: f64,
+    pub column_issues: HashMap<String, Vec<String>>, // Column name -> issue codes (mixed_types, leading_trailing_whitespace, inconsistent_casing)
+}
+
+// overall_score = (completeness * completeness_pct + duplicates * unique_row_pct
+//                  + column_issues * clean_column_pct) / (sum of weights), clamped to 0-100
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct QualityWeights {
+    pub completeness: f64,
+    pub duplicates: f64,
+    pub column_issues: f64,
+}
+
+impl Default for QualityWeights {
+    fn default() -> Self {
+        Self {
+            completeness: 0.5,
+            duplicates: 0.3,
+            column_issues: 0.2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SchemaValidationReport {
+    pub is_valid: bool,
+    pub rows_checked: usize,
+    pub missing_columns: Vec<String>,
+    pub extra_columns: Vec<String>,
+    pub violations: Vec<SchemaViolation>, // At most `max_violations`, in file order
+    pub violation_count: usize,           // Every violation found, including ones not listed
+    pub truncated: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SchemaViolation {
+    pub line: Option<u64>, // 1-based line number in the file; None for column-set problems
+    pub column: Option<String>,
+    pub kind: String, // "missing_column", "extra_column", "field_count", "wrong_type", "unexpected_null"
+    pub value: Option<String>,
+    pub message: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ExportOptions {
     pub delimiter: String,
     pub include_headers: bool,
-    pub encoding: String,
+    pub encoding: String, // Encoding label, e.g. "utf-8", "utf-16le", "windows-1252"
     pub quote_fields: String,
+    #[serde(default)]
+    pub decimal_separator: Option<String>, // "." (the default) or ","; numbers holding the delimiter are quoted
+    #[serde(default)]
+    pub compression: Option<String>, // "gzip" or "zip"; the extension is appended to the path if missing
 }
 
+/// Serializes as `{ analysis_type, summary, insights }`, with `summary` shaped by `analysis_type`.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AnalysisResult {
-    pub analysis_type: String,
-    pub summary: serde_json::Value,
+    #[serde(flatten)]
+    pub report: AnalysisReport,
     pub insights: Vec<String>,
 }
 
+impl AnalysisResult {
+    pub fn analysis_type(&self) -> &'static str {
+        self.report.analysis_type()
+    }
+}
+
+/// One report per analysis type; the variant name is the `analysis_type` and its content the `summary`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "analysis_type", content = "summary", rename_all = "snake_case")]
+pub enum AnalysisReport {
+    Summary(SummaryReport),
+    Correlation(CorrelationReport),
+    Distribution(DistributionReport),
+    Outliers(OutlierReport),
+    Patterns(PatternReport),
+    FullReport(Box<FullReport>),
+}
+
+impl AnalysisReport {
+    pub fn analysis_type(&self) -> &'static str {
+        match self {
+            AnalysisReport::Summary(_) => "summary",
+            AnalysisReport::Correlation(_) => "correlation",
+            AnalysisReport::Distribution(_) => "distribution",
+            AnalysisReport::Outliers(_) => "outliers",
+            AnalysisReport::Patterns(_) => "patterns",
+            AnalysisReport::FullReport(_) => "full_report",
+        }
+    }
+
+    /// Which rows the report covers, shared by every report type.
+    pub fn scope_mut(&mut self) -> &mut ReportScope {
+        match self {
+            AnalysisReport::Summary(r) => &mut r.scope,
+            AnalysisReport::Correlation(r) => &mut r.scope,
+            AnalysisReport::Distribution(r) => &mut r.scope,
+            AnalysisReport::Outliers(r) => &mut r.scope,
+            AnalysisReport::Patterns(r) => &mut r.scope,
+            AnalysisReport::FullReport(r) => &mut r.scope,
+        }
+    }
+}
+
+/// Set when a report covers only part of the data; every field is omitted otherwise.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReportScope {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<SampleInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters_applied: Option<usize>, // Number of active store filters the rows had to pass
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SampleInfo {
+    pub mode: String,
+    pub sample_rows: usize,
+    pub rows_scanned: usize,
+    pub seed: Option<u64>, // Random mode only
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SummaryReport {
+    pub total_rows: usize,
+    pub total_columns: usize,
+    pub data_types: BTreeMap<String, usize>, // Column count per data type
+    pub key_columns: Vec<Vec<String>>,
+    pub columns: Vec<SummaryColumn>,
+    #[serde(flatten)]
+    pub scope: ReportScope,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SummaryColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub null_percentage: f64,
+    pub unique_count: usize,
+    pub is_constant: bool,
+    pub dominant_value_ratio: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CorrelationReport {
+    pub method: String,
+    pub numeric_columns_count: usize,
+    pub correlation_available: bool,
+    pub columns: Vec<String>,
+    pub pairs: Vec<CorrelationPair>,
+    #[serde(flatten)]
+    pub scope: ReportScope,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CorrelationPair {
+    pub column_a: String,
+    pub column_b: String,
+    pub coefficient: Option<f64>, // None with fewer than two shared values or a constant column
+    pub n: usize,                 // Rows where both columns have a value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub significant: Option<bool>, // Only with `include_significance`
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DistributionReport {
+    pub columns_analyzed: usize,
+    pub distribution_data: Vec<DistributionColumn>,
+    #[serde(flatten)]
+    pub scope: ReportScope,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DistributionColumn {
+    pub column: String,
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub unique_values: usize,
+    pub null_percentage: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OutlierReport {
+    pub numeric_columns: usize,
+    pub outlier_analysis_available: bool,
+    #[serde(flatten)]
+    pub scope: ReportScope,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PatternReport {
+    pub text_columns: usize,
+    pub pattern_analysis_available: bool,
+    #[serde(flatten)]
+    pub scope: ReportScope,
+}
+
+/// Every sub-report, keyed by its analysis type.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FullReport {
+    pub summary: SummaryReport,
+    pub correlation: CorrelationReport,
+    pub distribution: DistributionReport,
+    pub outliers: OutlierReport,
+    pub patterns: PatternReport,
+    #[serde(flatten)]
+    pub scope: ReportScope,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AnalysisOptions {
+    pub correlation_method: Option<String>, // "pearson" (default) or "spearman"
+    pub include_significance: bool,         // Flag each correlation as significant at the 5% level
+    pub sample: Option<SampleSpec>,         // Analyze a sample of the rows instead of the whole file
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SampleSpec {
+    pub mode: String, // "head" (first `size` rows), "random" (`size` rows), or "every_kth" (every `size`-th row)
+    pub size: usize,
+    #[serde(default)]
+    pub seed: Option<u64>, // Random mode only; the same seed picks the same rows. Default: the default sample seed
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CSVDataStore {
     pub file_id: String,
@@ -85,11 +383,38 @@ pub struct CSVDataStore {
     pub processed_data: Vec<HashMap<String, serde_json::Value>>, // Parsed with types
     pub metadata: DataMetadata,
     pub filters: HashMap<String, FilterSpec>,
-    pub sort_config: Option<SortSpec>,
+    pub sort_config: Vec<SortSpec>,
+    pub load_options: CSVLoadOptions, // Reused when the file is reloaded
+    #[serde(default)]
+    pub source_files: Vec<String>, // Files a merged or joined dataset was built from; empty for single-file loads
+    #[serde(skip)]
+    pub query_cache: Option<(u64, Vec<usize>)>, // (filter/sort signature, matching row indices in order)
     pub created_at: String,
     pub last_accessed: String,
 }
 
+/// A loaded file as listed by `list_loaded_csvs`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DatasetSummary {
+    pub file_id: String,
+    pub file_path: String,
+    pub row_count: usize,
+    pub column_count: usize,
+    pub estimated_memory_usage: usize,
+    pub created_at: String,
+    pub last_accessed: String,
+}
+
+/// Records read by `read_csv_range`, plus where to resume.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CsvRange {
+    pub headers: Option<Vec<String>>, // First record of the file, only when the range starts at byte 0
+    pub rows: Vec<Vec<String>>,
+    pub start_offset: u64, // Requested offset after aligning to a record boundary
+    pub next_offset: u64,  // Pass back as `start_byte` to read the following records
+    pub done: bool,        // True when the range reached the end of the file
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DataMetadata {
     pub row_count: usize,
@@ -104,7 +429,13 @@ pub struct DataMetadata {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FilterSpec {
     pub column: String,
-    pub operator: String, // "equals", "contains", "greater_than", "less_than", etc.
+    // "equals", "not_equals", "contains", "starts_with", "ends_with",
+    // "greater_than", "less_than", "greater_than_or_equal", "less_than_or_equal",
+    // "in" (value is an array of candidates), "between" (value is a [low, high] array, inclusive),
+    // "regex" (value is a pattern matched against the cell text),
+    // "is_null", "is_not_null" (empty, whitespace-only, or JSON null; value is ignored)
+    pub operator: String,
+    #[serde(default)]
     pub value: serde_json::Value,
     pub case_sensitive: bool,
 }
@@ -126,13 +457,48 @@ pub struct PaginationSpec {
 pub struct DataQuery {
     pub file_id: String,
     pub filters: HashMap<String, FilterSpec>,
-    pub sort: Option<SortSpec>,
+    #[serde(default, deserialize_with = "deserialize_sort_specs")]
+    pub sort: Vec<SortSpec>, // Applied in order; later specs break ties
     pub pagination: PaginationSpec,
+    pub columns: Option<Vec<String>>, // Projection; None returns every column
+}
+
+// Accepts `null`, a single sort spec, or a list of sort specs
+fn deserialize_sort_specs<'de, D>(deserializer: D) -> Result<Vec<SortSpec>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(SortSpec),
+        Many(Vec<SortSpec>),
+    }
+
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(spec)) => vec![spec],
+        Some(OneOrMany::Many(specs)) => specs,
+        None => Vec::new(),
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AggregationSpec {
+    pub column: String,   // Measure column, or "*" to count rows
+    pub function: String, // "count", "sum", "avg", "min", "max"
+    pub alias: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GroupByAgg {
+    pub group_by: Vec<String>,
+    pub aggregations: Vec<AggregationSpec>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DataPage {
     pub data: Vec<HashMap<String, serde_json::Value>>,
+    pub columns: Vec<String>, // Keys present in each row, in display order
     pub total_rows: usize,
     pub filtered_rows: usize,
     pub current_page: usize,
@@ -149,4 +515,125 @@ pub struct CSVLoadResult {
     pub metadata: DataMetadata,
     pub headers: Vec<String>,
     pub error_message: Option<String>,
+    pub parse_warnings: Vec<ParseWarning>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CSVLoadOptions {
+    pub strict: bool,      // Fail the load if any row is malformed
+    pub trim_fields: bool, // Strip leading and trailing whitespace from every data field; headers are kept as-is
+    #[serde(flatten)]
+    pub parse: CsvParseOptions,
+    pub fixed_width: Option<FixedWidthSpec>, // Slice lines into fixed-width fields instead of splitting on a delimiter
+    pub sqlite_table: Option<String>,        // Read this table from a SQLite database instead of parsing CSV
+    pub xlsx: Option<XlsxSpec>,              // Read a worksheet of an Excel workbook instead of parsing CSV
+    pub column_types: Option<HashMap<String, String>>, // Force columns to "string", "number", "boolean", or "date"
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct XlsxSpec {
+    pub sheet: Option<String>, // Worksheet name; the first sheet when unset
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FixedWidthSpec {
+    pub field_widths: Vec<usize>,     // Characters per field, left to right
+    pub headers: Option<Vec<String>>, // Column names; None reads them from the first line
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CsvParseOptions {
+    pub delimiter_override: Option<String>, // Bypasses delimiter detection; "\t" means tab
+    pub skip_rows: usize,                   // Lines of preamble to ignore before the header row
+    pub header_row_index: Option<usize>,    // 0-based line of the header row; takes precedence over skip_rows
+    pub has_headers: Option<bool>,          // Overrides header detection when set
+    pub quote_char: Option<u8>,             // Field quote byte; defaults to b'"'
+    pub escape_char: Option<u8>,            // Escapes quotes inside quoted fields instead of doubling them
+}
+
+/// How numeric-looking text is typed during inference; see `set_numeric_inference_options`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct NumericInferenceOptions {
+    pub keep_leading_zeros: bool, // "007" and "0123" stay text (IDs, zip codes, phone numbers)
+    pub big_integers_as_float: bool, // Integers outside the i64 range become floats instead of text
+    pub recognize_hex: bool,      // A column whose values are all "0x"-prefixed hex is numeric
+}
+
+impl Default for NumericInferenceOptions {
+    fn default() -> Self {
+        Self {
+            keep_leading_zeros: true,
+            big_integers_as_float: false,
+            recognize_hex: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValueCount {
+    pub value: serde_json::Value,
+    pub count: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LoadProgress {
+    pub file_id: String,
+    pub rows_processed: usize,
+    pub bytes_processed: u64, // Of the decoded text, so it tracks total_bytes rather than the file size
+    pub total_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AnalysisProgress {
+    pub stage: String, // "reading" while the file is read, then the analysis about to run, e.g. "summary"
+    pub rows_processed: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParseWarning {
+    pub line: u64, // 1-based line number in the file
+    pub expected_columns: usize,
+    pub actual_columns: usize,
+    pub raw: String,
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn query_with_sort(sort: Option<serde_json::Value>) -> DataQuery {
+        let mut query = json!({"file_id": "f", "filters": {}, "pagination": {"page": 1, "page_size": 10}});
+        if let Some(sort) = sort {
+            query["sort"] = sort;
+        }
+        serde_json::from_value(query).unwrap()
+    }
+
+    #[test]
+    fn sort_accepts_one_spec_a_list_or_nothing() {
+        let spec = json!({"column": "c", "direction": "asc", "case_sensitive": true});
+        assert_eq!(query_with_sort(None).sort.len(), 0);
+        assert_eq!(query_with_sort(Some(json!(null))).sort.len(), 0);
+        assert_eq!(query_with_sort(Some(spec.clone())).sort.len(), 1);
+
+        let query = query_with_sort(Some(
+            json!([spec, {"column": "p", "direction": "desc", "case_sensitive": false}]),
+        ));
+        let columns: Vec<&str> = query.sort.iter().map(|s| s.column.as_str()).collect();
+        assert_eq!(columns, ["c", "p"]);
+    }
+
+    #[test]
+    fn load_options_take_parse_options_inline() {
+        let options: CSVLoadOptions = serde_json::from_value(json!({"strict": true, "skip_rows": 2})).unwrap();
+        assert!(options.strict);
+        assert_eq!(options.parse.skip_rows, 2);
+        assert_eq!(options.parse.header_row_index, None);
+    }
 }
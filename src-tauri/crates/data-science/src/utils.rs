@@ -1,24 +1,343 @@
+use crate::analysis::{infer_column_data_type, is_date_value};
+use crate::error::DataScienceError;
 use crate::types::*;
+use regex::Regex;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+// Stripped from either end of a value before it is parsed as a number
+const CURRENCY_SYMBOLS: [char; 8] = ['$', '€', '£', '¥', '₹', '₩', '₽', '¢'];
+// Whole-number values below this are stored as integers
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+// Types a load's `column_types` can force a column to
+pub const OVERRIDE_COLUMN_TYPES: [&str; 4] = ["string", "number", "boolean", "date"];
+
+pub const DEFAULT_NULL_TOKENS: [&str; 7] = ["NA", "N/A", "null", "NULL", "NaN", "-", "?"];
+
+// Error returned by loads and analyses stopped through `cancel_task`
+pub const CANCELLED_ERROR: &str = "Cancelled";
+
+// Seed for random samples that don't name their own; starts at `DEFAULT_SAMPLE_SEED`
+static DEFAULT_SEED: AtomicU64 = AtomicU64::new(crate::parser::DEFAULT_SAMPLE_SEED);
+
+// Suffix that keeps store ids generated in the same millisecond apart
+static NEXT_FILE_ID: AtomicU64 = AtomicU64::new(1);
+
+lazy_static::lazy_static! {
+    static ref NULL_TOKENS: std::sync::RwLock<Vec<String>> =
+        std::sync::RwLock::new(DEFAULT_NULL_TOKENS.iter().map(|t| t.to_string()).collect());
+    static ref NUMERIC_INFERENCE: std::sync::RwLock<NumericInferenceOptions> =
+        std::sync::RwLock::new(NumericInferenceOptions::default());
+    static ref CANCELLATION_FLAGS: std::sync::Mutex<HashMap<String, Arc<AtomicBool>>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// A new store entry id, `csv_{millis}_{n}`, unique within the process.
+pub(crate) fn new_file_id() -> String {
+    format!(
+        "csv_{}_{}",
+        chrono::Utc::now().timestamp_millis(),
+        NEXT_FILE_ID.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Register a cancellable task under `task_id` and return its flag.
+///
+/// The task should poll the flag and release it with `finish_task` when done.
+pub fn register_task(task_id: &str) -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    if let Ok(mut flags) = CANCELLATION_FLAGS.lock() {
+        flags.insert(task_id.to_string(), flag.clone());
+    }
+    flag
+}
+
+/// Ask a running task to stop; false when no task with that id is running.
+pub fn cancel_task(task_id: &str) -> bool {
+    CANCELLATION_FLAGS
+        .lock()
+        .ok()
+        .and_then(|flags| flags.get(task_id).map(|flag| flag.store(true, Ordering::Relaxed)))
+        .is_some()
+}
+
+pub fn finish_task(task_id: &str) {
+    if let Ok(mut flags) = CANCELLATION_FLAGS.lock() {
+        flags.remove(task_id);
+    }
+}
+
+/// Change the seed used by random samples that don't pass their own.
+pub fn set_default_sample_seed(seed: u64) {
+    DEFAULT_SEED.store(seed, Ordering::Relaxed);
+}
+
+pub fn get_default_sample_seed() -> u64 {
+    DEFAULT_SEED.load(Ordering::Relaxed)
+}
+
+/// Small seeded generator (SplitMix64) so random samples are reproducible.
+pub struct SampleRng(u64);
+
+impl SampleRng {
+    pub fn new(seed: u64) -> Self {
+        SampleRng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound`; `bound` must be non-zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+/// Approximate distinct counter (HyperLogLog with 2^12 registers, about 1.6% standard error).
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+const HLL_PRECISION: u32 = 12;
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        HyperLogLog {
+            registers: vec![0; 1 << HLL_PRECISION],
+        }
+    }
+}
+
+impl HyperLogLog {
+    pub fn insert(&mut self, value: &str) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        // The top bits pick a register; it keeps the longest run of leading zeros seen in the rest
+        let index = (hash >> (64 - HLL_PRECISION)) as usize;
+        let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    pub fn estimate(&self) -> usize {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let harmonic_sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-i32::from(r))).sum();
+        let raw = alpha * m * m / harmonic_sum;
+
+        // Linear counting is more accurate while many registers are still empty
+        let empty = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && empty > 0 {
+            (m * (m / empty as f64).ln()).round() as usize
+        } else {
+            raw.round() as usize
+        }
+    }
+}
+
+pub fn check_cancelled(cancel: &AtomicBool) -> Result<(), DataScienceError> {
+    if cancel.load(Ordering::Relaxed) {
+        Err(DataScienceError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+/// Replace the tokens treated as missing values during parsing and analysis.
+pub fn set_null_tokens(tokens: Vec<String>) -> Result<(), DataScienceError> {
+    let mut current = NULL_TOKENS
+        .write()
+        .map_err(|e| format!("Failed to lock null tokens: {}", e))?;
+    *current = tokens.into_iter().map(|t| t.trim().to_string()).collect();
+    Ok(())
+}
+
+pub fn get_null_tokens() -> Vec<String> {
+    NULL_TOKENS.read().map(|tokens| tokens.clone()).unwrap_or_default()
+}
+
+/// Replace the rules for typing leading-zero, out-of-range, and hex numbers.
+pub fn set_numeric_inference_options(options: NumericInferenceOptions) -> Result<(), DataScienceError> {
+    let mut current = NUMERIC_INFERENCE
+        .write()
+        .map_err(|e| format!("Failed to lock numeric inference options: {}", e))?;
+    *current = options;
+    Ok(())
+}
+
+pub fn get_numeric_inference_options() -> NumericInferenceOptions {
+    NUMERIC_INFERENCE.read().map(|options| *options).unwrap_or_default()
+}
+
+/// True for text that parses as a number but should stay text under the current options:
+/// leading-zero digits ("007") and integers outside the i64 range.
+pub fn numeric_stays_text(value: &str) -> bool {
+    let options = get_numeric_inference_options();
+    let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let all_digits = !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && digits.as_bytes()[1].is_ascii_digit();
+    (options.keep_leading_zeros && leading_zero)
+        || (!options.big_integers_as_float && all_digits && value.parse::<i64>().is_err())
+}
+
+/// Value of `0x`-prefixed hex text such as "0x1F", if `recognize_hex` is on.
+pub fn parse_hex_integer(value: &str) -> Option<i64> {
+    if !get_numeric_inference_options().recognize_hex {
+        return None;
+    }
+    let digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))?;
+    i64::from_str_radix(digits, 16)
+        .ok()
+        .filter(|_| !digits.starts_with(['+', '-']))
+}
+
+/// True for empty or whitespace-only cells and for any configured null token.
+pub fn is_null_token(value: &str) -> bool {
+    let trimmed = value.trim();
+    trimmed.is_empty()
+        || NULL_TOKENS
+            .read()
+            .map(|tokens| tokens.iter().any(|t| t == trimmed))
+            .unwrap_or(false)
+}
+
+/// Type every cell, coercing each column to the type most of its values share.
+///
+/// Number and boolean columns hold only numbers or booleans, with null for
+/// cells that don't fit; other columns keep their text. Null tokens are null
+/// in every column. This keeps a column's type stable for sorting and display.
+/// Columns named in `overrides` get that type instead (see `force_value`).
+pub fn infer_data_types(
+    raw_data: &[Vec<String>],
+    headers: &[String],
+    overrides: Option<&HashMap<String, String>>,
+) -> Vec<HashMap<String, serde_json::Value>> {
+    let inferred = infer_column_types(raw_data, headers);
+    let forced: Vec<Option<&String>> = headers
+        .iter()
+        .map(|header| overrides.and_then(|overrides| overrides.get(header)))
+        .collect();
 
-pub fn infer_data_types(raw_data: &[Vec<String>], headers: &[String]) -> Vec<HashMap<String, serde_json::Value>> {
     raw_data
         .iter()
         .map(|row| {
-            let mut processed_row = HashMap::new();
-            for (i, value) in row.iter().enumerate() {
-                if let Some(header) = headers.get(i) {
-                    processed_row.insert(header.clone(), infer_value_type(value));
-                }
-            }
-            processed_row
+            headers
+                .iter()
+                .zip(row)
+                .zip(inferred.iter().zip(&forced))
+                .map(|((header, value), (column_type, forced))| {
+                    let typed = match forced {
+                        Some(forced) => force_value(value, forced),
+                        None => coerce_value(value, column_type),
+                    };
+                    (header.clone(), typed)
+                })
+                .collect()
         })
         .collect()
 }
 
+/// Check that every type in a `column_types` override is one of `OVERRIDE_COLUMN_TYPES`.
+pub fn check_column_types(column_types: &HashMap<String, String>) -> Result<(), DataScienceError> {
+    match column_types
+        .iter()
+        .find(|(_, column_type)| !OVERRIDE_COLUMN_TYPES.contains(&column_type.as_str()))
+    {
+        Some((column, column_type)) => Err(DataScienceError::InvalidArgument(format!(
+            "Unknown type '{}' for column '{}': expected one of {}",
+            column_type,
+            column,
+            OVERRIDE_COLUMN_TYPES.join(", ")
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The majority type (`infer_column_data_type`) of each column named in `headers`.
+pub fn infer_column_types(raw_data: &[Vec<String>], headers: &[String]) -> Vec<String> {
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            let values: Vec<String> = raw_data.iter().filter_map(|row| row.get(i).cloned()).collect();
+            infer_column_data_type(header, &values)
+        })
+        .collect()
+}
+
+fn coerce_value(value: &str, column_type: &str) -> serde_json::Value {
+    let typed = infer_value_type(value);
+    match (column_type, &typed) {
+        (_, serde_json::Value::Null) => typed,
+        ("number", serde_json::Value::Number(_)) | ("boolean", serde_json::Value::Bool(_)) => typed,
+        ("number", serde_json::Value::String(_)) => {
+            parse_hex_integer(value.trim()).map_or(serde_json::Value::Null, serde_json::Value::from)
+        }
+        ("number" | "boolean", _) => serde_json::Value::Null,
+        _ => serde_json::Value::String(value.to_string()),
+    }
+}
+
+/// Type a cell of a column whose type the caller forced, with null when it doesn't convert.
+///
+/// A "string" column keeps the text as-is, even when it looks numeric; a
+/// "number" column also reads leading-zero and oversized integers; a "date"
+/// column keeps text in a recognized date format. Null tokens stay null.
+fn force_value(value: &str, column_type: &str) -> serde_json::Value {
+    let trimmed = value.trim();
+    if !trimmed.is_empty() && is_null_token(trimmed) {
+        return serde_json::Value::Null;
+    }
+    match column_type {
+        "string" => serde_json::Value::String(value.to_string()),
+        "number" => match infer_value_type(value) {
+            typed @ serde_json::Value::Number(_) => typed,
+            _ => trimmed
+                .parse::<i64>()
+                .map(serde_json::Value::from)
+                .ok()
+                .or_else(|| {
+                    trimmed
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map(Into::into)
+                })
+                .or_else(|| parse_hex_integer(trimmed).map(serde_json::Value::from))
+                .unwrap_or(serde_json::Value::Null),
+        },
+        "boolean" => match infer_value_type(value) {
+            typed @ serde_json::Value::Bool(_) => typed,
+            _ => serde_json::Value::Null,
+        },
+        "date" if is_date_value(trimmed) => serde_json::Value::String(value.to_string()),
+        _ => serde_json::Value::Null,
+    }
+}
+
 pub fn infer_value_type(value: &str) -> serde_json::Value {
     let trimmed = value.trim();
 
+    // Configured null tokens ("NA", "N/A", ...) become JSON null
+    if !trimmed.is_empty() && is_null_token(trimmed) {
+        return serde_json::Value::Null;
+    }
+
+    // ID-like digits ("007") and integers too big for i64 stay text
+    if numeric_stays_text(trimmed) {
+        return serde_json::Value::String(value.to_string());
+    }
+
     // Try to parse as integer first
     if let Ok(int_val) = trimmed.parse::<i64>() {
         return serde_json::Value::Number(serde_json::Number::from(int_val));
@@ -31,6 +350,16 @@ pub fn infer_value_type(value: &str) -> serde_json::Value {
         }
     }
 
+    // Formatted numbers ("$1,000", "1.234,56", "45%"); the raw row keeps the original text
+    if let Some(number) = parse_formatted_number(trimmed) {
+        if number.fract() == 0.0 && number.abs() < MAX_SAFE_INTEGER {
+            return serde_json::Value::Number(serde_json::Number::from(number as i64));
+        }
+        if let Some(num) = serde_json::Number::from_f64(number) {
+            return serde_json::Value::Number(num);
+        }
+    }
+
     // Try to parse as boolean
     match trimmed.to_lowercase().as_str() {
         "true" => return serde_json::Value::Bool(true),
@@ -42,19 +371,113 @@ pub fn infer_value_type(value: &str) -> serde_json::Value {
     serde_json::Value::String(value.to_string())
 }
 
-pub fn apply_filters(data: &[HashMap<String, serde_json::Value>], filters: &HashMap<String, FilterSpec>) -> Vec<usize> {
+/// Parse a number written with currency symbols, thousands separators, or a trailing `%`.
+///
+/// Both `1,234.56` and `1.234,56` are accepted: when a value has both separators
+/// the last one is the decimal point. A lone comma is a decimal comma unless
+/// exactly three digits follow it (`12,5` is 12.5, `1,234` is 1234). Repeated
+/// dots without a comma (`1.2.3`, IP addresses) are not numbers. Percentages are
+/// scaled by 1/100. Plain numbers that `str::parse` accepts aren't handled here.
+pub fn parse_formatted_number(value: &str) -> Option<f64> {
+    let mut text = value.trim();
+
+    let mut scale = 1.0;
+    if let Some(rest) = text.strip_suffix('%') {
+        text = rest.trim_end();
+        scale = 0.01;
+    }
+
+    // Sign and currency symbol may come in either order: "-$5", "$-5", "5 €"
+    let mut negative = false;
+    loop {
+        let before = text;
+        if let Some(rest) = text.strip_prefix('-') {
+            negative = !negative;
+            text = rest;
+        } else if let Some(rest) = text.strip_prefix('+') {
+            text = rest;
+        }
+        text = text
+            .trim_start_matches(|c: char| CURRENCY_SYMBOLS.contains(&c) || c.is_whitespace())
+            .trim_end_matches(|c: char| CURRENCY_SYMBOLS.contains(&c) || c.is_whitespace());
+        if text == before {
+            break;
+        }
+    }
+
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_digit() || c == ',' || c == '.') {
+        return None;
+    }
+
+    let (last_comma, last_dot) = (text.rfind(','), text.rfind('.'));
+    let (thousands, decimal) = match (last_comma, last_dot) {
+        (Some(comma), Some(dot)) if comma > dot => ('.', Some(',')),
+        (Some(_), Some(_)) => (',', Some('.')),
+        (Some(comma), None) => {
+            let lone = text.matches(',').count() == 1;
+            if lone && text.len() - comma - 1 != 3 {
+                ('.', Some(','))
+            } else {
+                (',', None)
+            }
+        }
+        (None, Some(_)) if text.matches('.').count() == 1 => (',', Some('.')),
+        (None, None) => (',', None),
+        _ => return None,
+    };
+
+    let (integer, fraction) = match decimal {
+        Some(separator) => text.rsplit_once(separator)?,
+        None => (text, ""),
+    };
+    if decimal.is_some() && (fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+
+    // Thousands groups: 1-3 leading digits, then groups of exactly three
+    let groups: Vec<&str> = integer.split(thousands).collect();
+    let valid_groups = groups.len() == 1
+        || (!groups[0].is_empty() && groups[0].len() <= 3 && groups[1..].iter().all(|g| g.len() == 3));
+    if integer.is_empty() || !valid_groups || !groups.iter().all(|g| g.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+
+    let normalized = format!(
+        "{}.{}",
+        groups.concat(),
+        if fraction.is_empty() { "0" } else { fraction }
+    );
+    let number = normalized.parse::<f64>().ok()? * scale;
+    let number = if negative { -number } else { number };
+    number.is_finite().then_some(number)
+}
+
+pub fn apply_filters(
+    data: &[HashMap<String, serde_json::Value>],
+    filters: &HashMap<String, FilterSpec>,
+) -> Result<Vec<usize>, DataScienceError> {
     if filters.is_empty() {
-        return (0..data.len()).collect();
+        return Ok((0..data.len()).collect());
     }
 
-    data.iter()
+    let regex_cache = compile_filter_regexes(filters)?;
+
+    Ok(data
+        .iter()
         .enumerate()
         .filter_map(|(index, row)| {
             let matches_all_filters = filters.iter().all(|(_, filter)| {
                 if let Some(value) = row.get(&filter.column) {
-                    match_filter_value(value, &filter.operator, &filter.value, filter.case_sensitive)
+                    match_filter_value(
+                        value,
+                        &filter.operator,
+                        &filter.value,
+                        filter.case_sensitive,
+                        &regex_cache,
+                    )
                 } else {
-                    false
+                    // A missing cell (short row) counts as null
+                    filter.operator == "is_null"
                 }
             });
 
@@ -64,7 +487,42 @@ pub fn apply_filters(data: &[HashMap<String, serde_json::Value>], filters: &Hash
                 None
             }
         })
-        .collect()
+        .collect())
+}
+
+pub fn regex_cache_key(pattern: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        pattern.to_string()
+    } else {
+        format!("(?i){}", pattern)
+    }
+}
+
+// Compile every regex filter pattern once per query, keyed by `regex_cache_key`
+pub fn compile_filter_regexes(
+    filters: &HashMap<String, FilterSpec>,
+) -> Result<HashMap<String, Regex>, DataScienceError> {
+    let mut cache = HashMap::new();
+    for filter in filters.values().filter(|f| f.operator == "regex") {
+        let pattern = filter.value.as_str().ok_or_else(|| {
+            DataScienceError::InvalidQuery(format!(
+                "Regex filter on column '{}' requires a string pattern",
+                filter.column
+            ))
+        })?;
+        if let std::collections::hash_map::Entry::Vacant(entry) =
+            cache.entry(regex_cache_key(pattern, filter.case_sensitive))
+        {
+            let regex = Regex::new(entry.key()).map_err(|e| {
+                DataScienceError::InvalidQuery(format!(
+                    "Invalid regex '{}' for column '{}': {}",
+                    pattern, filter.column, e
+                ))
+            })?;
+            entry.insert(regex);
+        }
+    }
+    Ok(cache)
 }
 
 pub fn match_filter_value(
@@ -72,15 +530,13 @@ pub fn match_filter_value(
     operator: &str,
     filter_value: &serde_json::Value,
     case_sensitive: bool,
+    regex_cache: &HashMap<String, Regex>,
 ) -> bool {
     match operator {
-        "equals" => {
-            if case_sensitive {
-                value == filter_value
-            } else {
-                value.to_string().to_lowercase() == filter_value.to_string().to_lowercase()
-            }
-        }
+        "is_null" => is_null_value(value),
+        "is_not_null" => !is_null_value(value),
+        "equals" => values_equal(value, filter_value, case_sensitive),
+        "not_equals" => !values_equal(value, filter_value, case_sensitive),
         "contains" => {
             let value_str = if case_sensitive {
                 value.to_string()
@@ -94,55 +550,112 @@ pub fn match_filter_value(
             };
             value_str.contains(&filter_str)
         }
-        "greater_than" => {
-            if let (Some(v), Some(f)) = (value.as_f64(), filter_value.as_f64()) {
-                v > f
-            } else {
-                false
-            }
-        }
-        "less_than" => {
-            if let (Some(v), Some(f)) = (value.as_f64(), filter_value.as_f64()) {
-                v < f
-            } else {
-                false
-            }
-        }
-        "greater_than_or_equal" => {
-            if let (Some(v), Some(f)) = (value.as_f64(), filter_value.as_f64()) {
-                v >= f
-            } else {
-                false
+        "starts_with" | "ends_with" => match filter_value.as_str() {
+            Some(affix) => {
+                let (value_str, affix) = if case_sensitive {
+                    (value_to_text(value), affix.to_string())
+                } else {
+                    (value_to_text(value).to_lowercase(), affix.to_lowercase())
+                };
+                if operator == "starts_with" {
+                    value_str.starts_with(&affix)
+                } else {
+                    value_str.ends_with(&affix)
+                }
             }
-        }
-        "less_than_or_equal" => {
-            if let (Some(v), Some(f)) = (value.as_f64(), filter_value.as_f64()) {
-                v <= f
-            } else {
-                false
+            None => false,
+        },
+        "regex" => match filter_value.as_str() {
+            Some(pattern) => regex_cache
+                .get(&regex_cache_key(pattern, case_sensitive))
+                .is_some_and(|regex| regex.is_match(&value_to_text(value))),
+            None => false,
+        },
+        "in" => match filter_value.as_array() {
+            Some(candidates) => candidates
+                .iter()
+                .any(|candidate| values_equal(value, candidate, case_sensitive)),
+            None => false,
+        },
+        "between" => match filter_value.as_array().map(|bounds| bounds.as_slice()) {
+            Some([low, high]) => {
+                if let (Some(v), Some(lo), Some(hi)) = (numeric_value(value), numeric_value(low), numeric_value(high)) {
+                    lo <= v && v <= hi
+                } else if let (Some(lo), Some(hi)) = (low.as_str(), high.as_str()) {
+                    let text = value_to_text(value);
+                    let (text, lo, hi) = if case_sensitive {
+                        (text, lo.to_string(), hi.to_string())
+                    } else {
+                        (text.to_lowercase(), lo.to_lowercase(), hi.to_lowercase())
+                    };
+                    lo <= text && text <= hi
+                } else {
+                    false
+                }
             }
-        }
+            _ => false,
+        },
+        "greater_than" => compare_numbers(value, filter_value).is_some_and(|o| o.is_gt()),
+        "less_than" => compare_numbers(value, filter_value).is_some_and(|o| o.is_lt()),
+        "greater_than_or_equal" => compare_numbers(value, filter_value).is_some_and(|o| o.is_ge()),
+        "less_than_or_equal" => compare_numbers(value, filter_value).is_some_and(|o| o.is_le()),
         _ => false,
     }
 }
 
+// None unless both values are numbers or numeric text
+fn compare_numbers(value: &serde_json::Value, filter_value: &serde_json::Value) -> Option<std::cmp::Ordering> {
+    numeric_value(value)?.partial_cmp(&numeric_value(filter_value)?)
+}
+
+// Numbers, and strings holding a plain number ("42", " 3.5 ") that inference left as text
+fn numeric_value(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::String(s) => s.trim().parse::<f64>().ok().filter(|v| v.is_finite()),
+        other => other.as_f64(),
+    }
+}
+
+fn values_equal(value: &serde_json::Value, other: &serde_json::Value, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        value == other
+    } else {
+        value.to_string().to_lowercase() == other.to_string().to_lowercase()
+    }
+}
+
+// String cells are used as-is; other values use their JSON representation
+pub fn value_to_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
 pub fn apply_sorting(
     indices: &[usize],
     data: &[HashMap<String, serde_json::Value>],
-    sort_spec: &SortSpec,
+    sort_specs: &[SortSpec],
 ) -> Vec<usize> {
     let mut sorted_indices = indices.to_vec();
 
+    // Stable sort, so rows that tie on every key keep their original order
     sorted_indices.sort_by(|&a, &b| {
-        let value_a = data[a].get(&sort_spec.column);
-        let value_b = data[b].get(&sort_spec.column);
+        sort_specs
+            .iter()
+            .fold(std::cmp::Ordering::Equal, |ordering, sort_spec| {
+                ordering.then_with(|| {
+                    let value_a = data[a].get(&sort_spec.column);
+                    let value_b = data[b].get(&sort_spec.column);
 
-        match (value_a, value_b) {
-            (Some(a), Some(b)) => compare_values(a, b, &sort_spec.direction, sort_spec.case_sensitive),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => std::cmp::Ordering::Equal,
-        }
+                    match (value_a, value_b) {
+                        (Some(a), Some(b)) => compare_values(a, b, &sort_spec.direction, sort_spec.case_sensitive),
+                        (Some(_), None) => std::cmp::Ordering::Less,
+                        (None, Some(_)) => std::cmp::Ordering::Greater,
+                        (None, None) => std::cmp::Ordering::Equal,
+                    }
+                })
+            })
     });
 
     sorted_indices
@@ -186,21 +699,459 @@ pub fn compare_values(
     }
 }
 
+/// Count each distinct value of `column` across the given rows, in order of first appearance.
+///
+/// Values are compared by their JSON form, so the number `1` and the string `"1"` stay distinct.
+pub fn count_distinct_values(
+    data: &[HashMap<String, serde_json::Value>],
+    indices: &[usize],
+    column: &str,
+) -> Vec<(serde_json::Value, usize)> {
+    let mut counts: Vec<(serde_json::Value, usize)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for value in indices.iter().filter_map(|&idx| data.get(idx)?.get(column)) {
+        match positions.entry(value.to_string()) {
+            std::collections::hash_map::Entry::Occupied(entry) => counts[*entry.get()].1 += 1,
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(counts.len());
+                counts.push((value.clone(), 1));
+            }
+        }
+    }
+
+    counts
+}
+
+pub fn is_null_value(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => true,
+        serde_json::Value::String(s) => is_null_token(s),
+        _ => false,
+    }
+}
+
+#[derive(Default)]
+struct AggregateAccumulator {
+    count: usize,
+    sum: f64,
+    min: Option<serde_json::Value>,
+    max: Option<serde_json::Value>,
+}
+
+pub fn aggregate_rows(
+    data: &[HashMap<String, serde_json::Value>],
+    headers: &[String],
+    spec: &GroupByAgg,
+) -> Result<Vec<HashMap<String, serde_json::Value>>, DataScienceError> {
+    for column in &spec.group_by {
+        if !headers.contains(column) {
+            return Err(DataScienceError::ColumnNotFound(column.clone()));
+        }
+    }
+    for agg in &spec.aggregations {
+        if !matches!(agg.function.as_str(), "count" | "sum" | "avg" | "min" | "max") {
+            return Err(DataScienceError::InvalidQuery(format!(
+                "Unknown aggregation function: {}",
+                agg.function
+            )));
+        }
+        let counts_rows = agg.column == "*" && agg.function == "count";
+        if !counts_rows && !headers.contains(&agg.column) {
+            return Err(DataScienceError::ColumnNotFound(agg.column.clone()));
+        }
+    }
+
+    // Groups are kept in order of first appearance
+    let mut group_index: HashMap<Vec<String>, usize> = HashMap::new();
+    let mut groups: Vec<(Vec<serde_json::Value>, Vec<AggregateAccumulator>)> = Vec::new();
+
+    for row in data {
+        let key_values: Vec<serde_json::Value> = spec
+            .group_by
+            .iter()
+            .map(|column| row.get(column).cloned().unwrap_or(serde_json::Value::Null))
+            .collect();
+        let key: Vec<String> = key_values.iter().map(|v| v.to_string()).collect();
+
+        let slot = *group_index.entry(key).or_insert_with(|| {
+            groups.push((
+                key_values,
+                spec.aggregations
+                    .iter()
+                    .map(|_| AggregateAccumulator::default())
+                    .collect(),
+            ));
+            groups.len() - 1
+        });
+
+        for (agg, acc) in spec.aggregations.iter().zip(groups[slot].1.iter_mut()) {
+            if agg.column == "*" {
+                acc.count += 1;
+                continue;
+            }
+
+            let value = match row.get(&agg.column) {
+                Some(v) if !is_null_value(v) => v,
+                _ => continue,
+            };
+
+            match agg.function.as_str() {
+                "sum" | "avg" => {
+                    let number = value.as_f64().ok_or_else(|| {
+                        DataScienceError::InvalidQuery(format!(
+                            "Cannot apply '{}' to non-numeric column '{}' (found value {})",
+                            agg.function, agg.column, value
+                        ))
+                    })?;
+                    acc.sum += number;
+                }
+                "min"
+                    if acc
+                        .min
+                        .as_ref()
+                        .is_none_or(|current| compare_values(value, current, "asc", true).is_lt()) =>
+                {
+                    acc.min = Some(value.clone());
+                }
+                "max"
+                    if acc
+                        .max
+                        .as_ref()
+                        .is_none_or(|current| compare_values(value, current, "asc", true).is_gt()) =>
+                {
+                    acc.max = Some(value.clone());
+                }
+                _ => {}
+            }
+            acc.count += 1;
+        }
+    }
+
+    // Aggregating without group-by columns always yields a single summary row
+    if groups.is_empty() && spec.group_by.is_empty() {
+        groups.push((
+            Vec::new(),
+            spec.aggregations
+                .iter()
+                .map(|_| AggregateAccumulator::default())
+                .collect(),
+        ));
+    }
+
+    let results = groups
+        .into_iter()
+        .map(|(key_values, accumulators)| {
+            let mut row: HashMap<String, serde_json::Value> = spec.group_by.iter().cloned().zip(key_values).collect();
+
+            for (agg, acc) in spec.aggregations.iter().zip(accumulators) {
+                let name = agg.alias.clone().unwrap_or_else(|| {
+                    let column = if agg.column == "*" { "all" } else { agg.column.as_str() };
+                    format!("{}_{}", agg.function, column)
+                });
+                let value = match agg.function.as_str() {
+                    "count" => serde_json::Value::from(acc.count),
+                    "sum" => serde_json::Number::from_f64(acc.sum)
+                        .map(serde_json::Value::Number)
+                        .unwrap_or(serde_json::Value::Null),
+                    "avg" if acc.count > 0 => serde_json::Number::from_f64(acc.sum / acc.count as f64)
+                        .map(serde_json::Value::Number)
+                        .unwrap_or(serde_json::Value::Null),
+                    "min" => acc.min.unwrap_or(serde_json::Value::Null),
+                    "max" => acc.max.unwrap_or(serde_json::Value::Null),
+                    _ => serde_json::Value::Null,
+                };
+                row.insert(name, value);
+            }
+            row
+        })
+        .collect();
+
+    Ok(results)
+}
+
 pub fn calculate_memory_usage(data: &[HashMap<String, serde_json::Value>]) -> usize {
     data.len() * std::mem::size_of::<HashMap<String, serde_json::Value>>()
 }
 
 pub fn detect_encoding(bytes: &[u8]) -> String {
+    detect_encoding_with_confidence(bytes).value
+}
+
+/// Guess the encoding, with confidence 1.0 when chardetng is sure and 0.3 otherwise.
+///
+/// When the winner is UTF-8 the runner-up is the best legacy encoding. A byte order
+/// mark overrides chardetng, with confidence 1.0 and chardetng's guess as the runner-up.
+pub fn detect_encoding_with_confidence(bytes: &[u8]) -> DetectionResult {
     let mut detector = chardetng::EncodingDetector::new();
     detector.feed(bytes, true);
-    let encoding = detector.guess(None, true);
+    let (encoding, confident) = detector.guess_assess(None, true);
+
+    if let Some((bom_encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+        let value = encoding_display_name(bom_encoding);
+        let runner_up = Some(encoding_display_name(encoding)).filter(|name| *name != value);
+        return DetectionResult {
+            value,
+            confidence: 1.0,
+            runner_up,
+        };
+    }
+
+    let value = encoding_display_name(encoding);
+    let runner_up = Some(encoding_display_name(detector.guess(None, false))).filter(|name| *name != value);
+    DetectionResult {
+        value,
+        confidence: if confident { 1.0 } else { 0.3 },
+        runner_up,
+    }
+}
 
+/// The detected encoding, its byte order mark if any, and how sure the detection is.
+pub fn detect_encoding_report(bytes: &[u8]) -> EncodingReport {
+    let detection = detect_encoding_with_confidence(bytes);
+    EncodingReport {
+        encoding: detection.value,
+        bom: encoding_rs::Encoding::for_bom(bytes).map(|(encoding, _)| encoding_display_name(encoding)),
+        confidence: detection.confidence,
+    }
+}
+
+fn encoding_display_name(encoding: &'static encoding_rs::Encoding) -> String {
     match encoding.name() {
-        "UTF-8" => "UTF-8".to_string(),
-        "UTF-16LE" => "UTF-16LE".to_string(),
-        "UTF-16BE" => "UTF-16BE".to_string(),
         "windows-1252" => "Windows-1252".to_string(),
-        "ISO-8859-1" => "ISO-8859-1".to_string(),
-        _ => "UTF-8".to_string(), // Default fallback
+        name => name.to_string(), // encoding_rs's canonical name, e.g. "UTF-8", "UTF-16LE", "Shift_JIS"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{change_globals, globals};
+    use serde_json::json;
+
+    fn rows(values: serde_json::Value) -> Vec<HashMap<String, serde_json::Value>> {
+        serde_json::from_value(values).unwrap()
+    }
+
+    fn sort(column: &str, direction: &str) -> SortSpec {
+        SortSpec {
+            column: column.to_string(),
+            direction: direction.to_string(),
+            case_sensitive: true,
+        }
+    }
+
+    fn matches(value: serde_json::Value, operator: &str, filter: serde_json::Value, case_sensitive: bool) -> bool {
+        match_filter_value(&value, operator, &filter, case_sensitive, &HashMap::new())
+    }
+
+    #[test]
+    fn not_equals_filter() {
+        assert!(matches(json!("Apple"), "not_equals", json!("pear"), true));
+        assert!(matches(json!("Apple"), "not_equals", json!("apple"), true));
+        assert!(!matches(json!("Apple"), "not_equals", json!("apple"), false));
+        assert!(!matches(json!(3), "not_equals", json!(3), true));
+    }
+
+    fn regex_matches(value: &str, pattern: &str, case_sensitive: bool) -> bool {
+        let filter: FilterSpec = serde_json::from_value(json!({
+            "column": "name",
+            "operator": "regex",
+            "value": pattern,
+            "case_sensitive": case_sensitive,
+        }))
+        .unwrap();
+        let regexes = compile_filter_regexes(&HashMap::from([("name".to_string(), filter)])).unwrap();
+        match_filter_value(&json!(value), "regex", &json!(pattern), case_sensitive, &regexes)
+    }
+
+    #[test]
+    fn regex_filter_case_sensitivity() {
+        assert!(regex_matches("Apple pie", "^Apple", true));
+        assert!(!regex_matches("apple pie", "^Apple", true));
+        // Case-insensitive filters compile the pattern with a `(?i)` prefix
+        assert!(regex_matches("apple pie", "^Apple", false));
+        assert!(regex_matches("APPLE PIE", "pie$", false));
+        assert!(!regex_matches("apple tart", "pie$", false));
+    }
+
+    #[test]
+    fn starts_with_filter() {
+        assert!(matches(json!("Apple"), "starts_with", json!("Ap"), true));
+        assert!(!matches(json!("Apple"), "starts_with", json!("ap"), true));
+        assert!(matches(json!("Apple"), "starts_with", json!("ap"), false));
+        assert!(matches(json!(1234), "starts_with", json!("12"), true));
+        assert!(!matches(json!("Apple"), "starts_with", json!(1), true));
+    }
+
+    #[test]
+    fn ends_with_filter() {
+        assert!(matches(json!("Apple"), "ends_with", json!("ple"), true));
+        assert!(!matches(json!("Apple"), "ends_with", json!("PLE"), true));
+        assert!(matches(json!("Apple"), "ends_with", json!("PLE"), false));
+        assert!(!matches(json!("Apple"), "ends_with", json!(null), false));
+    }
+
+    #[test]
+    fn in_filter() {
+        assert!(matches(json!("apple"), "in", json!(["pear", "apple"]), true));
+        assert!(!matches(json!("Apple"), "in", json!(["pear", "apple"]), true));
+        assert!(matches(json!("Apple"), "in", json!(["pear", "apple"]), false));
+        assert!(matches(json!(2), "in", json!([1, 2]), true));
+        assert!(!matches(json!("apple"), "in", json!("apple"), true));
+        assert!(!matches(json!("apple"), "in", json!([]), true));
+    }
+
+    #[test]
+    fn between_filter() {
+        assert!(matches(json!(3), "between", json!([1, 3]), true));
+        assert!(matches(json!(1), "between", json!([1, 3]), true));
+        assert!(!matches(json!(4), "between", json!([1, 3]), true));
+        assert!(matches(json!("m"), "between", json!(["a", "z"]), true));
+        assert!(!matches(json!("M"), "between", json!(["a", "z"]), true));
+        assert!(matches(json!("M"), "between", json!(["a", "z"]), false));
+    }
+
+    #[test]
+    fn malformed_operands_match_nothing() {
+        assert!(!matches(json!(4), "between", json!([1]), true));
+        assert!(!matches(json!(4), "between", json!([1, 2, 3]), true));
+        assert!(!matches(json!(4), "between", json!("1..5"), true));
+        assert!(!matches(json!(4), "between", json!([1, "z"]), true));
+        assert!(!matches(json!("a"), "in", json!({"a": 1}), true));
+        assert!(!matches(json!("a"), "no_such_operator", json!("a"), true));
+    }
+
+    #[test]
+    fn sorting_by_two_and_three_keys() {
+        let data = rows(json!([
+            {"c": "b", "p": 1, "n": 1},
+            {"c": "a", "p": 2, "n": 2},
+            {"c": "a", "p": 3, "n": 3},
+            {"c": "b", "p": 1, "n": 4},
+            {"c": "a", "p": 2, "n": 5},
+        ]));
+        let indices: Vec<usize> = (0..data.len()).collect();
+
+        // Ties on both keys keep their original order
+        assert_eq!(
+            apply_sorting(&indices, &data, &[sort("c", "asc"), sort("p", "desc")]),
+            vec![2, 1, 4, 0, 3]
+        );
+        assert_eq!(
+            apply_sorting(
+                &indices,
+                &data,
+                &[sort("c", "asc"), sort("p", "desc"), sort("n", "desc")]
+            ),
+            vec![2, 4, 1, 3, 0]
+        );
+        assert_eq!(apply_sorting(&indices, &data, &[]), indices);
+    }
+
+    #[test]
+    fn us_formatted_numbers() {
+        let _globals = globals();
+        assert_eq!(parse_formatted_number("1,234.56"), Some(1234.56));
+        assert_eq!(parse_formatted_number("1,234,567"), Some(1234567.0));
+        assert_eq!(parse_formatted_number("$1,000"), Some(1000.0));
+        assert_eq!(parse_formatted_number("-$1,000.5"), Some(-1000.5));
+        assert_eq!(parse_formatted_number("$-2"), Some(-2.0));
+        assert_eq!(infer_value_type("$1,000"), json!(1000));
+    }
+
+    #[test]
+    fn european_formatted_numbers() {
+        let _globals = globals();
+        assert_eq!(parse_formatted_number("1.234,56"), Some(1234.56));
+        assert_eq!(parse_formatted_number("1.234.567,8 €"), Some(1234567.8));
+        assert_eq!(parse_formatted_number("12,5"), Some(12.5));
+        assert_eq!(infer_value_type("1.234,56"), json!(1234.56));
+    }
+
+    #[test]
+    fn percentages_are_scaled() {
+        let _globals = globals();
+        assert_eq!(parse_formatted_number("45%"), Some(0.45));
+        assert_eq!(parse_formatted_number("12,5 %"), Some(0.125));
+        assert_eq!(infer_value_type("45%"), json!(0.45));
+    }
+
+    #[test]
+    fn text_that_only_looks_numeric() {
+        let _globals = globals();
+        for text in [
+            "1.2.3",
+            "192.168.1.10",
+            "1,2,3",
+            "abc",
+            "$",
+            "12,34,5",
+            "2024-01-02",
+            "1,23.4",
+            ",5",
+            "1.234,5,6",
+        ] {
+            assert_eq!(parse_formatted_number(text), None, "{}", text);
+        }
+    }
+
+    fn zip_and_hex_rows() -> Vec<HashMap<String, serde_json::Value>> {
+        let rows = vec![
+            vec!["02134".to_string(), "0x1F".to_string()],
+            vec!["10001".to_string(), "0xff".to_string()],
+        ];
+        infer_data_types(&rows, &["zip".to_string(), "hex".to_string()], None)
+    }
+
+    #[test]
+    fn leading_zeros_and_overflowing_integers_stay_strings() {
+        let _globals = globals();
+        assert_eq!(infer_value_type("007"), json!("007"));
+        assert_eq!(infer_value_type("-0123"), json!("-0123"));
+        assert_eq!(infer_value_type("0"), json!(0));
+        assert_eq!(infer_value_type("0.5"), json!(0.5));
+        assert_eq!(infer_value_type("99999999999999999999"), json!("99999999999999999999"));
+        assert_eq!(infer_value_type("9223372036854775807"), json!(i64::MAX));
+        let zips = ["02134".to_string(), "10001".to_string(), "00501".to_string()];
+        assert_eq!(crate::analysis::infer_data_type(&zips), "string");
+
+        let typed = zip_and_hex_rows();
+        assert_eq!(typed[0]["zip"], json!("02134"));
+        assert_eq!(typed[0]["hex"], json!("0x1F"));
+    }
+
+    #[test]
+    fn numeric_inference_options_change_the_rules() {
+        let _globals = change_globals();
+        set_numeric_inference_options(NumericInferenceOptions {
+            keep_leading_zeros: false,
+            big_integers_as_float: true,
+            recognize_hex: true,
+        })
+        .unwrap();
+        let leading_zeros = infer_value_type("007");
+        let big_integer = infer_value_type("99999999999999999999");
+        let typed = zip_and_hex_rows();
+        let mixed_hex = crate::analysis::infer_data_type(&["0x1".to_string(), "abc".to_string()]);
+        set_numeric_inference_options(NumericInferenceOptions::default()).unwrap();
+
+        assert_eq!(leading_zeros, json!(7));
+        assert_eq!(big_integer, json!(1e20));
+        assert_eq!(typed[0]["zip"], json!(2134));
+        // Hex is only recognised when the whole column is hex
+        assert_eq!(typed[0]["hex"], json!(31));
+        assert_eq!(typed[1]["hex"], json!(255));
+        assert_eq!(mixed_hex, "string");
+        assert!(get_numeric_inference_options().keep_leading_zeros);
+    }
+
+    #[test]
+    fn range_filters_parse_numeric_strings() {
+        assert!(matches(json!("007"), "greater_than", json!(5), false));
+        assert!(matches(json!("007"), "greater_than", json!("5"), false));
+        assert!(matches(json!(7), "less_than_or_equal", json!("7.0"), false));
+        assert!(!matches(json!("n/a"), "greater_than", json!(5), false));
+        assert!(!matches(json!("007"), "less_than", json!("abc"), false));
     }
 }
@@ -4,19 +4,38 @@
 //! application lifetime. All Tauri command wrappers call these free functions
 //! instead of managing state themselves.
 
-use crate::manager::DatabaseManager;
+use crate::manager::{DatabaseManager, now_timestamp};
 use crate::models::*;
-use chrono::Utc;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 // Global singleton
 
 static SURREAL_DB: Mutex<Option<Arc<DatabaseManager>>> = Mutex::new(None);
 
+/// File the embedded database is kept in, relative to the working directory.
+pub const LOCAL_DATABASE_PATH: &str = "surrealdb_database.db";
+
+// Environment variables that point start-up at a remote server instead of the local file
+const REMOTE_URL_VAR: &str = "SURREALDB_URL";
+const REMOTE_USER_VAR: &str = "SURREALDB_USER";
+const REMOTE_PASS_VAR: &str = "SURREALDB_PASS";
+const REMOTE_NS_VAR: &str = "SURREALDB_NS";
+const REMOTE_DB_VAR: &str = "SURREALDB_DB";
+
+/// Lock the global slot, recovering the guard if a previous holder panicked.
+///
+/// The slot only ever holds an `Option<Arc<_>>` that is swapped in one step, so
+/// a poisoned lock never leaves it half-written and is safe to keep using.
+fn db_slot() -> MutexGuard<'static, Option<Arc<DatabaseManager>>> {
+    SURREAL_DB.lock().unwrap_or_else(|poisoned| {
+        eprintln!("⚠️ SurrealDB lock was poisoned by a panic; recovering");
+        SURREAL_DB.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
 fn get_db() -> Result<Arc<DatabaseManager>, String> {
-    SURREAL_DB
-        .lock()
-        .unwrap()
+    db_slot()
         .as_ref()
         .cloned()
         .ok_or_else(|| "SurrealDB not initialised. Call initialize() first.".to_string())
@@ -27,7 +46,7 @@ fn get_db() -> Result<Arc<DatabaseManager>, String> {
 pub async fn initialize(db_path: &str) -> Result<(), String> {
     match DatabaseManager::new(db_path).await {
         Ok(db) => {
-            *SURREAL_DB.lock().unwrap() = Some(Arc::new(db));
+            *db_slot() = Some(Arc::new(db));
             println!("🗄️ SurrealDB initialised successfully");
             Ok(())
         }
@@ -39,6 +58,69 @@ pub async fn initialize(db_path: &str) -> Result<(), String> {
     }
 }
 
+/// Open the database chosen by the environment, for start-up.
+///
+/// When `SURREALDB_URL` is set this connects to that server with `SURREALDB_USER`,
+/// `SURREALDB_PASS`, `SURREALDB_NS` (default `data_science_app`), and `SURREALDB_DB`
+/// (default `main`). Otherwise, or if the remote connection fails, the local
+/// database at `LOCAL_DATABASE_PATH` is opened so the app still starts.
+pub async fn initialize_from_env() -> Result<(), String> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    if let Some(url) = var(REMOTE_URL_VAR) {
+        let remote = initialize_remote(
+            &url,
+            &var(REMOTE_USER_VAR).unwrap_or_default(),
+            &var(REMOTE_PASS_VAR).unwrap_or_default(),
+            &var(REMOTE_NS_VAR).unwrap_or_else(|| "data_science_app".to_string()),
+            &var(REMOTE_DB_VAR).unwrap_or_else(|| "main".to_string()),
+        )
+        .await;
+        if remote.is_ok() {
+            return remote;
+        }
+        eprintln!("⚠️ Falling back to the local database at {}", LOCAL_DATABASE_PATH);
+    }
+    initialize(LOCAL_DATABASE_PATH).await
+}
+
+/// Replace the global instance with the local database at `LOCAL_DATABASE_PATH`,
+/// e.g. to switch back after `initialize_remote`.
+pub async fn initialize_local() -> Result<(), String> {
+    initialize(LOCAL_DATABASE_PATH).await
+}
+
+/// Replace the global instance with a connection to a remote SurrealDB server.
+///
+/// Requires the `remote` feature; without it this always returns an error.
+pub async fn initialize_remote(
+    url: &str,
+    username: &str,
+    password: &str,
+    namespace: &str,
+    database: &str,
+) -> Result<(), String> {
+    #[cfg(feature = "remote")]
+    {
+        match DatabaseManager::new_remote(url, username, password, namespace, database).await {
+            Ok(db) => {
+                *db_slot() = Some(Arc::new(db));
+                println!("🗄️ Connected to remote SurrealDB at {}", url);
+                Ok(())
+            }
+            Err(e) => {
+                let msg = format!("Failed to connect to remote SurrealDB: {}", e);
+                eprintln!("❌ {}", msg);
+                Err(msg)
+            }
+        }
+    }
+    #[cfg(not(feature = "remote"))]
+    {
+        let _ = (url, username, password, namespace, database);
+        Err("Remote SurrealDB support is not enabled in this build (enable the `remote` feature)".to_string())
+    }
+}
+
 pub async fn verify_connection() -> Result<(), String> {
     let db = get_db()?;
     db.get_stats()
@@ -54,14 +136,16 @@ pub async fn create_safety_record(
     data: serde_json::Value,
     severity: String,
 ) -> Result<String, String> {
+    let severity: Severity = severity.parse()?;
     let db = get_db()?;
+    let now = now_timestamp();
     let record = SafetyRecord {
         id: None,
         record_type,
-        timestamp: Utc::now().to_rfc3339(),
+        timestamp: now.clone(),
         data,
-        severity,
-        created_at: Utc::now().to_rfc3339(),
+        severity: severity.to_string(),
+        created_at: now,
     };
     db.insert_safety_record(&record)
         .await
@@ -69,6 +153,45 @@ pub async fn create_safety_record(
         .map_err(|e| format!("Failed to create safety record: {}", e))
 }
 
+/// Create many safety records at once from a JSON array of
+/// `{ "record_type", "data", "severity" }` objects.
+pub async fn create_safety_records_batch(records: serde_json::Value) -> Result<serde_json::Value, String> {
+    let db = get_db()?;
+    let items = records
+        .as_array()
+        .ok_or_else(|| "Expected a JSON array of safety records".to_string())?;
+
+    let now = now_timestamp();
+    let records = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let field = |name: &str| {
+                item.get(name)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("Record {} is missing string field '{}'", i, name))
+            };
+            Ok(SafetyRecord {
+                id: None,
+                record_type: field("record_type")?,
+                timestamp: now.clone(),
+                data: item.get("data").cloned().unwrap_or(serde_json::Value::Null),
+                severity: field("severity")?
+                    .parse::<Severity>()
+                    .map_err(|e| format!("Record {}: {}", i, e))?
+                    .to_string(),
+                created_at: now.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    db.insert_safety_records_batch(&records)
+        .await
+        .map(|ids| serde_json::json!({ "success": true, "count": ids.len(), "ids": ids }))
+        .map_err(|e| format!("Failed to create safety records: {}", e))
+}
+
 pub async fn get_safety_records(limit: Option<i32>) -> Result<serde_json::Value, String> {
     let db = get_db()?;
     db.get_safety_records(limit)
@@ -78,6 +201,7 @@ pub async fn get_safety_records(limit: Option<i32>) -> Result<serde_json::Value,
 }
 
 pub async fn get_safety_records_by_severity(severity: String) -> Result<serde_json::Value, String> {
+    let severity = severity.parse::<Severity>()?.to_string();
     let db = get_db()?;
     db.get_safety_records_by_severity(&severity)
         .await
@@ -85,6 +209,49 @@ pub async fn get_safety_records_by_severity(severity: String) -> Result<serde_js
         .map_err(|e| format!("Failed to get safety records by severity: {}", e))
 }
 
+/// A page of safety records, newest first; `severity` is validated like in `create_safety_record`.
+pub async fn get_safety_records_paginated(
+    page: i32,
+    limit: i32,
+    severity: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let severity = severity.map(|s| s.parse::<Severity>()).transpose()?;
+    get_db()?
+        .get_safety_records_paginated(page, limit, severity)
+        .await
+        .map_err(|e| format!("Failed to get paginated safety records: {}", e))
+}
+
+/// A page of safety records matching every filter set in `query`, a `SafetyRecordQuery` as JSON.
+pub async fn query_safety_records(query: serde_json::Value) -> Result<serde_json::Value, String> {
+    let query: SafetyRecordQuery =
+        serde_json::from_value(query).map_err(|e| format!("Invalid safety record query: {}", e))?;
+    get_db()?
+        .query_safety_records(&query)
+        .await
+        .map_err(|e| format!("Failed to query safety records: {}", e))
+}
+
+pub async fn get_safety_records_between(
+    start: String,
+    end: String,
+    limit: Option<i32>,
+) -> Result<serde_json::Value, String> {
+    let db = get_db()?;
+    db.get_safety_records_between(&start, &end, limit)
+        .await
+        .map(|r| serde_json::json!({ "success": true, "count": r.len(), "start": start, "end": end, "records": r }))
+        .map_err(|e| format!("Failed to get safety records between dates: {}", e))
+}
+
+pub async fn search_safety_records(term: String, limit: Option<i32>) -> Result<serde_json::Value, String> {
+    let db = get_db()?;
+    db.search_safety_records(&term, limit)
+        .await
+        .map(|r| serde_json::json!({ "success": true, "count": r.len(), "records": r }))
+        .map_err(|e| format!("Failed to search safety records: {}", e))
+}
+
 pub async fn get_database_stats() -> Result<serde_json::Value, String> {
     get_db()?
         .get_stats()
@@ -92,14 +259,66 @@ pub async fn get_database_stats() -> Result<serde_json::Value, String> {
         .map_err(|e| format!("Failed to get database stats: {}", e))
 }
 
-pub async fn reset_database() -> Result<String, String> {
+/// Run a read-only SurrealQL query with `params` bound as `$name` variables.
+pub async fn query_readonly(surql: String, params: Option<serde_json::Value>) -> Result<serde_json::Value, String> {
+    get_db()?
+        .query_readonly(&surql, params.unwrap_or(serde_json::Value::Null))
+        .await
+        .map(|rows| serde_json::json!({ "success": true, "count": rows.as_array().map_or(0, Vec::len), "results": rows }))
+        .map_err(|e| format!("Failed to run read-only query: {}", e))
+}
+
+pub async fn reset_database(confirm: bool) -> Result<String, String> {
+    if !confirm {
+        return Err("Database reset not confirmed. Pass confirm = true to delete all records.".to_string());
+    }
     get_db()?
         .reset_database()
         .await
-        .map(|_| "Database reset successfully.".to_string())
+        .map(|counts| {
+            let total: u64 = counts.iter().map(|(_, n)| n).sum();
+            format!("Database reset successfully. Removed {} records.", total)
+        })
         .map_err(|e| format!("Failed to reset database: {}", e))
 }
 
+/// Write every table to a JSON archive at `out_path`.
+pub async fn backup_database(out_path: String) -> Result<serde_json::Value, String> {
+    get_db()?
+        .export_database(&out_path)
+        .await
+        .map(|counts| {
+            let total: u64 = counts.iter().map(|(_, n)| n).sum();
+            let tables: serde_json::Map<String, serde_json::Value> = counts
+                .into_iter()
+                .map(|(table, n)| (table, serde_json::json!(n)))
+                .collect();
+            serde_json::json!({ "success": true, "path": out_path, "tables": tables, "total_records": total })
+        })
+        .map_err(|e| format!("Failed to back up database: {}", e))
+}
+
+/// Restore the tables in a backup archive, replacing their current contents.
+///
+/// `success` is `false` if any table failed; those tables are listed with their error and
+/// keep their previous contents.
+pub async fn restore_database(in_path: String) -> Result<serde_json::Value, String> {
+    get_db()?
+        .import_database(&in_path)
+        .await
+        .map(|tables| {
+            let failed = tables.iter().filter(|t| t.error.is_some()).count();
+            let total: u64 = tables.iter().map(|t| t.restored).sum();
+            serde_json::json!({
+                "success": failed == 0,
+                "failed_tables": failed,
+                "total_records": total,
+                "tables": tables
+            })
+        })
+        .map_err(|e| format!("Failed to restore database: {}", e))
+}
+
 pub async fn cleanup_old_records(days: i32) -> Result<String, String> {
     get_db()?
         .cleanup_old_records(days)
@@ -110,17 +329,23 @@ pub async fn cleanup_old_records(days: i32) -> Result<String, String> {
 
 // Legacy Configuration
 
-pub async fn save_configuration(name: String, content: serde_json::Value) -> Result<String, String> {
+pub async fn save_configuration(name: String, content: serde_json::Value) -> Result<serde_json::Value, String> {
     let db = get_db()?;
     let cfg = Configuration {
         id: None,
         name,
         content: content.to_string(),
-        updated_at: Utc::now().to_rfc3339(),
+        updated_at: now_timestamp(),
     };
     db.save_configuration(&cfg)
         .await
-        .map(|id| format!("Configuration saved with ID: {}", id))
+        .map(|(created, id)| {
+            serde_json::json!({
+                "success": true,
+                "action": if created { "insert" } else { "update" },
+                "id": id
+            })
+        })
         .map_err(|e| format!("Failed to save configuration: {}", e))
 }
 
@@ -143,7 +368,8 @@ pub async fn save_stream_config(config_name: String, config_content: String) ->
             id: None,
             config_name,
             config_content,
-            created_at: Utc::now().to_rfc3339(),
+            created_at: now_timestamp(),
+            updated_at: None,
         })
         .await
         .map_err(|e| format!("Failed to save stream config: {}", e))
@@ -156,13 +382,37 @@ pub async fn get_stream_configs() -> Result<Vec<StreamConfig>, String> {
         .map_err(|e| format!("Failed to get stream configs: {}", e))
 }
 
+pub async fn update_stream_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    get_db()?
+        .update_stream_config(
+            &id,
+            &StreamConfig {
+                id: None,
+                config_name,
+                config_content,
+                created_at: now_timestamp(),
+                updated_at: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to update stream config: {}", e))
+}
+
+pub async fn delete_stream_config(id: String) -> Result<bool, String> {
+    get_db()?
+        .delete_stream_config(&id)
+        .await
+        .map_err(|e| format!("Failed to delete stream config: {}", e))
+}
+
 pub async fn save_ogg_config(config_name: String, config_content: String) -> Result<String, String> {
     get_db()?
         .save_ogg_config(&OggConfig {
             id: None,
             config_name,
             config_content,
-            created_at: Utc::now().to_rfc3339(),
+            created_at: now_timestamp(),
+            updated_at: None,
         })
         .await
         .map_err(|e| format!("Failed to save OGG config: {}", e))
@@ -175,13 +425,37 @@ pub async fn get_ogg_configs() -> Result<Vec<OggConfig>, String> {
         .map_err(|e| format!("Failed to get OGG configs: {}", e))
 }
 
+pub async fn update_ogg_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    get_db()?
+        .update_ogg_config(
+            &id,
+            &OggConfig {
+                id: None,
+                config_name,
+                config_content,
+                created_at: now_timestamp(),
+                updated_at: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to update OGG config: {}", e))
+}
+
+pub async fn delete_ogg_config(id: String) -> Result<bool, String> {
+    get_db()?
+        .delete_ogg_config(&id)
+        .await
+        .map_err(|e| format!("Failed to delete OGG config: {}", e))
+}
+
 pub async fn save_org_config(config_name: String, config_content: String) -> Result<String, String> {
     get_db()?
         .save_org_config(&OrgConfig {
             id: None,
             config_name,
             config_content,
-            created_at: Utc::now().to_rfc3339(),
+            created_at: now_timestamp(),
+            updated_at: None,
         })
         .await
         .map_err(|e| format!("Failed to save ORG config: {}", e))
@@ -194,13 +468,37 @@ pub async fn get_org_configs() -> Result<Vec<OrgConfig>, String> {
         .map_err(|e| format!("Failed to get ORG configs: {}", e))
 }
 
+pub async fn update_org_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    get_db()?
+        .update_org_config(
+            &id,
+            &OrgConfig {
+                id: None,
+                config_name,
+                config_content,
+                created_at: now_timestamp(),
+                updated_at: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to update ORG config: {}", e))
+}
+
+pub async fn delete_org_config(id: String) -> Result<bool, String> {
+    get_db()?
+        .delete_org_config(&id)
+        .await
+        .map_err(|e| format!("Failed to delete ORG config: {}", e))
+}
+
 pub async fn save_orsg_config(config_name: String, config_content: String) -> Result<String, String> {
     get_db()?
         .save_orsg_config(&OrsgConfig {
             id: None,
             config_name,
             config_content,
-            created_at: Utc::now().to_rfc3339(),
+            created_at: now_timestamp(),
+            updated_at: None,
         })
         .await
         .map_err(|e| format!("Failed to save ORSG config: {}", e))
@@ -213,13 +511,37 @@ pub async fn get_orsg_configs() -> Result<Vec<OrsgConfig>, String> {
         .map_err(|e| format!("Failed to get ORSG configs: {}", e))
 }
 
+pub async fn update_orsg_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    get_db()?
+        .update_orsg_config(
+            &id,
+            &OrsgConfig {
+                id: None,
+                config_name,
+                config_content,
+                created_at: now_timestamp(),
+                updated_at: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to update ORSG config: {}", e))
+}
+
+pub async fn delete_orsg_config(id: String) -> Result<bool, String> {
+    get_db()?
+        .delete_orsg_config(&id)
+        .await
+        .map_err(|e| format!("Failed to delete ORSG config: {}", e))
+}
+
 pub async fn save_event_config(config_name: String, config_content: String) -> Result<String, String> {
     get_db()?
         .save_event_config(&EventConfig {
             id: None,
             config_name,
             config_content,
-            created_at: Utc::now().to_rfc3339(),
+            created_at: now_timestamp(),
+            updated_at: None,
         })
         .await
         .map_err(|e| format!("Failed to save Event config: {}", e))
@@ -232,6 +554,29 @@ pub async fn get_event_configs() -> Result<Vec<EventConfig>, String> {
         .map_err(|e| format!("Failed to get Event configs: {}", e))
 }
 
+pub async fn update_event_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    get_db()?
+        .update_event_config(
+            &id,
+            &EventConfig {
+                id: None,
+                config_name,
+                config_content,
+                created_at: now_timestamp(),
+                updated_at: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to update Event config: {}", e))
+}
+
+pub async fn delete_event_config(id: String) -> Result<bool, String> {
+    get_db()?
+        .delete_event_config(&id)
+        .await
+        .map_err(|e| format!("Failed to delete Event config: {}", e))
+}
+
 pub async fn save_config_recipe(
     recipe_name: String,
     stream_config_id: Option<i64>,
@@ -253,7 +598,8 @@ pub async fn save_config_recipe(
             id: None,
             recipe_name,
             recipe_content,
-            created_at: Utc::now().to_rfc3339(),
+            created_at: now_timestamp(),
+            updated_at: None,
         })
         .await
         .map_err(|e| format!("Failed to save config recipe: {}", e))
@@ -266,6 +612,82 @@ pub async fn get_config_recipes() -> Result<Vec<ConfigRecipe>, String> {
         .map_err(|e| format!("Failed to get config recipes: {}", e))
 }
 
+pub async fn update_config_recipe(
+    id: String,
+    recipe_name: String,
+    stream_config_id: Option<i64>,
+    ogg_config_id: Option<i64>,
+    org_config_id: Option<i64>,
+    orsg_config_id: Option<i64>,
+    event_config_id: Option<i64>,
+) -> Result<bool, String> {
+    let recipe_content = serde_json::json!({
+        "stream_config_id": stream_config_id,
+        "ogg_config_id": ogg_config_id,
+        "org_config_id": org_config_id,
+        "orsg_config_id": orsg_config_id,
+        "event_config_id": event_config_id,
+    })
+    .to_string();
+    get_db()?
+        .update_config_recipe(
+            &id,
+            &ConfigRecipe {
+                id: None,
+                recipe_name,
+                recipe_content,
+                created_at: now_timestamp(),
+                updated_at: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to update config recipe: {}", e))
+}
+
+pub async fn delete_config_recipe(id: String) -> Result<bool, String> {
+    get_db()?
+        .delete_config_recipe(&id)
+        .await
+        .map_err(|e| format!("Failed to delete config recipe: {}", e))
+}
+
+// Loaded Datasets
+
+/// Record that a CSV file was loaded into the data store so it can be offered as "recently opened".
+pub async fn record_loaded_dataset(
+    file_id: String,
+    file_path: String,
+    headers: Vec<String>,
+    metadata: serde_json::Value,
+) -> Result<String, String> {
+    let dataset = LoadedDataset {
+        id: None,
+        file_id,
+        file_path,
+        headers,
+        metadata,
+        created_at: now_timestamp(),
+    };
+    get_db()?
+        .save_loaded_dataset(&dataset)
+        .await
+        .map_err(|e| format!("Failed to record loaded dataset: {}", e))
+}
+
+pub async fn list_recent_datasets(limit: Option<usize>) -> Result<Vec<LoadedDataset>, String> {
+    get_db()?
+        .get_recent_loaded_datasets(limit.unwrap_or(20))
+        .await
+        .map_err(|e| format!("Failed to list recent datasets: {}", e))
+}
+
+pub async fn delete_loaded_dataset(id: String) -> Result<bool, String> {
+    get_db()?
+        .delete_loaded_dataset(&id)
+        .await
+        .map_err(|e| format!("Failed to delete loaded dataset: {}", e))
+}
+
 // Generic CRUD (implemented via DatabaseManager generic methods)
 
 pub async fn get_config(table: String, id: Option<i64>, name: Option<String>) -> Result<serde_json::Value, String> {
@@ -283,15 +705,31 @@ pub async fn save_config(table: String, name: String, content: String) -> Result
     get_db()?
         .save_generic(&table, &name, &content)
         .await
-        .map(|id| serde_json::json!({ "success": true, "id": id }))
+        .map(|record| serde_json::json!({ "success": true, "id": record.get("id").cloned(), "record": record }))
         .map_err(|e| format!("Failed to save config: {}", e))
 }
 
+/// Save a config under `name`, updating the existing record with that name if there is one.
+pub async fn upsert_config(table: String, name: String, content: String) -> Result<serde_json::Value, String> {
+    get_db()?
+        .upsert_generic(&table, &name, &content)
+        .await
+        .map(|(created, record)| {
+            serde_json::json!({
+                "success": true,
+                "action": if created { "insert" } else { "update" },
+                "id": record.get("id").cloned(),
+                "record": record
+            })
+        })
+        .map_err(|e| format!("Failed to upsert config: {}", e))
+}
+
 pub async fn update_config(table: String, id: i64, field: String, value: String) -> Result<serde_json::Value, String> {
     get_db()?
         .update_generic_field(&table, id, &field, &value)
         .await
-        .map(|ok| serde_json::json!({ "success": ok }))
+        .map(|updated| affected_result("updated", updated))
         .map_err(|e| format!("Failed to update config: {}", e))
 }
 
@@ -299,10 +737,20 @@ pub async fn delete_config(table: String, id: Option<i64>, name: Option<String>)
     get_db()?
         .delete_generic(&table, id, name.as_deref())
         .await
-        .map(|ok| serde_json::json!({ "success": ok }))
+        .map(|deleted| affected_result("deleted", deleted))
         .map_err(|e| format!("Failed to delete config: {}", e))
 }
 
+/// `{ success, <key>: count }`, reported like a missing record when nothing was affected.
+fn affected_result(key: &str, count: u64) -> serde_json::Value {
+    let mut result = serde_json::json!({ "success": count > 0 });
+    result[key] = count.into();
+    if count == 0 {
+        result["message"] = "Record not found".into();
+    }
+    result
+}
+
 pub async fn search_configs(table: String, query: String, limit: Option<i32>) -> Result<serde_json::Value, String> {
     get_db()?
         .search_generic(&table, &query, limit)
@@ -331,3 +779,47 @@ pub async fn get_configs_paginated(
         .await
         .map_err(|e| format!("Failed to get paginated configs: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_affected_reads_as_not_found() {
+        assert_eq!(
+            affected_result("deleted", 0),
+            serde_json::json!({ "success": false, "deleted": 0, "message": "Record not found" })
+        );
+        assert_eq!(
+            affected_result("updated", 2),
+            serde_json::json!({ "success": true, "updated": 2 })
+        );
+    }
+
+    #[test]
+    fn poisoned_lock_is_recovered() {
+        let _ = std::thread::spawn(|| {
+            let _slot = SURREAL_DB.lock().unwrap();
+            panic!("poison the global slot");
+        })
+        .join();
+        assert!(SURREAL_DB.is_poisoned());
+
+        // Calls keep failing cleanly instead of panicking on the poisoned lock
+        assert!(get_db().unwrap_err().contains("not initialised"));
+        assert!(!SURREAL_DB.is_poisoned());
+        assert!(get_db().is_err());
+    }
+
+    #[tokio::test]
+    async fn unknown_severities_are_rejected_before_storing() {
+        let created = create_safety_record("inspection".into(), serde_json::json!({}), "hgih".into()).await;
+        assert!(created.unwrap_err().starts_with("Unknown severity 'hgih'"));
+        assert!(
+            get_safety_records_paginated(1, 10, Some("urgent".into()))
+                .await
+                .unwrap_err()
+                .starts_with("Unknown severity")
+        );
+    }
+}
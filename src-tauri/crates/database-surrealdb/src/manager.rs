@@ -1,21 +1,373 @@
 use crate::models::*;
 use anyhow::Result;
 use surrealdb::Surreal;
-use surrealdb::engine::local::{Db, SurrealKv};
+use surrealdb::engine::any::{self, Any};
+
+/// Generates `update_*` / `delete_*` methods for a typed config table.
+macro_rules! typed_config_mutations {
+    ($update:ident, $delete:ident, $ty:ty, $table:literal) => {
+        pub async fn $update(&self, id: &str, config: &$ty) -> Result<bool> {
+            self.update_typed($table, id, config).await
+        }
+
+        pub async fn $delete(&self, id: &str) -> Result<bool> {
+            self.delete_typed($table, id).await
+        }
+    };
+}
+
+/// Every table managed by the application, used for stats and maintenance.
+pub const TABLES: [&str; 9] = [
+    "safety_records",
+    "configurations",
+    "stream_configs",
+    "ogg_configs",
+    "org_configs",
+    "orsg_configs",
+    "event_configs",
+    "config_recipes",
+    "loaded_datasets",
+];
+
+/// Tables holding named configs, which get a `config_name` index and timestamp fields.
+pub const CONFIG_TABLES: [&str; 5] = [
+    "stream_configs",
+    "ogg_configs",
+    "org_configs",
+    "orsg_configs",
+    "event_configs",
+];
+
+/// Identifies archives written by `DatabaseManager::export_database`.
+const BACKUP_FORMAT: &str = "data-science-app-backup";
+const BACKUP_VERSION: u64 = 1;
+/// Field holding each record's original key inside a backup archive.
+const BACKUP_KEY_FIELD: &str = "backup_key";
+
+/// Reject anything but a non-empty `[A-Za-z0-9_]` identifier before it reaches a query.
+///
+/// Used for table names, field names, and sort columns supplied by callers.
+pub fn validate_identifier(identifier: &str) -> Result<()> {
+    if identifier.is_empty() || !identifier.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        anyhow::bail!(
+            "Invalid identifier '{}': only letters, digits and underscores are allowed",
+            identifier
+        );
+    }
+    Ok(())
+}
+
+/// Fields holding timestamps; caller-supplied values for them are normalised before storing.
+const TIMESTAMP_FIELDS: [&str; 3] = ["timestamp", "created_at", "updated_at"];
+
+/// Render `time` as a stored timestamp: UTC RFC3339 with microseconds and a `+00:00` offset.
+///
+/// Every write uses this fixed-width form, so comparing timestamp fields as strings
+/// matches chronological order.
+pub fn format_timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Micros, false)
+}
+
+/// The current time as a stored timestamp.
+pub fn now_timestamp() -> String {
+    format_timestamp(chrono::Utc::now())
+}
+
+/// Parse an RFC3339 timestamp in any offset and convert it to UTC.
+pub fn parse_timestamp(value: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .map_err(|e| anyhow::anyhow!("Invalid timestamp '{}': {}", value, e))
+}
+
+/// Rewrite a caller-supplied RFC3339 timestamp in the stored form, rejecting anything unparseable.
+pub fn normalize_timestamp(value: &str) -> Result<String> {
+    parse_timestamp(value).map(format_timestamp)
+}
+
+/// A requested page, clamped to `page >= 1` and `limit >= 1`, shared by the paginated queries.
+struct PageWindow {
+    page: i32,
+    limit: i32,
+}
+
+impl PageWindow {
+    fn new(page: i32, limit: i32) -> Self {
+        Self {
+            page: page.max(1),
+            limit: limit.max(1),
+        }
+    }
+
+    /// Records to skip before this page.
+    fn start(&self) -> i32 {
+        (self.page - 1) * self.limit
+    }
+
+    /// The `{ success, data, pagination }` envelope for this page of a `total`-record result.
+    fn envelope<T: serde::Serialize>(&self, data: &[T], total: i64) -> serde_json::Value {
+        let total_pages = (total + self.limit as i64 - 1) / self.limit as i64;
+        serde_json::json!({
+            "success": true,
+            "data": data,
+            "pagination": {
+                "page": self.page,
+                "limit": self.limit,
+                "total": total,
+                "total_pages": total_pages,
+                "has_next": (self.page as i64) < total_pages,
+                "has_prev": self.page > 1
+            }
+        })
+    }
+}
+
+/// Fields `query_safety_records` can sort by.
+const SAFETY_RECORD_SORT_FIELDS: [&str; 4] = ["timestamp", "created_at", "severity", "record_type"];
+
+/// The `WHERE` condition and bound parameters for the filters set in `query`, or `None` without any.
+fn safety_record_filter(
+    query: &SafetyRecordQuery,
+) -> Result<(Option<String>, serde_json::Map<String, serde_json::Value>)> {
+    let mut conditions = Vec::new();
+    let mut params = serde_json::Map::new();
+    if let Some(record_type) = &query.record_type {
+        conditions.push("record_type = $record_type");
+        params.insert("record_type".to_string(), record_type.as_str().into());
+    }
+    if let Some(severity) = query.severity {
+        conditions.push("severity = $severity");
+        params.insert("severity".to_string(), severity.as_str().into());
+    }
+    let bound = |label: &str, value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|v| parse_timestamp(v).map_err(|e| anyhow::anyhow!("Invalid {} bound: {}", label, e)))
+            .transpose()
+    };
+    let start = bound("start", &query.start)?;
+    let end = bound("end", &query.end)?;
+    if let (Some(start), Some(end)) = (start, end)
+        && start > end
+    {
+        anyhow::bail!(
+            "start ({}) must not be after end ({})",
+            start.to_rfc3339(),
+            end.to_rfc3339()
+        );
+    }
+    // Stored timestamps use `format_timestamp`, so normalized bounds compare chronologically
+    if let Some(start) = start {
+        conditions.push("timestamp >= $range_start");
+        params.insert("range_start".to_string(), format_timestamp(start).into());
+    }
+    if let Some(end) = end {
+        conditions.push("timestamp <= $range_end");
+        params.insert("range_end".to_string(), format_timestamp(end).into());
+    }
+    if let Some(term) = &query.data_contains {
+        conditions.push("string::contains(string::lowercase(type::string(data)), $data_term)");
+        params.insert("data_term".to_string(), term.to_lowercase().into());
+    }
+    let filter = (!conditions.is_empty()).then(|| conditions.join(" AND "));
+    Ok((filter, params))
+}
+
+/// The count from a `SELECT count() ... GROUP ALL` row, which is absent when nothing matched.
+fn group_count(row: Option<serde_json::Value>) -> i64 {
+    row.and_then(|v| v.get("count").and_then(|c| c.as_i64())).unwrap_or(0)
+}
+
+/// SurrealQL keywords that can change data or schema; `query_readonly` refuses any query using them.
+const MUTATING_KEYWORDS: [&str; 13] = [
+    "INSERT", "UPDATE", "UPSERT", "DELETE", "CREATE", "RELATE", "REMOVE", "DEFINE", "ALTER", "REBUILD", "KILL", "LIVE",
+    "ACCESS",
+];
+
+/// Reject a SurrealQL query if any keyword outside string literals and comments is a mutating one.
+///
+/// This is deliberately conservative: a field that happens to be called `update` is refused too.
+pub fn ensure_read_only(surql: &str) -> Result<()> {
+    let mut chars = surql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                // Skip to the closing quote, honouring backslash escapes
+                while let Some(next) = chars.next() {
+                    if next == '\\' {
+                        chars.next();
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            '⟨' => {
+                for next in chars.by_ref() {
+                    if next == '⟩' {
+                        break;
+                    }
+                }
+            }
+            '#' => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '-' | '/' if chars.peek() == Some(&c) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_ascii_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                let word = word.to_ascii_uppercase();
+                if MUTATING_KEYWORDS.contains(&word.as_str()) {
+                    anyhow::bail!("Query is not read-only: '{}' statements are not allowed", word);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Parse-tree nodes that can change data, schema, or session state, or run code whose body isn't visible here:
+/// write statements and subqueries, custom `fn::` functions, and embedded scripts.
+const MUTATING_NODES: [&str; 20] = [
+    "Create", "Update", "Upsert", "Delete", "Relate", "Insert", "Define", "Remove", "Rebuild", "Alter", "Kill", "Live",
+    "Access", "Option", "Use", "Begin", "Commit", "Cancel", "Custom", "Script",
+];
+
+/// Reject a SurrealQL query if anything in SurrealDB's own parse of it can write.
+///
+/// Unlike the keyword scan in `ensure_read_only`, this sees every statement and
+/// nested subquery exactly as the database will run it. The tree is walked in its
+/// serialized form, so an object literal with a key such as `Create` is refused too.
+pub fn ensure_read_only_parsed(surql: &str) -> Result<()> {
+    let query = surrealdb::sql::parse(surql)?;
+    let tree = serde_json::to_value(&query)?;
+    if let Some(node) = find_mutating_node(&tree) {
+        anyhow::bail!("Query is not read-only: '{}' is not allowed", node.to_ascii_uppercase());
+    }
+    Ok(())
+}
+
+fn find_mutating_node(node: &serde_json::Value) -> Option<&str> {
+    match node {
+        serde_json::Value::Object(fields) => fields.iter().find_map(|(key, value)| {
+            if MUTATING_NODES.contains(&key.as_str()) {
+                Some(key.as_str())
+            } else {
+                find_mutating_node(value)
+            }
+        }),
+        serde_json::Value::Array(items) => items.iter().find_map(find_mutating_node),
+        _ => None,
+    }
+}
 
 pub struct DatabaseManager {
-    pub db: Surreal<Db>,
+    // `Any` lets the embedded and remote engines share every method below
+    pub db: Surreal<Any>,
+    /// Local database path, or the server URL for remote connections.
+    pub db_path: String,
 }
 
 impl DatabaseManager {
     pub async fn new(db_path: &str) -> Result<Self> {
-        // Initialize SurrealDB with RocksDB/SurrealKV at the specified path
-        let db: Surreal<Db> = Surreal::new::<SurrealKv>(db_path).await?;
+        // Initialize SurrealDB with the embedded SurrealKV engine at the specified path
+        let db: Surreal<Any> = any::connect(format!("surrealkv://{}", db_path)).await?;
 
         // Use a default namespace and database
         db.use_ns("data_science_app").use_db("main").await?;
 
-        Ok(Self { db })
+        let manager = Self {
+            db,
+            db_path: db_path.to_string(),
+        };
+        manager.ensure_schema().await?;
+        Ok(manager)
+    }
+
+    /// Connect to a remote SurrealDB server over WebSocket.
+    ///
+    /// `url` may be given with or without the `ws://` / `wss://` scheme. Root credentials are
+    /// tried first, falling back to namespace-level credentials for `ns`; if both fail the
+    /// error names both reasons.
+    #[cfg(feature = "remote")]
+    pub async fn new_remote(url: &str, username: &str, password: &str, ns: &str, db_name: &str) -> Result<Self> {
+        use surrealdb::opt::auth::{Namespace, Root};
+
+        let address = if url.starts_with("ws://") || url.starts_with("wss://") {
+            url.to_string()
+        } else {
+            format!("ws://{}", url)
+        };
+        let db: Surreal<Any> = any::connect(address.as_str()).await?;
+
+        if let Err(root_error) = db.signin(Root { username, password }).await {
+            db.signin(Namespace {
+                namespace: ns,
+                username,
+                password,
+            })
+            .await
+            .map_err(|namespace_error| {
+                anyhow::anyhow!(
+                    "root signin failed: {}; namespace signin failed: {}",
+                    root_error,
+                    namespace_error
+                )
+            })?;
+        }
+
+        db.use_ns(ns).use_db(db_name).await?;
+
+        let manager = Self { db, db_path: address };
+        manager.ensure_schema().await?;
+        Ok(manager)
+    }
+
+    /// Define every table, the timestamp fields, and the `config_name` indexes.
+    ///
+    /// Every statement uses `IF NOT EXISTS`, so this is safe to run on each start-up
+    /// against a database that already has data. Tables stay schemaless.
+    pub async fn ensure_schema(&self) -> Result<()> {
+        // Table names come from constants, so interpolating them is safe
+        let mut schema: String = TABLES
+            .iter()
+            .map(|table| format!("DEFINE TABLE IF NOT EXISTS {} SCHEMALESS;", table))
+            .collect();
+        for table in CONFIG_TABLES {
+            schema.push_str(&format!(
+                "DEFINE FIELD IF NOT EXISTS created_at ON TABLE {table} TYPE option<string>;\
+                 DEFINE FIELD IF NOT EXISTS updated_at ON TABLE {table} TYPE option<string>;\
+                 DEFINE INDEX IF NOT EXISTS {table}_config_name ON TABLE {table} FIELDS config_name;"
+            ));
+        }
+        self.db.query(schema).await?.check()?;
+        Ok(())
     }
 
     // --- Safety Records ---
@@ -29,6 +381,29 @@ impl DatabaseManager {
         }
     }
 
+    /// Insert many safety records in a single transaction, returning their IDs in input order.
+    ///
+    /// Either every record is created or, on failure, none are.
+    pub async fn insert_safety_records_batch(&self, records: &[SafetyRecord]) -> Result<Vec<String>> {
+        if records.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut response = self
+            .db
+            .query("BEGIN TRANSACTION; INSERT INTO safety_records $records; COMMIT TRANSACTION;")
+            .bind(("records", records.to_vec()))
+            .await?;
+        let offset = response.num_statements().saturating_sub(1);
+        let created: Vec<SafetyRecord> = response.take(offset)?;
+        if created.len() != records.len() {
+            anyhow::bail!("Expected {} created records, got {}", records.len(), created.len());
+        }
+        Ok(created
+            .into_iter()
+            .map(|r| r.id.map(|t| t.to_string()).unwrap_or_default())
+            .collect())
+    }
+
     pub async fn get_safety_records(&self, limit: Option<i32>) -> Result<Vec<SafetyRecord>> {
         let mut query = "SELECT * FROM safety_records ORDER BY timestamp DESC".to_string();
         if let Some(l) = limit {
@@ -48,25 +423,291 @@ impl DatabaseManager {
         Ok(records)
     }
 
+    /// One page of safety records, newest first, optionally only those with `severity`.
+    pub async fn get_safety_records_paginated(
+        &self,
+        page: i32,
+        limit: i32,
+        severity: Option<Severity>,
+    ) -> Result<serde_json::Value> {
+        let mut params = serde_json::Map::new();
+        if let Some(severity) = severity {
+            params.insert("severity".to_string(), severity.as_str().into());
+        }
+        self.paginate_table::<SafetyRecord>(
+            "safety_records",
+            page,
+            limit,
+            severity.map(|_| "severity = $severity"),
+            params,
+            Some(("timestamp", "DESC")),
+        )
+        .await
+    }
+
+    /// One page of safety records matching every filter set in `query`.
+    ///
+    /// Each filter adds a condition on a bound parameter, so no caller input is
+    /// interpolated into the statement; `sort_by` must be one of the record's
+    /// sortable fields. Uses the same envelope as `get_safety_records_paginated`.
+    pub async fn query_safety_records(&self, query: &SafetyRecordQuery) -> Result<serde_json::Value> {
+        let (filter, params) = safety_record_filter(query)?;
+        let sort_by = query.sort_by.as_deref().unwrap_or("timestamp");
+        if !SAFETY_RECORD_SORT_FIELDS.contains(&sort_by) {
+            anyhow::bail!(
+                "Cannot sort safety records by '{}': expected one of {}",
+                sort_by,
+                SAFETY_RECORD_SORT_FIELDS.join(", ")
+            );
+        }
+        self.paginate_table::<SafetyRecord>(
+            "safety_records",
+            query.page.unwrap_or(1),
+            query.limit.unwrap_or(50),
+            filter.as_deref(),
+            params,
+            Some((sort_by, query.sort_order.as_deref().unwrap_or("DESC"))),
+        )
+        .await
+    }
+
+    /// Safety records whose `timestamp` lies within `[start, end]`, newest first.
+    ///
+    /// `timestamp` is stored in the form written by `format_timestamp`, so the bounds are
+    /// normalised to it and compared lexicographically, which matches chronological order.
+    pub async fn get_safety_records_between(
+        &self,
+        start: &str,
+        end: &str,
+        limit: Option<i32>,
+    ) -> Result<Vec<SafetyRecord>> {
+        let parse = |label: &str, value: &str| {
+            parse_timestamp(value).map_err(|e| anyhow::anyhow!("Invalid {} bound: {}", label, e))
+        };
+        let start = parse("start", start)?;
+        let end = parse("end", end)?;
+        if start > end {
+            anyhow::bail!(
+                "start ({}) must not be after end ({})",
+                start.to_rfc3339(),
+                end.to_rfc3339()
+            );
+        }
+
+        let mut query =
+            "SELECT * FROM safety_records WHERE timestamp >= $start AND timestamp <= $end ORDER BY timestamp DESC"
+                .to_string();
+        if let Some(l) = limit {
+            query.push_str(&format!(" LIMIT {}", l));
+        }
+        let records: Vec<SafetyRecord> = self
+            .db
+            .query(query)
+            .bind(("start", format_timestamp(start)))
+            .bind(("end", format_timestamp(end)))
+            .await?
+            .take(0)?;
+        Ok(records)
+    }
+
+    /// Case-insensitive substring search over each record's serialized `data` and its `record_type`.
+    pub async fn search_safety_records(&self, term: &str, limit: Option<i32>) -> Result<Vec<SafetyRecord>> {
+        let records: Vec<SafetyRecord> = self
+            .db
+            .query(
+                "SELECT * FROM safety_records \
+                 WHERE string::contains(string::lowercase(type::string(data)), $term) \
+                 OR string::contains(string::lowercase(record_type), $term) \
+                 ORDER BY timestamp DESC LIMIT $lim",
+            )
+            .bind(("term", term.to_lowercase()))
+            .bind(("lim", limit.unwrap_or(50)))
+            .await?
+            .take(0)?;
+        Ok(records)
+    }
+
+    /// Count the records in a single table.
+    async fn count_table(&self, table: &str) -> Result<i64> {
+        let mut result = self
+            .db
+            .query("SELECT count() FROM type::table($table) GROUP ALL")
+            .bind(("table", table.to_string()))
+            .await?;
+        Ok(group_count(result.take(0)?))
+    }
+
     pub async fn get_stats(&self) -> Result<serde_json::Value> {
-        // Implement database stats gathering here
-        // For now, return a placeholder or partial stats
+        let mut tables = serde_json::Map::new();
+        let mut total_records: i64 = 0;
+        for table in TABLES {
+            let count = self.count_table(table).await?;
+            total_records += count;
+            tables.insert(table.to_string(), serde_json::json!(count));
+        }
+
         Ok(serde_json::json!({
             "success": true,
-            "message": "Stats not fully implemented for SurrealDB yet"
+            "database_path": self.db_path,
+            "tables": tables,
+            "total_records": total_records
         }))
     }
 
-    pub async fn reset_database(&self) -> Result<()> {
-        // Warning: This deletes everything in the current namespace/database
-        // self.db.query("REMOVE DATABASE main").await?; // Example, be careful
-        // self.db.use_ns("data_science_app").use_db("main").await?;
-        Ok(())
+    /// Remove every record from every known table in a single transaction.
+    ///
+    /// Returns the number of deleted records per table.
+    pub async fn reset_database(&self) -> Result<Vec<(String, u64)>> {
+        // Table names come from the TABLES constant, so interpolating them is safe
+        let deletes: String = TABLES
+            .iter()
+            .map(|table| format!("DELETE {} RETURN BEFORE;", table))
+            .collect();
+        let mut response = self
+            .db
+            .query(format!("BEGIN TRANSACTION; {} COMMIT TRANSACTION;", deletes))
+            .await?;
+
+        // Skip any leading results produced by the transaction statements themselves
+        let offset = response.num_statements().saturating_sub(TABLES.len());
+        let mut counts = Vec::with_capacity(TABLES.len());
+        for (i, table) in TABLES.iter().enumerate() {
+            let deleted: Vec<serde_json::Value> = response.take(offset + i)?;
+            counts.push((table.to_string(), deleted.len() as u64));
+        }
+        Ok(counts)
+    }
+
+    /// Write every known table to `out_path` as a single JSON archive.
+    ///
+    /// Each record's key is kept under `BACKUP_KEY_FIELD` so `import_database` can restore
+    /// records with their original ids. Returns the number of exported records per table.
+    pub async fn export_database(&self, out_path: &str) -> Result<Vec<(String, u64)>> {
+        let mut tables = serde_json::Map::new();
+        let mut counts = Vec::with_capacity(TABLES.len());
+        for table in TABLES {
+            let records: Vec<serde_json::Value> = self
+                .db
+                .query(format!(
+                    "SELECT * OMIT id, meta::id(id) AS {} FROM {}",
+                    BACKUP_KEY_FIELD, table
+                ))
+                .await?
+                .take(0)?;
+            counts.push((table.to_string(), records.len() as u64));
+            tables.insert(table.to_string(), serde_json::Value::Array(records));
+        }
+
+        let archive = serde_json::json!({
+            "format": BACKUP_FORMAT,
+            "version": BACKUP_VERSION,
+            "created_at": now_timestamp(),
+            "tables": tables,
+        });
+        tokio::fs::write(out_path, serde_json::to_vec_pretty(&archive)?).await?;
+        Ok(counts)
+    }
+
+    /// Replay an archive written by `export_database` into the current namespace and database.
+    ///
+    /// Each table in the archive replaces the table's current contents inside its own
+    /// transaction, so a failing table is rolled back without affecting the others. Tables
+    /// missing from the archive are left untouched. Returns the outcome per table.
+    pub async fn import_database(&self, in_path: &str) -> Result<Vec<TableRestore>> {
+        let bytes = tokio::fs::read(in_path).await?;
+        let archive: serde_json::Value = serde_json::from_slice(&bytes)?;
+        if archive.get("format").and_then(|f| f.as_str()) != Some(BACKUP_FORMAT) {
+            anyhow::bail!("{} is not a database backup", in_path);
+        }
+        let version = archive.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+        if version > BACKUP_VERSION {
+            anyhow::bail!(
+                "Backup version {} is newer than the supported version {}",
+                version,
+                BACKUP_VERSION
+            );
+        }
+        let tables = archive
+            .get("tables")
+            .and_then(|t| t.as_object())
+            .ok_or_else(|| anyhow::anyhow!("Backup has no 'tables' object"))?;
+
+        let mut results = Vec::with_capacity(tables.len());
+        for (table, records) in tables {
+            let outcome = if TABLES.contains(&table.as_str()) {
+                self.restore_table(table, records).await
+            } else {
+                Err(anyhow::anyhow!("Unknown table"))
+            };
+            results.push(match outcome {
+                Ok(restored) => TableRestore {
+                    table: table.clone(),
+                    restored,
+                    error: None,
+                },
+                Err(e) => TableRestore {
+                    table: table.clone(),
+                    restored: 0,
+                    error: Some(e.to_string()),
+                },
+            });
+        }
+        Ok(results)
     }
 
-    pub async fn cleanup_old_records(&self, _days: i32) -> Result<u64> {
-        // Implement cleanup logic
-        Ok(0)
+    /// Replace the contents of one table with archived records in a single transaction.
+    async fn restore_table(&self, table: &str, records: &serde_json::Value) -> Result<u64> {
+        let records = records
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Expected an array of records"))?
+            .iter()
+            .map(|record| {
+                let mut record = record
+                    .as_object()
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("Expected every record to be an object"))?;
+                if let Some(key) = record.remove(BACKUP_KEY_FIELD) {
+                    record.insert("id".to_string(), key);
+                }
+                Ok(serde_json::Value::Object(record))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let count = records.len() as u64;
+
+        // Table names come from the TABLES constant, so interpolating them is safe
+        let insert = if records.is_empty() {
+            String::new()
+        } else {
+            format!("INSERT INTO {} $records;", table)
+        };
+        self.db
+            .query(format!(
+                "BEGIN TRANSACTION; DELETE {}; {} COMMIT TRANSACTION;",
+                table, insert
+            ))
+            .bind(("records", records))
+            .await?
+            .check()?;
+        Ok(count)
+    }
+
+    /// Delete safety records created more than `days` days ago.
+    ///
+    /// `created_at` is stored as a UTC RFC3339 string, which sorts lexicographically in
+    /// chronological order, so a plain string comparison against the cutoff is sufficient.
+    /// With `days == 0` every record created before now is removed.
+    pub async fn cleanup_old_records(&self, days: i32) -> Result<u64> {
+        if days < 0 {
+            anyhow::bail!("days must be non-negative, got {}", days);
+        }
+        let cutoff = format_timestamp(chrono::Utc::now() - chrono::Duration::days(days as i64));
+        let deleted: Vec<serde_json::Value> = self
+            .db
+            .query("DELETE safety_records WHERE created_at < $cutoff RETURN BEFORE")
+            .bind(("cutoff", cutoff))
+            .await?
+            .take(0)?;
+        Ok(deleted.len() as u64)
     }
 
     async fn get_configs_generic<T: for<'de> serde::Deserialize<'de> + Send + Sync + 'static>(
@@ -77,6 +718,55 @@ impl DatabaseManager {
         Ok(configs)
     }
 
+    /// Accept either a full record id (`table:key`) or just the key.
+    fn record_key<'a>(table: &str, id: &'a str) -> &'a str {
+        id.strip_prefix(table)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .unwrap_or(id)
+    }
+
+    /// Merge a typed record into an existing row, keeping its id and creation time and stamping `updated_at`.
+    async fn update_typed<T: serde::Serialize>(&self, table: &str, id: &str, config: &T) -> Result<bool> {
+        let mut patch = serde_json::to_value(config)?;
+        if let Some(fields) = patch.as_object_mut() {
+            fields.remove("id");
+            fields.remove("created_at");
+            fields.insert("updated_at".to_string(), serde_json::Value::String(now_timestamp()));
+        }
+        let key = Self::record_key(table, id).to_string();
+        let updated: Option<serde_json::Value> = self.db.update((table.to_string(), key)).merge(patch).await?;
+        Ok(updated.is_some())
+    }
+
+    async fn delete_typed(&self, table: &str, id: &str) -> Result<bool> {
+        let key = Self::record_key(table, id).to_string();
+        let deleted: Option<serde_json::Value> = self.db.delete((table.to_string(), key)).await?;
+        Ok(deleted.is_some())
+    }
+
+    /// Run an ad-hoc read-only SurrealQL query and return its first result set as a JSON array.
+    ///
+    /// `params` is an object whose entries are bound as `$name` variables; `null` binds nothing.
+    /// The keyword scan in `ensure_read_only` gives an early, readable error; the
+    /// query is then only run if `ensure_read_only_parsed` finds nothing that can
+    /// write anywhere in its parse tree.
+    pub async fn query_readonly(&self, surql: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        ensure_read_only(surql)?;
+        ensure_read_only_parsed(surql)?;
+        let params = match params {
+            serde_json::Value::Object(map) => map,
+            serde_json::Value::Null => serde_json::Map::new(),
+            other => anyhow::bail!("Query params must be a JSON object, got {}", other),
+        };
+        let mut query = self.db.query(surql);
+        for (name, value) in params {
+            validate_identifier(&name)?;
+            query = query.bind((name, value));
+        }
+        let rows: Vec<serde_json::Value> = query.await?.take(0)?;
+        Ok(serde_json::Value::Array(rows))
+    }
+
     // --- Stream Configs ---
     pub async fn save_stream_config(&self, config: &StreamConfig) -> Result<String> {
         let created: Option<StreamConfig> = self.db.create("stream_configs").content(config.clone()).await?;
@@ -87,6 +777,13 @@ impl DatabaseManager {
         self.get_configs_generic("stream_configs").await
     }
 
+    typed_config_mutations!(
+        update_stream_config,
+        delete_stream_config,
+        StreamConfig,
+        "stream_configs"
+    );
+
     // --- OGG Configs ---
     pub async fn save_ogg_config(&self, config: &OggConfig) -> Result<String> {
         let created: Option<OggConfig> = self.db.create("ogg_configs").content(config.clone()).await?;
@@ -97,6 +794,8 @@ impl DatabaseManager {
         self.get_configs_generic("ogg_configs").await
     }
 
+    typed_config_mutations!(update_ogg_config, delete_ogg_config, OggConfig, "ogg_configs");
+
     // --- ORG Configs ---
     pub async fn save_org_config(&self, config: &OrgConfig) -> Result<String> {
         let created: Option<OrgConfig> = self.db.create("org_configs").content(config.clone()).await?;
@@ -107,6 +806,8 @@ impl DatabaseManager {
         self.get_configs_generic("org_configs").await
     }
 
+    typed_config_mutations!(update_org_config, delete_org_config, OrgConfig, "org_configs");
+
     // --- ORSG Configs ---
     pub async fn save_orsg_config(&self, config: &OrsgConfig) -> Result<String> {
         let created: Option<OrsgConfig> = self.db.create("orsg_configs").content(config.clone()).await?;
@@ -117,6 +818,8 @@ impl DatabaseManager {
         self.get_configs_generic("orsg_configs").await
     }
 
+    typed_config_mutations!(update_orsg_config, delete_orsg_config, OrsgConfig, "orsg_configs");
+
     // --- Event Configs ---
     pub async fn save_event_config(&self, config: &EventConfig) -> Result<String> {
         let created: Option<EventConfig> = self.db.create("event_configs").content(config.clone()).await?;
@@ -127,6 +830,8 @@ impl DatabaseManager {
         self.get_configs_generic("event_configs").await
     }
 
+    typed_config_mutations!(update_event_config, delete_event_config, EventConfig, "event_configs");
+
     // --- Config Recipes ---
     pub async fn save_config_recipe(&self, config: &ConfigRecipe) -> Result<String> {
         let created: Option<ConfigRecipe> = self.db.create("config_recipes").content(config.clone()).await?;
@@ -137,10 +842,86 @@ impl DatabaseManager {
         self.get_configs_generic("config_recipes").await
     }
 
+    typed_config_mutations!(
+        update_config_recipe,
+        delete_config_recipe,
+        ConfigRecipe,
+        "config_recipes"
+    );
+
+    // --- Loaded Datasets ---
+    /// Record a load of `dataset.file_path`, updating its entry or creating one, and return the record ID.
+    ///
+    /// Reloading a file refreshes its `file_id`, `headers`, `metadata`, and `created_at`
+    /// (the load time), so the history keeps one entry per path. Duplicate entries
+    /// written before this was an upsert are removed when their path is next loaded.
+    pub async fn save_loaded_dataset(&self, dataset: &LoadedDataset) -> Result<String> {
+        let updated: Vec<LoadedDataset> = self
+            .db
+            .query(
+                "UPDATE loaded_datasets SET file_id = $file_id, headers = $headers, metadata = $metadata, \
+                 created_at = $ts WHERE file_path = $path RETURN AFTER",
+            )
+            .bind(("path", dataset.file_path.clone()))
+            .bind(("file_id", dataset.file_id.clone()))
+            .bind(("headers", dataset.headers.clone()))
+            .bind(("metadata", dataset.metadata.clone()))
+            .bind(("ts", dataset.created_at.clone()))
+            .await?
+            .take(0)?;
+        if let Some(id) = updated.first().and_then(|d| d.id.clone()) {
+            if updated.len() > 1 {
+                self.db
+                    .query("DELETE loaded_datasets WHERE file_path = $path AND id != $id")
+                    .bind(("path", dataset.file_path.clone()))
+                    .bind(("id", id.clone()))
+                    .await?
+                    .check()?;
+            }
+            return Ok(id.to_string());
+        }
+        let created: Option<LoadedDataset> = self.db.create("loaded_datasets").content(dataset.clone()).await?;
+        Ok(created.and_then(|d| d.id).map(|t| t.to_string()).unwrap_or_default())
+    }
+
+    /// Most recently loaded datasets, newest first, with one entry per file path.
+    pub async fn get_recent_loaded_datasets(&self, limit: usize) -> Result<Vec<LoadedDataset>> {
+        let datasets: Vec<LoadedDataset> = self
+            .db
+            .query("SELECT * FROM loaded_datasets ORDER BY created_at DESC LIMIT $lim")
+            .bind(("lim", limit as i64))
+            .await?
+            .take(0)?;
+        Ok(datasets)
+    }
+
+    /// Remove a dataset from the load history; `false` if no record had that id.
+    pub async fn delete_loaded_dataset(&self, id: &str) -> Result<bool> {
+        self.delete_typed("loaded_datasets", id).await
+    }
+
     // --- Legacy Configurations ---
-    pub async fn save_configuration(&self, config: &Configuration) -> Result<String> {
+    /// Update the configuration named `config.name`, or create it if none exists.
+    ///
+    /// Updates set `content` and `updated_at`. Returns `true` alongside the record ID
+    /// when it was newly created, `false` when an existing record was updated.
+    pub async fn save_configuration(&self, config: &Configuration) -> Result<(bool, String)> {
+        let updated: Vec<Configuration> = self
+            .db
+            .query("UPDATE configurations SET content = $content, updated_at = $ts WHERE name = $name RETURN AFTER")
+            .bind(("name", config.name.clone()))
+            .bind(("content", config.content.clone()))
+            .bind(("ts", config.updated_at.clone()))
+            .await?
+            .take(0)?;
+        if let Some(existing) = updated.into_iter().next() {
+            return Ok((false, existing.id.map(|t| t.to_string()).unwrap_or_default()));
+        }
         let created: Option<Configuration> = self.db.create("configurations").content(config.clone()).await?;
-        Ok(created.and_then(|c| c.id).map(|t| t.to_string()).unwrap_or_default())
+        Ok((
+            true,
+            created.and_then(|c| c.id).map(|t| t.to_string()).unwrap_or_default(),
+        ))
     }
 
     pub async fn get_configuration(&self, name: &str) -> Result<Option<Configuration>> {
@@ -164,6 +945,7 @@ impl DatabaseManager {
         id: Option<i64>,
         name: Option<&str>,
     ) -> Result<Option<serde_json::Value>> {
+        validate_identifier(table)?;
         let mut result = if let Some(name_val) = name {
             self.db
                 .query("SELECT * FROM type::table($table) WHERE config_name = $name LIMIT 1")
@@ -183,9 +965,10 @@ impl DatabaseManager {
         Ok(record)
     }
 
-    /// Save (create) a new record in any config table.
-    pub async fn save_generic(&self, table: &str, name: &str, content: &str) -> Result<String> {
-        let now = chrono::Utc::now().to_rfc3339();
+    /// Save (create) a new record in any config table, returning the created record.
+    pub async fn save_generic(&self, table: &str, name: &str, content: &str) -> Result<serde_json::Value> {
+        validate_identifier(table)?;
+        let now = now_timestamp();
         let mut result = self
             .db
             .query("CREATE type::table($table) SET config_name = $name, config_content = $content, created_at = $ts")
@@ -195,50 +978,93 @@ impl DatabaseManager {
             .bind(("ts", now))
             .await?;
         let created: Option<serde_json::Value> = result.take(0)?;
-        Ok(created
-            .and_then(|v| v.get("id").cloned())
-            .map(|v| v.to_string())
-            .unwrap_or_default())
+        created.ok_or_else(|| anyhow::anyhow!("Failed to create record in {}", table))
     }
 
-    /// Update a single named field on a record in any config table.
-    pub async fn update_generic_field(&self, table: &str, id: i64, field: &str, value: &str) -> Result<bool> {
-        // Validate field name to prevent SurrealQL injection
-        if !field.chars().all(|c| c.is_alphanumeric() || c == '_') {
-            anyhow::bail!("Invalid field name: {}", field);
+    /// Update the record named `name` in any config table, or create it if none exists.
+    ///
+    /// Updates set `config_content` and `updated_at`. Returns `true` alongside the record
+    /// when it was newly created, `false` when an existing record was updated.
+    pub async fn upsert_generic(&self, table: &str, name: &str, content: &str) -> Result<(bool, serde_json::Value)> {
+        validate_identifier(table)?;
+        let updated: Vec<serde_json::Value> = self
+            .db
+            .query(
+                "UPDATE type::table($table) SET config_content = $content, updated_at = $ts \
+                 WHERE config_name = $name RETURN AFTER",
+            )
+            .bind(("table", table.to_string()))
+            .bind(("name", name.to_string()))
+            .bind(("content", content.to_string()))
+            .bind(("ts", now_timestamp()))
+            .await?
+            .take(0)?;
+        match updated.into_iter().next() {
+            Some(record) => Ok((false, record)),
+            None => Ok((true, self.save_generic(table, name, content).await?)),
         }
-        let query = format!("UPDATE type::table($table) SET {} = $value WHERE id = $id", field);
-        self.db
+    }
+
+    /// Update a single named field on a record in any config table, stamping `updated_at`.
+    ///
+    /// Returns the number of records updated, so `0` means nothing matched `id`.
+    pub async fn update_generic_field(&self, table: &str, id: i64, field: &str, value: &str) -> Result<u64> {
+        validate_identifier(table)?;
+        // The field is interpolated into the query, so it must be a plain identifier
+        validate_identifier(field)?;
+        let value = if TIMESTAMP_FIELDS.contains(&field) {
+            normalize_timestamp(value)?
+        } else {
+            value.to_string()
+        };
+        // Stamp `updated_at` unless the caller is setting it explicitly
+        let query = if field == "updated_at" {
+            "UPDATE type::table($table) SET updated_at = $value WHERE id = $id RETURN AFTER".to_string()
+        } else {
+            format!(
+                "UPDATE type::table($table) SET {} = $value, updated_at = $ts WHERE id = $id RETURN AFTER",
+                field
+            )
+        };
+        let updated: Vec<serde_json::Value> = self
+            .db
             .query(query)
             .bind(("table", table.to_string()))
-            .bind(("value", value.to_string()))
+            .bind(("value", value))
+            .bind(("ts", now_timestamp()))
             .bind(("id", id))
-            .await?;
-        Ok(true)
+            .await?
+            .take(0)?;
+        Ok(updated.len() as u64)
     }
 
-    /// Delete a record from any config table by name or ID.
-    pub async fn delete_generic(&self, table: &str, id: Option<i64>, name: Option<&str>) -> Result<bool> {
-        if let Some(name_val) = name {
+    /// Delete records from any config table by name or ID.
+    ///
+    /// Returns the number of records deleted; `0` if nothing matched or neither key was given.
+    pub async fn delete_generic(&self, table: &str, id: Option<i64>, name: Option<&str>) -> Result<u64> {
+        validate_identifier(table)?;
+        let mut result = if let Some(name_val) = name {
             self.db
-                .query("DELETE FROM type::table($table) WHERE config_name = $name")
+                .query("DELETE FROM type::table($table) WHERE config_name = $name RETURN BEFORE")
                 .bind(("table", table.to_string()))
                 .bind(("name", name_val.to_string()))
-                .await?;
+                .await?
         } else if let Some(id_val) = id {
             self.db
-                .query("DELETE FROM type::table($table) WHERE id = $id")
+                .query("DELETE FROM type::table($table) WHERE id = $id RETURN BEFORE")
                 .bind(("table", table.to_string()))
                 .bind(("id", id_val))
-                .await?;
+                .await?
         } else {
-            return Ok(false);
-        }
-        Ok(true)
+            return Ok(0);
+        };
+        let deleted: Vec<serde_json::Value> = result.take(0)?;
+        Ok(deleted.len() as u64)
     }
 
     /// Search records in any config table by config_name (substring match).
     pub async fn search_generic(&self, table: &str, query: &str, limit: Option<i32>) -> Result<Vec<serde_json::Value>> {
+        validate_identifier(table)?;
         let lim = limit.unwrap_or(50);
         let mut result = self
             .db
@@ -253,6 +1079,7 @@ impl DatabaseManager {
 
     /// Fetch all records from any config table.
     pub async fn get_all_generic(&self, table: &str) -> Result<Vec<serde_json::Value>> {
+        validate_identifier(table)?;
         let records: Vec<serde_json::Value> = self
             .db
             .query("SELECT * FROM type::table($table)")
@@ -271,58 +1098,801 @@ impl DatabaseManager {
         sort_by: Option<&str>,
         sort_order: Option<&str>,
     ) -> Result<serde_json::Value> {
-        let page = page.max(1);
-        let limit = limit.max(1);
-        let start = (page - 1) * limit;
+        let order = sort_by.map(|col| (col, sort_order.unwrap_or("ASC")));
+        self.paginate_table::<serde_json::Value>(table, page, limit, None, serde_json::Map::new(), order)
+            .await
+    }
 
-        let order_clause = match sort_by {
-            Some(col) if col.chars().all(|c| c.is_alphanumeric() || c == '_') => {
-                let dir = if sort_order.map(|o| o.to_uppercase()) == Some("DESC".to_string()) {
+    /// One page of `table` with pagination metadata, shared by every paginated query.
+    ///
+    /// The data and `GROUP ALL` count queries share `where_clause`, which is interpolated
+    /// as-is and so must never contain caller input; values go in `params`, bound as
+    /// `$name` (`table`, `limit`, and `start` are taken). `order` is a column, validated as
+    /// an identifier, and a direction: `DESC` (any case) or ascending. Records are read as `T`.
+    async fn paginate_table<T>(
+        &self,
+        table: &str,
+        page: i32,
+        limit: i32,
+        where_clause: Option<&str>,
+        params: serde_json::Map<String, serde_json::Value>,
+        order: Option<(&str, &str)>,
+    ) -> Result<serde_json::Value>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize,
+    {
+        validate_identifier(table)?;
+        let filter = where_clause
+            .map(|clause| format!(" WHERE {}", clause))
+            .unwrap_or_default();
+        let order_clause = match order {
+            Some((col, dir)) => {
+                validate_identifier(col)?;
+                let dir = if dir.eq_ignore_ascii_case("DESC") {
                     "DESC"
                 } else {
                     "ASC"
                 };
                 format!(" ORDER BY {} {}", col, dir)
             }
-            _ => String::new(),
+            None => String::new(),
         };
+        let window = PageWindow::new(page, limit);
 
-        let query = format!(
-            "SELECT * FROM type::table($table){} LIMIT $limit START $start",
-            order_clause
-        );
-        let mut result = self
+        let mut query = self
             .db
-            .query(query)
+            .query(format!(
+                "SELECT * FROM type::table($table){}{} LIMIT $limit START $start",
+                filter, order_clause
+            ))
+            .query(format!("SELECT count() FROM type::table($table){} GROUP ALL", filter))
             .bind(("table", table.to_string()))
-            .bind(("limit", limit))
-            .bind(("start", start))
-            .await?;
-        let records: Vec<serde_json::Value> = result.take(0)?;
+            .bind(("limit", window.limit))
+            .bind(("start", window.start()));
+        for (name, value) in params {
+            query = query.bind((name, value));
+        }
+        let mut result = query.await?;
+        let records: Vec<T> = result.take(0)?;
+        let total = group_count(result.take(1)?);
+        Ok(window.envelope(&records, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use surrealdb::sql::Thing;
+
+    // A fresh in-memory database with the app's schema
+    async fn manager() -> DatabaseManager {
+        let db: Surreal<Any> = any::connect("mem://").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        let manager = DatabaseManager {
+            db,
+            db_path: "mem://".to_string(),
+        };
+        manager.ensure_schema().await.unwrap();
+        manager
+    }
+
+    fn safety_record(severity: Severity, timestamp: &str) -> SafetyRecord {
+        SafetyRecord {
+            id: None,
+            record_type: "inspection".to_string(),
+            timestamp: timestamp.to_string(),
+            data: serde_json::json!({}),
+            severity: severity.to_string(),
+            created_at: timestamp.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn stats_count_records_per_table() {
+        let db = manager().await;
+        let stats = db.get_stats().await.unwrap();
+        assert_eq!(stats["total_records"], 0);
+        assert_eq!(stats["database_path"], "mem://");
+        assert_eq!(stats["tables"].as_object().unwrap().len(), TABLES.len());
+
+        let record = safety_record(Severity::High, "2024-01-01T00:00:00Z");
+        db.insert_safety_record(&record).await.unwrap();
+        db.insert_safety_record(&record).await.unwrap();
+        db.save_generic("stream_configs", "cam-1", "{}").await.unwrap();
+
+        let stats = db.get_stats().await.unwrap();
+        assert_eq!(stats["success"], true);
+        assert_eq!(stats["tables"]["safety_records"], 2);
+        assert_eq!(stats["tables"]["stream_configs"], 1);
+        assert_eq!(stats["tables"]["ogg_configs"], 0);
+        assert_eq!(stats["total_records"], 3);
+    }
+
+    #[tokio::test]
+    async fn batch_insert_returns_ids_in_input_order() {
+        let db = manager().await;
+        let records: Vec<SafetyRecord> = (0..1000)
+            .map(|i| safety_record(Severity::Medium, &format!("2024-01-01T00:00:{:02}Z", i % 60)))
+            .collect();
+        let ids = db.insert_safety_records_batch(&records).await.unwrap();
+        assert_eq!(ids.len(), 1000);
+
+        let stored = db.get_safety_records(None).await.unwrap();
+        assert_eq!(stored.len(), 1000);
+        let timestamps: HashMap<String, String> = stored
+            .into_iter()
+            .map(|r| (r.id.unwrap().to_string(), r.timestamp))
+            .collect();
+        for (id, record) in ids.iter().zip(&records) {
+            assert_eq!(timestamps[id], record.timestamp);
+        }
 
-        // Total count for pagination metadata
-        let mut count_result = self
+        assert!(db.insert_safety_records_batch(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_batch_insert_creates_nothing() {
+        let db = manager().await;
+        let mut duplicate = safety_record(Severity::Critical, "2024-01-01T00:00:00Z");
+        duplicate.id = Some(Thing::from(("safety_records", "dup")));
+        let records = vec![
+            safety_record(Severity::Low, "2024-01-01T00:00:00Z"),
+            duplicate.clone(),
+            duplicate,
+        ];
+
+        assert!(db.insert_safety_records_batch(&records).await.is_err());
+        assert_eq!(db.count_table("safety_records").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn search_matches_data_substrings_and_record_type() {
+        let db = manager().await;
+        let mut leak = safety_record(Severity::High, "2024-01-02T00:00:00Z");
+        leak.data = serde_json::json!({"note": "Gas leak near Boiler 3"});
+        let mut fall = safety_record(Severity::Low, "2024-01-01T00:00:00Z");
+        fall.record_type = "incident".to_string();
+        fall.data = serde_json::json!({"note": "slip on stairs"});
+        db.insert_safety_record(&leak).await.unwrap();
+        db.insert_safety_record(&fall).await.unwrap();
+
+        let found = db.search_safety_records("boiler", None).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].data["note"], "Gas leak near Boiler 3");
+
+        let found = db.search_safety_records("INCIDENT", None).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].record_type, "incident");
+
+        assert!(db.search_safety_records("forklift", None).await.unwrap().is_empty());
+        assert_eq!(db.search_safety_records("", Some(1)).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn records_between_includes_both_bounds() {
+        let db = manager().await;
+        for timestamp in ["2024-01-01T00:00:00Z", "2024-01-02T00:00:00Z", "2024-01-03T00:00:00Z"] {
+            let stored = normalize_timestamp(timestamp).unwrap();
+            db.insert_safety_record(&safety_record(Severity::Low, &stored))
+                .await
+                .unwrap();
+        }
+
+        // The start bound is the first record's time written in another offset
+        let found = db
+            .get_safety_records_between("2024-01-01T02:00:00+02:00", "2024-01-02T00:00:00Z", None)
+            .await
+            .unwrap();
+        let timestamps: Vec<&str> = found.iter().map(|r| r.timestamp.as_str()).collect();
+        assert_eq!(
+            timestamps,
+            ["2024-01-02T00:00:00.000000+00:00", "2024-01-01T00:00:00.000000+00:00"]
+        );
+
+        let limited = db
+            .get_safety_records_between("2024-01-01T00:00:00Z", "2024-01-03T00:00:00Z", Some(1))
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn records_between_rejects_bad_bounds() {
+        let db = manager().await;
+        let error = db
+            .get_safety_records_between("yesterday", "2024-01-02T00:00:00Z", None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Invalid start bound"));
+        assert!(
+            db.get_safety_records_between("2024-01-02T00:00:00Z", "2024-01-01T00:00:00Z", None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn reset_clears_every_table() {
+        let db = manager().await;
+        let record = safety_record(Severity::Low, "2024-01-01T00:00:00Z");
+        db.insert_safety_record(&record).await.unwrap();
+        db.save_generic("stream_configs", "cam-1", "{}").await.unwrap();
+        db.save_generic("event_configs", "alarm", "{}").await.unwrap();
+
+        let counts = db.reset_database().await.unwrap();
+        assert_eq!(counts.len(), TABLES.len());
+        let deleted = |table: &str| counts.iter().find(|(name, _)| name == table).unwrap().1;
+        assert_eq!(deleted("safety_records"), 1);
+        assert_eq!(deleted("stream_configs"), 1);
+        assert_eq!(deleted("event_configs"), 1);
+        assert_eq!(deleted("configurations"), 0);
+
+        assert_eq!(db.get_stats().await.unwrap()["total_records"], 0);
+    }
+
+    #[tokio::test]
+    async fn backup_survives_a_reset() {
+        let db = manager().await;
+        let record = safety_record(Severity::Critical, "2024-01-01T00:00:00Z");
+        db.insert_safety_records_batch(&[record.clone(), record]).await.unwrap();
+        db.save_generic("stream_configs", "cam-1", "{}").await.unwrap();
+        db.save_generic("config_recipes", "night", "{}").await.unwrap();
+        let names_before = db.get_all_generic("stream_configs").await.unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let backup = dir.path().join("backup.json");
+        let backup = backup.to_str().unwrap();
+        let exported = db.export_database(backup).await.unwrap();
+        db.reset_database().await.unwrap();
+        assert_eq!(db.get_stats().await.unwrap()["total_records"], 0);
+
+        let restored = db.import_database(backup).await.unwrap();
+        assert!(restored.iter().all(|table| table.error.is_none()));
+        for (table, count) in &exported {
+            let restored = restored.iter().find(|r| &r.table == table).unwrap();
+            assert_eq!(restored.restored, *count, "{}", table);
+        }
+        let stats = db.get_stats().await.unwrap();
+        assert_eq!(stats["tables"]["safety_records"], 2);
+        assert_eq!(stats["total_records"], 4);
+        // Records come back under their original ids
+        assert_eq!(db.get_all_generic("stream_configs").await.unwrap(), names_before);
+    }
+
+    #[tokio::test]
+    async fn non_backup_files_are_refused() {
+        let db = manager().await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("other.json");
+        std::fs::write(&file, r#"{"format": "something-else", "tables": {}}"#).unwrap();
+        assert!(db.import_database(file.to_str().unwrap()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn schema_indexes_config_names_idempotently() {
+        let db = manager().await;
+        // Running it again must not fail on the existing definitions
+        db.ensure_schema().await.unwrap();
+
+        let info: Option<serde_json::Value> = db
             .db
-            .query("SELECT count() FROM type::table($table) GROUP ALL")
-            .bind(("table", table.to_string()))
-            .await?;
-        let count_val: Option<serde_json::Value> = count_result.take(0)?;
-        let total: i64 = count_val
-            .and_then(|v| v.get("count").and_then(|c| c.as_i64()))
-            .unwrap_or(0);
-        let total_pages = ((total as f64) / (limit as f64)).ceil() as i64;
+            .query("INFO FOR TABLE stream_configs")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        let indexes = info.unwrap()["indexes"].clone();
+        assert!(indexes.get("stream_configs_config_name").is_some(), "{}", indexes);
 
-        Ok(serde_json::json!({
-            "success": true,
-            "data": records,
-            "pagination": {
-                "page": page,
-                "limit": limit,
-                "total": total,
-                "total_pages": total_pages,
-                "has_next": (page as i64) < total_pages,
-                "has_prev": page > 1
-            }
-        }))
+        db.save_generic("stream_configs", "front-door", "{}").await.unwrap();
+        db.save_generic("stream_configs", "back-door", "{}").await.unwrap();
+        db.save_generic("stream_configs", "lobby", "{}").await.unwrap();
+        let found = db.search_generic("stream_configs", "door", None).await.unwrap();
+        let mut names: Vec<&str> = found.iter().map(|r| r["config_name"].as_str().unwrap()).collect();
+        names.sort();
+        assert_eq!(names, ["back-door", "front-door"]);
+    }
+
+    #[test]
+    fn identifiers_are_plain_words() {
+        for valid in ["stream_configs", "config_name", "T1"] {
+            assert!(validate_identifier(valid).is_ok(), "{}", valid);
+        }
+        for invalid in [
+            "",
+            "configs; REMOVE TABLE safety_records",
+            "a-b",
+            "name`",
+            "x y",
+            "café",
+        ] {
+            assert!(validate_identifier(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn malicious_identifiers_are_rejected_before_querying() {
+        let db = manager().await;
+        db.save_generic("stream_configs", "cam-1", "{}").await.unwrap();
+
+        let table = "stream_configs; DELETE stream_configs";
+        assert!(db.save_generic(table, "x", "{}").await.is_err());
+        assert!(db.get_all_generic(table).await.is_err());
+        assert!(db.delete_generic(table, None, Some("cam-1")).await.is_err());
+        assert!(
+            db.update_generic_field("stream_configs", 1, "config_content = 'x', config_name", "y")
+                .await
+                .is_err()
+        );
+        assert!(
+            db.get_generic_paginated(
+                "stream_configs",
+                1,
+                10,
+                Some("config_name; DELETE stream_configs"),
+                None
+            )
+            .await
+            .is_err()
+        );
+
+        assert_eq!(db.get_all_generic("stream_configs").await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn mutating_statements_are_not_read_only() {
+        for query in [
+            "SELECT * FROM stream_configs",
+            "SELECT * FROM stream_configs WHERE config_name = 'delete me'",
+        ] {
+            assert!(ensure_read_only(query).is_ok(), "{}", query);
+            assert!(ensure_read_only_parsed(query).is_ok(), "{}", query);
+        }
+        for query in [
+            "DELETE stream_configs",
+            "SELECT * FROM (UPDATE stream_configs SET config_content = 'x')",
+            "select 1; define table sneaky",
+        ] {
+            assert!(ensure_read_only(query).is_err(), "{}", query);
+            assert!(ensure_read_only_parsed(query).is_err(), "{}", query);
+        }
+    }
+
+    #[tokio::test]
+    async fn readonly_queries_bind_params_and_refuse_writes() {
+        let db = manager().await;
+        db.save_generic("stream_configs", "cam-1", "{}").await.unwrap();
+        db.save_generic("stream_configs", "cam-2", "{}").await.unwrap();
+
+        let rows = db
+            .query_readonly(
+                "SELECT config_name FROM stream_configs WHERE config_name = $name",
+                serde_json::json!({"name": "cam-2"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows, serde_json::json!([{"config_name": "cam-2"}]));
+
+        assert!(
+            db.query_readonly("DELETE stream_configs", serde_json::Value::Null)
+                .await
+                .is_err()
+        );
+        assert_eq!(db.count_table("stream_configs").await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn save_generic_returns_the_created_record() {
+        let db = manager().await;
+        let created = db.save_generic("ogg_configs", "night", "{\"gain\":2}").await.unwrap();
+        assert_eq!(created["config_name"], "night");
+        assert_eq!(created["config_content"], "{\"gain\":2}");
+        assert!(created.get("id").is_some());
+        parse_timestamp(created["created_at"].as_str().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn upsert_creates_then_updates_by_name() {
+        let db = manager().await;
+        let (created, record) = db.upsert_generic("org_configs", "site-a", "v1").await.unwrap();
+        assert!(created);
+        assert_eq!(record["config_content"], "v1");
+
+        let (created, record) = db.upsert_generic("org_configs", "site-a", "v2").await.unwrap();
+        assert!(!created);
+        assert_eq!(record["config_content"], "v2");
+        assert!(record.get("updated_at").is_some());
+
+        let records = db.get_all_generic("org_configs").await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["config_name"], "site-a");
+    }
+
+    #[tokio::test]
+    async fn generic_update_and_delete_count_affected_records() {
+        let db = manager().await;
+        db.save_generic("stream_configs", "cam-1", "{}").await.unwrap();
+        db.save_generic("stream_configs", "cam-2", "{}").await.unwrap();
+
+        let missing = db.update_generic_field("stream_configs", 999, "config_content", "x");
+        assert_eq!(missing.await.unwrap(), 0);
+
+        assert_eq!(
+            db.delete_generic("stream_configs", None, Some("cam-1")).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            db.delete_generic("stream_configs", None, Some("cam-1")).await.unwrap(),
+            0
+        );
+        assert_eq!(db.delete_generic("stream_configs", Some(999), None).await.unwrap(), 0);
+        assert_eq!(db.delete_generic("stream_configs", None, None).await.unwrap(), 0);
+        assert_eq!(db.get_all_generic("stream_configs").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn repeated_configuration_saves_keep_one_row() {
+        let db = manager().await;
+        let config = |content: &str, updated_at: &str| Configuration {
+            id: None,
+            name: "layout".to_string(),
+            content: content.to_string(),
+            updated_at: updated_at.to_string(),
+        };
+
+        let (created, first_id) = db
+            .save_configuration(&config("v1", "2024-01-01T00:00:00Z"))
+            .await
+            .unwrap();
+        assert!(created);
+        let (created, second_id) = db
+            .save_configuration(&config("v2", "2024-01-02T00:00:00Z"))
+            .await
+            .unwrap();
+        assert!(!created);
+        assert_eq!(second_id, first_id);
+
+        let stats = db.get_stats().await.unwrap();
+        assert_eq!(stats["tables"]["configurations"], 1);
+        let saved = db.get_configuration("layout").await.unwrap().unwrap();
+        assert_eq!(saved.content, "v2");
+        assert_eq!(saved.updated_at, "2024-01-02T00:00:00Z");
+    }
+
+    #[test]
+    fn timestamps_are_normalised_to_utc() {
+        assert_eq!(
+            normalize_timestamp("2024-01-01T02:00:00+02:00").unwrap(),
+            "2024-01-01T00:00:00.000000+00:00"
+        );
+        assert_eq!(
+            normalize_timestamp("2024-01-01T00:00:00.5Z").unwrap(),
+            "2024-01-01T00:00:00.500000+00:00"
+        );
+        for invalid in ["2024-01-01 00:00:00", "2024-01-01", "yesterday", ""] {
+            assert!(normalize_timestamp(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    // Stored timestamps are already in the form `normalize_timestamp` produces
+    fn assert_stored_form(value: &serde_json::Value) {
+        let timestamp = value.as_str().unwrap();
+        assert_eq!(normalize_timestamp(timestamp).unwrap(), timestamp);
+    }
+
+    #[tokio::test]
+    async fn every_write_path_stores_the_same_format() {
+        let db = manager().await;
+        let created = db.save_generic("org_configs", "site-a", "v1").await.unwrap();
+        assert_stored_form(&created["created_at"]);
+        let (_, updated) = db.upsert_generic("org_configs", "site-a", "v2").await.unwrap();
+        assert_stored_form(&updated["updated_at"]);
+
+        let config = StreamConfig {
+            id: None,
+            config_name: "cam-1".to_string(),
+            config_content: "{}".to_string(),
+            created_at: now_timestamp(),
+            updated_at: None,
+        };
+        let id = db.save_stream_config(&config).await.unwrap();
+        assert!(db.update_stream_config(&id, &config).await.unwrap());
+        let stored = db.get_stream_configs().await.unwrap();
+        assert_stored_form(&stored[0].created_at.clone().into());
+        assert_stored_form(&stored[0].updated_at.clone().unwrap().into());
+
+        let rejected = db.update_generic_field("org_configs", 1, "created_at", "yesterday");
+        assert!(rejected.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn safety_record_pages_compose_with_severity() {
+        let db = manager().await;
+        for i in 0..5 {
+            let severity = if i % 2 == 0 { Severity::High } else { Severity::Low };
+            let timestamp = format!("2024-01-0{}T00:00:00.000000+00:00", i + 1);
+            db.insert_safety_record(&safety_record(severity, &timestamp))
+                .await
+                .unwrap();
+        }
+
+        let page = db.get_safety_records_paginated(2, 2, None).await.unwrap();
+        assert_eq!(page["pagination"]["total"], 5);
+        assert_eq!(page["pagination"]["total_pages"], 3);
+        assert_eq!(page["pagination"]["has_next"], true);
+        assert_eq!(page["pagination"]["has_prev"], true);
+        assert_eq!(page["data"][0]["timestamp"], "2024-01-03T00:00:00.000000+00:00");
+
+        let high = db
+            .get_safety_records_paginated(2, 2, Some(Severity::High))
+            .await
+            .unwrap();
+        assert_eq!(high["pagination"]["total"], 3);
+        assert_eq!(high["pagination"]["total_pages"], 2);
+        assert_eq!(high["pagination"]["has_next"], false);
+        assert_eq!(high["data"].as_array().unwrap().len(), 1);
+        assert_eq!(high["data"][0]["timestamp"], "2024-01-01T00:00:00.000000+00:00");
+    }
+
+    async fn table_page(db: &DatabaseManager, page: i32, limit: i32) -> serde_json::Value {
+        db.paginate_table::<serde_json::Value>(
+            "stream_configs",
+            page,
+            limit,
+            None,
+            serde_json::Map::new(),
+            Some(("config_name", "asc")),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn empty_tables_have_no_pages() {
+        let db = manager().await;
+        let page = table_page(&db, 1, 10).await;
+        assert_eq!(page["data"].as_array().unwrap().len(), 0);
+        assert_eq!(page["pagination"]["total"], 0);
+        assert_eq!(page["pagination"]["total_pages"], 0);
+        assert_eq!(page["pagination"]["has_next"], false);
+        assert_eq!(page["pagination"]["has_prev"], false);
+    }
+
+    #[tokio::test]
+    async fn pages_end_exactly_on_the_limit() {
+        let db = manager().await;
+        for i in 0..4 {
+            db.save_generic("stream_configs", &format!("cam-{}", i), "{}")
+                .await
+                .unwrap();
+        }
+
+        let last = table_page(&db, 2, 2).await;
+        assert_eq!(last["pagination"]["total_pages"], 2);
+        assert_eq!(last["pagination"]["has_next"], false);
+        assert_eq!(last["data"][0]["config_name"], "cam-2");
+        assert_eq!(last["data"][1]["config_name"], "cam-3");
+
+        let past_the_end = table_page(&db, 3, 2).await;
+        assert_eq!(past_the_end["data"].as_array().unwrap().len(), 0);
+        assert_eq!(past_the_end["pagination"]["total"], 4);
+
+        let clamped = table_page(&db, 0, 0).await;
+        assert_eq!(clamped["pagination"]["page"], 1);
+        assert_eq!(clamped["pagination"]["limit"], 1);
+        assert_eq!(clamped["pagination"]["total_pages"], 4);
+    }
+
+    async fn query_fixture() -> DatabaseManager {
+        let db = manager().await;
+        let records = [
+            (
+                "inspection",
+                Severity::High,
+                "2024-01-01T00:00:00Z",
+                "Forklift at dock 3",
+            ),
+            ("incident", Severity::High, "2024-01-02T00:00:00Z", "Spill at dock 3"),
+            ("incident", Severity::Low, "2024-01-03T00:00:00Z", "Forklift near miss"),
+            ("incident", Severity::High, "2024-01-04T00:00:00Z", "forklift collision"),
+        ];
+        for (record_type, severity, timestamp, note) in records {
+            let mut record = safety_record(severity, &normalize_timestamp(timestamp).unwrap());
+            record.record_type = record_type.to_string();
+            record.data = serde_json::json!({ "note": note });
+            db.insert_safety_record(&record).await.unwrap();
+        }
+        db
+    }
+
+    fn notes(page: &serde_json::Value) -> Vec<&str> {
+        page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["data"]["note"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn queries_combine_two_filters() {
+        let db = query_fixture().await;
+        let query = SafetyRecordQuery {
+            record_type: Some("incident".to_string()),
+            severity: Some(Severity::High),
+            ..Default::default()
+        };
+        let page = db.query_safety_records(&query).await.unwrap();
+        assert_eq!(notes(&page), ["forklift collision", "Spill at dock 3"]);
+        assert_eq!(page["pagination"]["total"], 2);
+    }
+
+    #[tokio::test]
+    async fn queries_combine_three_filters() {
+        let db = query_fixture().await;
+        let query = SafetyRecordQuery {
+            severity: Some(Severity::High),
+            start: Some("2024-01-01T00:00:00Z".to_string()),
+            end: Some("2024-01-03T00:00:00Z".to_string()),
+            data_contains: Some("DOCK".to_string()),
+            sort_order: Some("asc".to_string()),
+            ..Default::default()
+        };
+        let page = db.query_safety_records(&query).await.unwrap();
+        assert_eq!(notes(&page), ["Forklift at dock 3", "Spill at dock 3"]);
+
+        let query = SafetyRecordQuery {
+            record_type: Some("incident".to_string()),
+            data_contains: Some("forklift".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let page = db.query_safety_records(&query).await.unwrap();
+        assert_eq!(notes(&page), ["forklift collision"]);
+        assert_eq!(page["pagination"]["total"], 2);
+        assert_eq!(page["pagination"]["has_next"], true);
+    }
+
+    #[tokio::test]
+    async fn invalid_queries_are_rejected() {
+        let db = manager().await;
+        let reversed = SafetyRecordQuery {
+            start: Some("2024-02-01T00:00:00Z".to_string()),
+            end: Some("2024-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert!(db.query_safety_records(&reversed).await.is_err());
+        let unparseable = SafetyRecordQuery {
+            start: Some("last week".to_string()),
+            ..Default::default()
+        };
+        assert!(db.query_safety_records(&unparseable).await.is_err());
+        let unsortable = SafetyRecordQuery {
+            sort_by: Some("data".to_string()),
+            ..Default::default()
+        };
+        assert!(db.query_safety_records(&unsortable).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn typed_configs_update_then_get_and_delete_then_get() {
+        let db = manager().await;
+        let mut config = StreamConfig {
+            id: None,
+            config_name: "cam-1".to_string(),
+            config_content: "v1".to_string(),
+            created_at: now_timestamp(),
+            updated_at: None,
+        };
+        let id = db.save_stream_config(&config).await.unwrap();
+
+        config.config_content = "v2".to_string();
+        assert!(db.update_stream_config(&id, &config).await.unwrap());
+        let stored = db.get_stream_configs().await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].config_content, "v2");
+        assert!(stored[0].updated_at.is_some());
+        assert!(
+            !db.update_stream_config("stream_configs:missing", &config)
+                .await
+                .unwrap()
+        );
+
+        assert!(db.delete_stream_config(&id).await.unwrap());
+        assert!(db.get_stream_configs().await.unwrap().is_empty());
+        assert!(!db.delete_stream_config(&id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn cleanup_removes_only_records_outside_the_window() {
+        let db = manager().await;
+        let month_ago = format_timestamp(chrono::Utc::now() - chrono::Duration::days(30));
+        db.insert_safety_record(&safety_record(Severity::Low, &month_ago))
+            .await
+            .unwrap();
+        db.insert_safety_record(&safety_record(Severity::Low, &month_ago))
+            .await
+            .unwrap();
+        db.insert_safety_record(&safety_record(Severity::High, &now_timestamp()))
+            .await
+            .unwrap();
+
+        assert_eq!(db.cleanup_old_records(7).await.unwrap(), 2);
+        let remaining = db.get_safety_records(None).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].severity, Severity::High.to_string());
+
+        // With no window every record created before now goes
+        assert_eq!(db.cleanup_old_records(0).await.unwrap(), 1);
+        assert!(db.get_safety_records(None).await.unwrap().is_empty());
+        assert_eq!(db.cleanup_old_records(0).await.unwrap(), 0);
+
+        assert!(db.cleanup_old_records(-1).await.is_err());
+    }
+
+    fn loaded_dataset(file_path: &str, file_id: &str, created_at: &str) -> LoadedDataset {
+        LoadedDataset {
+            id: None,
+            file_id: file_id.to_string(),
+            file_path: file_path.to_string(),
+            headers: vec!["a".to_string(), "b".to_string()],
+            metadata: serde_json::json!({"row_count": 2}),
+            created_at: created_at.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reloading_a_dataset_updates_its_history_entry() {
+        let db = manager().await;
+        let first = db
+            .save_loaded_dataset(&loaded_dataset("/data/a.csv", "f1", "2024-01-01T00:00:00.000000+00:00"))
+            .await
+            .unwrap();
+        db.save_loaded_dataset(&loaded_dataset("/data/b.csv", "f2", "2024-01-02T00:00:00.000000+00:00"))
+            .await
+            .unwrap();
+        let again = db
+            .save_loaded_dataset(&loaded_dataset("/data/a.csv", "f3", "2024-01-03T00:00:00.000000+00:00"))
+            .await
+            .unwrap();
+        assert_eq!(again, first);
+
+        let recent = db.get_recent_loaded_datasets(10).await.unwrap();
+        let paths: Vec<&str> = recent.iter().map(|d| d.file_path.as_str()).collect();
+        assert_eq!(paths, vec!["/data/a.csv", "/data/b.csv"]);
+        assert_eq!(recent[0].file_id, "f3");
+        assert_eq!(db.get_recent_loaded_datasets(1).await.unwrap().len(), 1);
+
+        assert!(db.delete_loaded_dataset(&first).await.unwrap());
+        let recent = db.get_recent_loaded_datasets(10).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].file_path, "/data/b.csv");
+        assert!(!db.delete_loaded_dataset(&first).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn saving_collapses_older_duplicate_entries() {
+        let db = manager().await;
+        // Rows as earlier versions wrote them, one per load
+        for file_id in ["f1", "f2"] {
+            let _: Option<LoadedDataset> = db
+                .db
+                .create("loaded_datasets")
+                .content(loaded_dataset(
+                    "/data/a.csv",
+                    file_id,
+                    "2024-01-01T00:00:00.000000+00:00",
+                ))
+                .await
+                .unwrap();
+        }
+
+        db.save_loaded_dataset(&loaded_dataset("/data/a.csv", "f3", "2024-01-02T00:00:00.000000+00:00"))
+            .await
+            .unwrap();
+        let recent = db.get_recent_loaded_datasets(10).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].file_id, "f3");
     }
 }
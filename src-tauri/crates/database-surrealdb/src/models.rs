@@ -7,10 +7,71 @@ pub struct SafetyRecord {
     pub record_type: String,
     pub timestamp: String,
     pub data: serde_json::Value,
-    pub severity: String,
+    pub severity: String, // Canonical lowercase name of a `Severity`
     pub created_at: String,
 }
 
+/// Severity of a safety record, stored as its lowercase name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 4] = [Severity::Low, Severity::Medium, Severity::High, Severity::Critical];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Case-insensitive, ignoring surrounding whitespace.
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Severity::ALL
+            .into_iter()
+            .find(|severity| severity.as_str().eq_ignore_ascii_case(value.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown severity '{}': expected one of low, medium, high, critical",
+                    value
+                )
+            })
+    }
+}
+
+/// Filters for `query_safety_records`; every field is optional and the ones set must all match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafetyRecordQuery {
+    pub record_type: Option<String>,
+    pub severity: Option<Severity>,
+    pub start: Option<String>,         // Inclusive RFC3339 lower bound on `timestamp`
+    pub end: Option<String>,           // Inclusive RFC3339 upper bound on `timestamp`
+    pub data_contains: Option<String>, // Case-insensitive substring of the serialized `data`
+    pub page: Option<i32>,             // 1-based, default 1
+    pub limit: Option<i32>,            // Records per page, default 50
+    pub sort_by: Option<String>,       // timestamp (default), created_at, severity, or record_type
+    pub sort_order: Option<String>,    // "DESC" (default) or "ASC"
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Configuration {
     pub id: Option<Thing>,
@@ -25,6 +86,9 @@ pub struct StreamConfig {
     pub config_name: String,
     pub config_content: String,
     pub created_at: String,
+    /// Set whenever the record is updated; absent until its first update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +97,9 @@ pub struct OggConfig {
     pub config_name: String,
     pub config_content: String,
     pub created_at: String,
+    /// Set whenever the record is updated; absent until its first update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +108,9 @@ pub struct OrgConfig {
     pub config_name: String,
     pub config_content: String,
     pub created_at: String,
+    /// Set whenever the record is updated; absent until its first update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +119,9 @@ pub struct OrsgConfig {
     pub config_name: String,
     pub config_content: String,
     pub created_at: String,
+    /// Set whenever the record is updated; absent until its first update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +130,9 @@ pub struct EventConfig {
     pub config_name: String,
     pub config_content: String,
     pub created_at: String,
+    /// Set whenever the record is updated; absent until its first update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,4 +141,48 @@ pub struct ConfigRecipe {
     pub recipe_name: String,
     pub recipe_content: String,
     pub created_at: String,
+    /// Set whenever the record is updated; absent until its first update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// Lightweight record of a CSV file loaded into the in-memory data store.
+///
+/// Only metadata is persisted; the rows stay in memory and are re-read from
+/// `file_path` when the dataset is reopened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedDataset {
+    pub id: Option<Thing>,
+    pub file_id: String,
+    pub file_path: String,
+    pub headers: Vec<String>,
+    pub metadata: serde_json::Value,
+    pub created_at: String,
+}
+
+/// Outcome of restoring one table from a backup archive.
+///
+/// A table whose restore failed keeps its previous contents and reports the reason in `error`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableRestore {
+    pub table: String,
+    pub restored: u64,
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severities_parse_case_insensitively() {
+        assert_eq!("high".parse::<Severity>().unwrap(), Severity::High);
+        assert_eq!(" Critical ".parse::<Severity>().unwrap(), Severity::Critical);
+        assert_eq!("LOW".parse::<Severity>().unwrap().to_string(), "low");
+        assert_eq!(serde_json::to_value(Severity::Medium).unwrap(), "medium");
+        assert_eq!(
+            "hgih".parse::<Severity>().unwrap_err(),
+            "Unknown severity 'hgih': expected one of low, medium, high, critical"
+        );
+    }
 }
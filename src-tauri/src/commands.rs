@@ -20,10 +20,16 @@ pub fn run() {
 
     builder
         .setup(|_app| {
-            // Initialise SurrealDB when the app starts
+            // Dragged files are normally removed by the frontend; sweep up any it missed, e.g. after a crash
+            match data_science::cleanup_stale_dragged_files(data_science::STALE_DRAGGED_FILE_AGE) {
+                Ok(0) => {}
+                Ok(removed) => println!("Removed {} stale dragged files", removed),
+                Err(e) => eprintln!("⚠️ Failed to clean up dragged files: {}", e),
+            }
+            // Initialise SurrealDB when the app starts: a remote server if SURREALDB_URL is set, else the local file
             tauri::async_runtime::block_on(async {
                 // Use the external crate 'database_surrealdb'
-                match database_surrealdb::initialize("surrealdb_database.db").await {
+                match database_surrealdb::initialize_from_env().await {
                     Ok(_) => match database_surrealdb::verify_connection().await {
                         Ok(_) => {}
                         Err(e) => eprintln!("❌ Database verification failed: {}", e),
@@ -35,30 +41,55 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             // SurrealDB Commands
+            database_commands::connect_remote_database,
+            database_commands::connect_local_database,
             database_commands::create_safety_record,
+            database_commands::create_safety_records_batch,
             database_commands::get_safety_records,
             database_commands::get_safety_records_by_severity,
+            database_commands::get_safety_records_paginated,
+            database_commands::query_safety_records,
+            database_commands::get_safety_records_between,
+            database_commands::search_safety_records,
             database_commands::get_database_stats,
             database_commands::reset_database,
+            database_commands::query_readonly,
             database_commands::save_configuration,
             database_commands::get_configuration,
             database_commands::cleanup_old_records,
+            database_commands::backup_database,
+            database_commands::restore_database,
+            database_commands::list_recent_datasets,
+            database_commands::delete_loaded_dataset,
             // New 5+1 Table Schema Commands
             database_commands::save_stream_config,
             database_commands::get_stream_configs,
+            database_commands::update_stream_config,
+            database_commands::delete_stream_config,
             database_commands::save_ogg_config,
             database_commands::get_ogg_configs,
+            database_commands::update_ogg_config,
+            database_commands::delete_ogg_config,
             database_commands::save_org_config,
             database_commands::get_org_configs,
+            database_commands::update_org_config,
+            database_commands::delete_org_config,
             database_commands::save_orsg_config,
             database_commands::get_orsg_configs,
+            database_commands::update_orsg_config,
+            database_commands::delete_orsg_config,
             database_commands::save_event_config,
             database_commands::get_event_configs,
+            database_commands::update_event_config,
+            database_commands::delete_event_config,
             database_commands::save_config_recipe,
             database_commands::get_config_recipes,
+            database_commands::update_config_recipe,
+            database_commands::delete_config_recipe,
             // Generic CRUD Commands
             database_commands::get_config,
             database_commands::save_config,
+            database_commands::upsert_config,
             database_commands::update_config,
             database_commands::delete_config,
             database_commands::search_configs,
@@ -66,17 +97,59 @@ pub fn run() {
             database_commands::get_configs_paginated,
             // CSV Data Analysis Commands
             data_science::read_csv_file,
+            data_science::read_csv_range,
             data_science::validate_csv_file,
             data_science::scan_directory_for_csvs,
             data_science::analyze_csv_columns,
+            data_science::analyze_single_column_file,
+            data_science::describe_csv,
+            data_science::profile_csv_streaming,
+            data_science::detect_key_columns,
+            data_science::infer_schema,
+            data_science::validate_against_schema,
             data_science::validate_data_quality,
             data_science::export_to_csv,
+            data_science::export_to_json,
+            data_science::export_dataset_to_csv,
+            data_science::export_dataset_to_json,
+            data_science::preview_export,
+            data_science::export_data_dictionary,
+            data_science::export_to_parquet,
+            data_science::export_dataset_to_parquet,
+            data_science::export_to_sqlite,
+            data_science::import_from_sqlite,
+            data_science::load_xlsx_into_store,
             data_science::perform_data_science,
+            data_science::analyze_loaded_data,
+            data_science::cancel_analysis,
+            data_science::get_null_tokens,
+            data_science::set_null_tokens,
+            data_science::get_default_sample_seed,
+            data_science::set_default_sample_seed,
+            data_science::get_numeric_inference_options,
+            data_science::set_numeric_inference_options,
             data_science::open_file_location,
             // Store-backed CSV commands
             data_science::load_csv_into_store,
+            data_science::cancel_csv_load,
+            data_science::load_fixed_width_into_store,
+            data_science::load_and_merge_csvs,
+            data_science::join_datasets,
             data_science::query_csv_data,
+            data_science::count_csv_data,
+            data_science::analyze_single_column,
+            data_science::aggregate_csv_data,
+            data_science::add_computed_column,
+            data_science::append_rows_to_store,
+            data_science::trim_store_columns,
+            data_science::deduplicate_store,
+            data_science::rename_columns,
+            data_science::get_column_distinct_values,
+            data_science::get_column_value_counts,
+            data_science::list_loaded_csvs,
+            data_science::unload_all_csv_data,
             data_science::get_csv_headers,
+            data_science::reload_csv_in_store,
             data_science::unload_csv_data,
             // Drag-and-drop CSV commands
             data_science::process_dragged_csv,
@@ -1,33 +1,152 @@
 // Allow dead code for Tauri command functions that are registered but not directly called
 #[allow(dead_code)]
 // Import data analysis library types
-use data_science::{CSVLoadResult, DataPage, DataQuery};
+use data_science::{CSVLoadOptions, CSVLoadResult, DataPage, DataQuery, GroupByAgg};
+use tauri::Emitter;
+
+#[cfg(not(feature = "parquet"))]
+const PARQUET_DISABLED: &str = "Parquet export isn't available in this build (enable the `parquet` feature)";
+#[cfg(not(feature = "sqlite"))]
+const SQLITE_DISABLED: &str = "SQLite support isn't available in this build (enable the `sqlite` feature)";
+#[cfg(not(feature = "xlsx"))]
+const XLSX_DISABLED: &str = "Excel support isn't available in this build (enable the `xlsx` feature)";
 
 // CSV File Operations
 
 #[tauri::command]
-pub async fn read_csv_file(file_path: String) -> Result<data_science::CSVContent, String> {
-    data_science::read_csv_file(file_path)
+pub async fn read_csv_file(
+    file_path: String,
+    options: Option<data_science::CsvParseOptions>,
+) -> Result<data_science::CSVContent, String> {
+    data_science::read_csv_file(file_path, &options.unwrap_or_default()).map_err(String::from)
 }
 
 #[tauri::command]
-pub async fn validate_csv_file(file_path: String) -> Result<data_science::CsvValidationResult, String> {
-    data_science::validate_csv_file(file_path)
+pub async fn read_csv_range(
+    file_path: String,
+    start_byte: u64,
+    max_rows: usize,
+    options: Option<data_science::CsvParseOptions>,
+) -> Result<data_science::CsvRange, String> {
+    data_science::read_csv_range(&file_path, start_byte, max_rows, &options.unwrap_or_default()).map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn validate_csv_file(
+    file_path: String,
+    options: Option<data_science::CsvParseOptions>,
+) -> Result<data_science::CsvValidationResult, String> {
+    data_science::validate_csv_file(file_path, &options.unwrap_or_default()).map_err(String::from)
 }
 
 #[tauri::command]
 pub async fn scan_directory_for_csvs(dir_path: String) -> Result<Vec<data_science::CsvFileInfo>, String> {
-    data_science::scan_directory_for_csvs(dir_path)
+    data_science::scan_directory_for_csvs(dir_path).map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn analyze_csv_columns(
+    file_path: String,
+    options: Option<data_science::CsvParseOptions>,
+    sample_size: Option<usize>,
+    distinct_limit: Option<usize>,
+) -> Result<Vec<data_science::ColumnAnalysis>, String> {
+    data_science::analyze_csv_columns(
+        file_path,
+        &options.unwrap_or_default(),
+        sample_size.unwrap_or(data_science::DEFAULT_SAMPLE_SIZE),
+        distinct_limit,
+    )
+    .map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn analyze_single_column_file(
+    file_path: String,
+    column: String,
+    options: Option<data_science::CsvParseOptions>,
+) -> Result<data_science::ColumnDetail, String> {
+    data_science::analyze_single_column_file(&file_path, &column, &options.unwrap_or_default()).map_err(String::from)
 }
 
 #[tauri::command]
-pub async fn analyze_csv_columns(file_path: String) -> Result<Vec<data_science::ColumnAnalysis>, String> {
-    data_science::analyze_csv_columns(file_path)
+pub async fn describe_csv(
+    file_path: String,
+    options: Option<data_science::CsvParseOptions>,
+) -> Result<serde_json::Value, String> {
+    data_science::describe_csv(&file_path, &options.unwrap_or_default()).map_err(String::from)
 }
 
 #[tauri::command]
-pub async fn validate_data_quality(file_path: String) -> Result<data_science::DataQualityReport, String> {
-    data_science::validate_data_quality(file_path)
+pub async fn profile_csv_streaming(
+    file_path: String,
+    options: Option<data_science::CsvParseOptions>,
+    seed: Option<u64>,
+    task_id: Option<String>,
+) -> Result<data_science::StreamingProfile, String> {
+    // Only profiles started with a task_id can be cancelled (through cancel_analysis)
+    let cancel = match &task_id {
+        Some(task_id) => data_science::register_task(task_id),
+        None => Default::default(),
+    };
+    let result = data_science::profile_csv_streaming(file_path, &options.unwrap_or_default(), seed, &cancel);
+    if let Some(task_id) = &task_id {
+        data_science::finish_task(task_id);
+    }
+    result.map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn detect_key_columns(
+    file_path: String,
+    options: Option<data_science::CsvParseOptions>,
+) -> Result<Vec<Vec<String>>, String> {
+    data_science::detect_key_columns(file_path, &options.unwrap_or_default()).map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn infer_schema(
+    file_path: String,
+    options: Option<data_science::CsvParseOptions>,
+    output_path: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let schema = data_science::infer_schema(file_path, &options.unwrap_or_default())?;
+    if let Some(output_path) = output_path {
+        data_science::write_schema_file(&schema, &output_path)?;
+    }
+    Ok(schema)
+}
+
+#[tauri::command]
+pub async fn validate_against_schema(
+    file_path: String,
+    schema: serde_json::Value,
+    options: Option<data_science::CsvParseOptions>,
+    max_violations: Option<usize>,
+) -> Result<data_science::SchemaValidationReport, String> {
+    data_science::validate_against_schema(
+        file_path,
+        &options.unwrap_or_default(),
+        &schema,
+        max_violations.unwrap_or(100),
+    )
+    .map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn validate_data_quality(
+    file_path: String,
+    options: Option<data_science::CsvParseOptions>,
+    key_columns: Option<Vec<String>>,
+    weights: Option<data_science::QualityWeights>,
+) -> Result<data_science::DataQualityReport, String> {
+    data_science::validate_data_quality(
+        file_path,
+        &options.unwrap_or_default(),
+        &key_columns.unwrap_or_default(),
+        &weights.unwrap_or_default(),
+    )
+    .map_err(String::from)
 }
 
 #[tauri::command]
@@ -35,56 +154,565 @@ pub async fn export_to_csv(
     data: Vec<serde_json::Value>,
     file_path: String,
     options: data_science::ExportOptions,
+) -> Result<String, String> {
+    data_science::export_to_csv(data, file_path, options).map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn export_to_json(
+    data: Vec<serde_json::Value>,
+    file_path: String,
+    options: data_science::ExportOptions,
+) -> Result<String, String> {
+    data_science::export_to_json(data, file_path, options).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn export_dataset_to_csv(
+    file_id: String,
+    file_path: String,
+    options: data_science::ExportOptions,
+) -> Result<String, String> {
+    data_science::export_dataset_to_csv(&file_id, &file_path, &options).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn export_dataset_to_json(
+    file_id: String,
+    file_path: String,
+    options: data_science::ExportOptions,
+) -> Result<String, String> {
+    data_science::export_dataset_to_json(&file_id, &file_path, &options).map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn preview_export(
+    data: Vec<serde_json::Value>,
+    options: data_science::ExportOptions,
+    max_lines: Option<usize>,
+) -> Result<String, String> {
+    data_science::preview_export(&data, &options, max_lines.unwrap_or(10)).map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn export_data_dictionary(
+    file_path: String,
+    out_path: String,
+    format: String,
+    options: Option<data_science::CsvParseOptions>,
+) -> Result<data_science::DataDictionary, String> {
+    data_science::export_data_dictionary(&file_path, &out_path, &format, &options.unwrap_or_default())
+        .map_err(String::from)
+}
+
+#[cfg(feature = "parquet")]
+#[tauri::command]
+pub async fn export_to_parquet(
+    data: Vec<serde_json::Value>,
+    file_path: String,
+    schema_hint: Option<std::collections::HashMap<String, String>>,
+) -> Result<(), String> {
+    data_science::export_to_parquet(data, file_path, schema_hint).map_err(String::from)
+}
+
+#[cfg(not(feature = "parquet"))]
+#[tauri::command]
+#[allow(unused_variables)]
+pub async fn export_to_parquet(
+    data: Vec<serde_json::Value>,
+    file_path: String,
+    schema_hint: Option<std::collections::HashMap<String, String>>,
 ) -> Result<(), String> {
-    data_science::export_to_csv(data, file_path, options)
+    Err(PARQUET_DISABLED.to_string())
+}
+
+#[cfg(feature = "parquet")]
+#[tauri::command]
+pub fn export_dataset_to_parquet(file_id: String, file_path: String) -> Result<(), String> {
+    data_science::export_dataset_to_parquet(&file_id, &file_path).map_err(String::from)
+}
+
+#[cfg(not(feature = "parquet"))]
+#[tauri::command]
+#[allow(unused_variables)]
+pub fn export_dataset_to_parquet(file_id: String, file_path: String) -> Result<(), String> {
+    Err(PARQUET_DISABLED.to_string())
+}
+
+#[cfg(feature = "sqlite")]
+#[tauri::command]
+pub fn export_to_sqlite(file_id: String, db_path: String, table_name: String) -> Result<usize, String> {
+    data_science::export_to_sqlite(&file_id, &db_path, &table_name).map_err(String::from)
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[tauri::command]
+#[allow(unused_variables)]
+pub fn export_to_sqlite(file_id: String, db_path: String, table_name: String) -> Result<usize, String> {
+    Err(SQLITE_DISABLED.to_string())
+}
+
+#[cfg(feature = "sqlite")]
+#[tauri::command]
+pub async fn import_from_sqlite(db_path: String, table_name: String) -> Result<CSVLoadResult, String> {
+    let options = CSVLoadOptions {
+        sqlite_table: Some(table_name.clone()),
+        ..Default::default()
+    };
+    let path = db_path.clone();
+    let result = load_blocking(move || data_science::import_from_sqlite(&path, &table_name)).await?;
+    record_load(&result, db_path, &options).await;
+    Ok(result)
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[tauri::command]
+#[allow(unused_variables)]
+pub async fn import_from_sqlite(db_path: String, table_name: String) -> Result<CSVLoadResult, String> {
+    Err(SQLITE_DISABLED.to_string())
+}
+
+#[cfg(feature = "xlsx")]
+#[tauri::command]
+pub async fn load_xlsx_into_store(file_path: String, sheet: Option<String>) -> Result<CSVLoadResult, String> {
+    let options = CSVLoadOptions {
+        xlsx: Some(data_science::XlsxSpec { sheet: sheet.clone() }),
+        ..Default::default()
+    };
+    let path = file_path.clone();
+    let result = load_blocking(move || data_science::load_xlsx_into_store(&path, sheet)).await?;
+    record_load(&result, file_path, &options).await;
+    Ok(result)
+}
+
+#[cfg(not(feature = "xlsx"))]
+#[tauri::command]
+#[allow(unused_variables)]
+pub async fn load_xlsx_into_store(file_path: String, sheet: Option<String>) -> Result<CSVLoadResult, String> {
+    Err(XLSX_DISABLED.to_string())
 }
 
 #[tauri::command]
 pub async fn perform_data_science(
+    window: tauri::Window,
     file_path: String,
     analysis_type: String,
+    options: Option<data_science::AnalysisOptions>,
+    task_id: Option<String>,
 ) -> Result<data_science::AnalysisResult, String> {
-    data_science::perform_data_science(file_path, analysis_type).await
+    // Only analyses started with a task_id can be cancelled
+    let cancel = match &task_id {
+        Some(task_id) => data_science::register_task(task_id),
+        None => Default::default(),
+    };
+    // Progress is advisory; a failed emit shouldn't abort the analysis
+    let result = data_science::perform_data_science_with_progress(
+        file_path,
+        analysis_type,
+        &options.unwrap_or_default(),
+        &cancel,
+        |progress| {
+            let _ = window.emit("analysis-progress", &progress);
+        },
+    )
+    .await;
+    if let Some(task_id) = &task_id {
+        data_science::finish_task(task_id);
+    }
+    result.map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn analyze_loaded_data(
+    window: tauri::Window,
+    file_id: String,
+    analysis_type: String,
+    options: Option<data_science::AnalysisOptions>,
+    task_id: Option<String>,
+) -> Result<data_science::AnalysisResult, String> {
+    let cancel = match &task_id {
+        Some(task_id) => data_science::register_task(task_id),
+        None => Default::default(),
+    };
+    let result = data_science::analyze_loaded_data_with_progress(
+        file_id,
+        analysis_type,
+        &options.unwrap_or_default(),
+        &cancel,
+        |progress| {
+            let _ = window.emit("analysis-progress", &progress);
+        },
+    )
+    .await;
+    if let Some(task_id) = &task_id {
+        data_science::finish_task(task_id);
+    }
+    result.map_err(String::from)
+}
+
+#[tauri::command]
+pub fn cancel_analysis(task_id: String) -> bool {
+    data_science::cancel_task(&task_id)
+}
+
+#[tauri::command]
+pub fn get_null_tokens() -> Vec<String> {
+    data_science::get_null_tokens()
+}
+
+#[tauri::command]
+pub fn set_null_tokens(tokens: Vec<String>) -> Result<(), String> {
+    data_science::set_null_tokens(tokens).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn get_default_sample_seed() -> u64 {
+    data_science::get_default_sample_seed()
+}
+
+#[tauri::command]
+pub fn set_default_sample_seed(seed: u64) {
+    data_science::set_default_sample_seed(seed)
+}
+
+#[tauri::command]
+pub fn get_numeric_inference_options() -> data_science::NumericInferenceOptions {
+    data_science::get_numeric_inference_options()
+}
+
+#[tauri::command]
+pub fn set_numeric_inference_options(options: data_science::NumericInferenceOptions) -> Result<(), String> {
+    data_science::set_numeric_inference_options(options).map_err(String::from)
 }
 
 #[tauri::command]
 pub async fn open_file_location(file_path: String) -> Result<(), String> {
-    // For now, just return success. In a real implementation,
-    // you might use Tauri's shell API to open the file location
-    println!("Opening file location: {}", file_path);
+    let path = std::path::Path::new(&file_path);
+    if !path.is_absolute() {
+        return Err(format!("Can't show '{}': the path must be absolute", file_path));
+    }
+    if !path.exists() {
+        return Err(format!("Can't show '{}': the file doesn't exist", file_path));
+    }
+    reveal_in_file_manager(path).map_err(|e| format!("Failed to open the location of '{}': {}", file_path, e))
+}
+
+/// Show `path` in the platform file manager without waiting for it to open.
+///
+/// Explorer and Finder open the containing folder with the file selected. Linux has no
+/// common way to select a file, so `xdg-open` just opens the containing folder.
+fn reveal_in_file_manager(path: &std::path::Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+        // Explorer only understands the path when it is quoted after the comma
+        let mut command = std::process::Command::new("explorer");
+        command.raw_arg(format!("/select,\"{}\"", path.display()));
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = std::process::Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = {
+        let folder = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(folder);
+        command
+    };
+    let mut child = command.spawn()?;
+    // Reap the launcher in the background so it doesn't linger as a zombie process
+    std::thread::spawn(move || child.wait());
     Ok(())
 }
 
 // Backend-Centric CSV Data Management Commands
 
 #[tauri::command]
-pub fn load_csv_into_store(file_path: String) -> Result<CSVLoadResult, String> {
-    data_science::load_csv_into_store(&file_path)
+pub async fn load_csv_into_store(
+    window: tauri::Window,
+    file_path: String,
+    options: Option<CSVLoadOptions>,
+    task_id: Option<String>,
+) -> Result<CSVLoadResult, String> {
+    let options = options.unwrap_or_default();
+    let (path, load_options) = (file_path.clone(), options.clone());
+    let result = load_blocking(move || {
+        // Progress is advisory; a failed emit shouldn't abort the load
+        data_science::load_csv_into_store_with_progress(&path, &load_options, task_id.as_deref(), |progress| {
+            let _ = window.emit("csv-load-progress", &progress);
+        })
+    })
+    .await?;
+    record_load(&result, file_path, &options).await;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn load_fixed_width_into_store(
+    file_path: String,
+    field_widths: Vec<usize>,
+    headers: Option<Vec<String>>,
+) -> Result<CSVLoadResult, String> {
+    let options = CSVLoadOptions {
+        fixed_width: Some(data_science::FixedWidthSpec {
+            field_widths: field_widths.clone(),
+            headers: headers.clone(),
+        }),
+        ..Default::default()
+    };
+    let path = file_path.clone();
+    let result = load_blocking(move || data_science::load_fixed_width_into_store(&path, field_widths, headers)).await?;
+    record_load(&result, file_path, &options).await;
+    Ok(result)
+}
+
+// Loads are synchronous, so run them off the async runtime's worker threads
+async fn load_blocking(
+    load: impl FnOnce() -> Result<CSVLoadResult, data_science::DataScienceError> + Send + 'static,
+) -> Result<CSVLoadResult, String> {
+    tauri::async_runtime::spawn_blocking(load)
+        .await
+        .map_err(|e| format!("Load task failed: {}", e))?
+        .map_err(String::from)
+}
+
+// Remember a successful load for "recently opened", with the options that re-open it under `load_options`;
+// history is best-effort and never fails the load
+async fn record_load(result: &CSVLoadResult, file_path: String, options: &CSVLoadOptions) {
+    if !result.success {
+        return;
+    }
+    let mut metadata = serde_json::to_value(&result.metadata).unwrap_or_default();
+    if let Some(fields) = metadata.as_object_mut() {
+        fields.insert(
+            "load_options".to_string(),
+            serde_json::to_value(options).unwrap_or_default(),
+        );
+    }
+    if let Err(e) =
+        database_surrealdb::record_loaded_dataset(result.file_id.clone(), file_path, result.headers.clone(), metadata)
+            .await
+    {
+        eprintln!("⚠️ {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn load_and_merge_csvs(
+    file_paths: Vec<String>,
+    options: Option<CSVLoadOptions>,
+    union_columns: Option<bool>,
+) -> Result<CSVLoadResult, String> {
+    data_science::load_and_merge_csvs(
+        &file_paths,
+        &options.unwrap_or_default(),
+        union_columns.unwrap_or(false),
+    )
+    .map_err(String::from)
+}
+
+#[tauri::command]
+pub fn join_datasets(
+    left_file_id: String,
+    right_file_id: String,
+    left_key: String,
+    right_key: String,
+    join_type: Option<String>,
+) -> Result<CSVLoadResult, String> {
+    data_science::join_datasets(
+        &left_file_id,
+        &right_file_id,
+        &left_key,
+        &right_key,
+        join_type.as_deref().unwrap_or("inner"),
+    )
+    .map_err(String::from)
+}
+
+#[tauri::command]
+pub fn cancel_csv_load(task_id: String) -> bool {
+    data_science::cancel_task(&task_id)
 }
 
 #[tauri::command]
 pub fn query_csv_data(query: DataQuery) -> Result<DataPage, String> {
-    data_science::query_csv_data(&query)
+    data_science::query_csv_data(&query).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn count_csv_data(
+    file_id: String,
+    filters: std::collections::HashMap<String, data_science::FilterSpec>,
+) -> Result<usize, String> {
+    data_science::count_csv_data(&file_id, &filters).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn analyze_single_column(file_id: String, column: String) -> Result<data_science::ColumnDetail, String> {
+    data_science::analyze_single_column(&file_id, &column).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn aggregate_csv_data(
+    file_id: String,
+    spec: GroupByAgg,
+) -> Result<Vec<std::collections::HashMap<String, serde_json::Value>>, String> {
+    data_science::aggregate_csv_data(&file_id, &spec).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn add_computed_column(
+    file_id: String,
+    new_column: String,
+    expression: String,
+) -> Result<data_science::DataMetadata, String> {
+    data_science::add_computed_column(&file_id, &new_column, &expression).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn trim_store_columns(file_id: String, columns: Option<Vec<String>>) -> Result<usize, String> {
+    data_science::trim_store_columns(&file_id, columns).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn deduplicate_store(
+    file_id: String,
+    key_columns: Option<Vec<String>>,
+    keep: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<usize, String> {
+    data_science::deduplicate_store(
+        &file_id,
+        key_columns,
+        keep.as_deref().unwrap_or("first"),
+        dry_run.unwrap_or(false),
+    )
+    .map_err(String::from)
+}
+
+#[tauri::command]
+pub fn rename_columns(
+    file_id: String,
+    mapping: std::collections::HashMap<String, String>,
+) -> Result<Vec<String>, String> {
+    data_science::rename_columns(&file_id, mapping).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn append_rows_to_store(file_id: String, rows: Vec<Vec<String>>) -> Result<usize, String> {
+    data_science::append_rows_to_store(&file_id, rows).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn get_column_distinct_values(
+    file_id: String,
+    column: String,
+    limit: Option<usize>,
+    use_filters: Option<bool>,
+) -> Result<Vec<serde_json::Value>, String> {
+    data_science::get_column_distinct_values(&file_id, &column, limit, use_filters.unwrap_or(false))
+        .map_err(String::from)
+}
+
+#[tauri::command]
+pub fn get_column_value_counts(
+    file_id: String,
+    column: String,
+    limit: Option<usize>,
+    use_filters: Option<bool>,
+) -> Result<Vec<data_science::ValueCount>, String> {
+    data_science::get_column_value_counts(&file_id, &column, limit, use_filters.unwrap_or(false)).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn list_loaded_csvs() -> Result<Vec<data_science::DatasetSummary>, String> {
+    data_science::list_loaded_csvs().map_err(String::from)
+}
+
+#[tauri::command]
+pub fn unload_all_csv_data() -> Result<usize, String> {
+    data_science::unload_all_csv_data().map_err(String::from)
 }
 
 #[tauri::command]
 pub fn get_csv_headers(file_id: String) -> Result<Vec<String>, String> {
-    data_science::get_csv_headers(&file_id)
+    data_science::get_csv_headers(&file_id).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn reload_csv_in_store(file_id: String) -> Result<data_science::DataMetadata, String> {
+    data_science::reload_csv_in_store(&file_id).map_err(String::from)
 }
 
 #[tauri::command]
 pub fn unload_csv_data(file_id: String) -> Result<(), String> {
-    data_science::unload_csv_data(&file_id)
+    data_science::unload_csv_data(&file_id).map_err(String::from)
 }
 
 // Native Drag-and-Drop File Handling
 
+/// Dragged files older than this are deleted at start-up, in case the frontend never cleaned them up.
+pub const STALE_DRAGGED_FILE_AGE: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+// Native app-specific temp directory (more structured than system temp)
+fn dragged_files_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join("data-science-app").join("dragged-files")
+}
+
+/// Delete files in the dragged-file temp directory last modified more than `max_age` ago.
+///
+/// Returns how many files were removed; a missing directory means nothing to clean.
+pub fn cleanup_stale_dragged_files(max_age: std::time::Duration) -> Result<usize, String> {
+    remove_files_older_than(&dragged_files_dir(), max_age)
+}
+
+fn remove_files_older_than(dir: &std::path::Path, max_age: std::time::Duration) -> Result<usize, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    let now = std::time::SystemTime::now();
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else { continue };
+        // Files with a modification time in the future count as fresh
+        let is_stale = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > max_age);
+        if metadata.is_file() && is_stale && std::fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
 #[tauri::command]
-pub async fn process_dragged_csv(file_data: Vec<u8>, filename: String) -> Result<String, String> {
+pub async fn process_dragged_csv(
+    file_data: Vec<u8>,
+    filename: String,
+    max_bytes: Option<usize>,
+) -> Result<String, String> {
     use std::fs;
 
-    // Use native app-specific temp directory (more structured than system temp)
-    let app_temp_dir = std::env::temp_dir().join("data-science-app").join("dragged-files");
+    // Reject oversized or binary payloads and strip any directories from the name before touching disk
+    let filename = data_science::check_dropped_file(
+        &file_data,
+        &filename,
+        max_bytes.unwrap_or(data_science::DEFAULT_MAX_DROPPED_FILE_BYTES),
+    )?;
+
+    let app_temp_dir = dragged_files_dir();
 
     // Create directory if it doesn't exist
     fs::create_dir_all(&app_temp_dir).map_err(|e| format!("Failed to create app temp directory: {}", e))?;
@@ -113,7 +741,7 @@ pub async fn cleanup_dragged_file(file_path: String) -> Result<(), String> {
     use std::path::Path;
 
     // Get app-specific temp directory for safety check
-    let app_temp_dir = std::env::temp_dir().join("data-science-app").join("dragged-files");
+    let app_temp_dir = dragged_files_dir();
 
     let file_path_obj = Path::new(&file_path);
 
@@ -127,3 +755,33 @@ pub async fn cleanup_dragged_file(file_path: String) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn only_stale_dragged_files_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("1_old.csv");
+        let fresh = dir.path().join("2_fresh.csv");
+        std::fs::write(&old, "a\n1\n").unwrap();
+        std::fs::write(&fresh, "a\n1\n").unwrap();
+        let two_days_ago = SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(two_days_ago)
+            .unwrap();
+
+        assert_eq!(remove_files_older_than(dir.path(), STALE_DRAGGED_FILE_AGE), Ok(1));
+        assert!(!old.exists());
+        assert!(fresh.exists());
+        assert_eq!(
+            remove_files_older_than(&dir.path().join("missing"), STALE_DRAGGED_FILE_AGE),
+            Ok(0)
+        );
+    }
+}
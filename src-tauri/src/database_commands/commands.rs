@@ -3,6 +3,24 @@
 //! Thin wrappers over `database_surrealdb::global` free functions.
 //! No logic here — each function simply forwards its arguments to the crate.
 
+// Connection
+
+#[tauri::command]
+pub async fn connect_remote_database(
+    url: String,
+    username: String,
+    password: String,
+    namespace: String,
+    database: String,
+) -> Result<(), String> {
+    database_surrealdb::initialize_remote(&url, &username, &password, &namespace, &database).await
+}
+
+#[tauri::command]
+pub async fn connect_local_database() -> Result<(), String> {
+    database_surrealdb::initialize_local().await
+}
+
 // Safety Records
 
 #[tauri::command]
@@ -14,24 +32,80 @@ pub async fn create_safety_record(
     database_surrealdb::create_safety_record(record_type, data, severity).await
 }
 
+#[tauri::command]
+pub async fn create_safety_records_batch(records: serde_json::Value) -> Result<serde_json::Value, String> {
+    database_surrealdb::create_safety_records_batch(records).await
+}
+
 #[tauri::command]
 pub async fn get_safety_records(limit: Option<i32>) -> Result<serde_json::Value, String> {
     database_surrealdb::get_safety_records(limit).await
 }
 
+#[tauri::command]
+pub async fn get_safety_records_paginated(
+    page: i32,
+    limit: i32,
+    severity: Option<String>,
+) -> Result<serde_json::Value, String> {
+    database_surrealdb::get_safety_records_paginated(page, limit, severity).await
+}
+
+#[tauri::command]
+pub async fn query_safety_records(query: serde_json::Value) -> Result<serde_json::Value, String> {
+    database_surrealdb::query_safety_records(query).await
+}
+
 #[tauri::command]
 pub async fn get_safety_records_by_severity(severity: String) -> Result<serde_json::Value, String> {
     database_surrealdb::get_safety_records_by_severity(severity).await
 }
 
+#[tauri::command]
+pub async fn get_safety_records_between(
+    start: String,
+    end: String,
+    limit: Option<i32>,
+) -> Result<serde_json::Value, String> {
+    database_surrealdb::get_safety_records_between(start, end, limit).await
+}
+
+#[tauri::command]
+pub async fn search_safety_records(term: String, limit: Option<i32>) -> Result<serde_json::Value, String> {
+    database_surrealdb::search_safety_records(term, limit).await
+}
+
 #[tauri::command]
 pub async fn get_database_stats() -> Result<serde_json::Value, String> {
     database_surrealdb::get_database_stats().await
 }
 
+#[cfg(feature = "db-query")]
+#[tauri::command]
+pub async fn query_readonly(surql: String, params: Option<serde_json::Value>) -> Result<serde_json::Value, String> {
+    database_surrealdb::query_readonly(surql, params).await
+}
+
+#[cfg(not(feature = "db-query"))]
+#[tauri::command]
+#[allow(unused_variables)]
+pub async fn query_readonly(surql: String, params: Option<serde_json::Value>) -> Result<serde_json::Value, String> {
+    Err("Ad-hoc queries aren't available in this build (enable the `db-query` feature)".to_string())
+}
+
+#[tauri::command]
+pub async fn reset_database(confirm: bool) -> Result<String, String> {
+    database_surrealdb::reset_database(confirm).await
+}
+
+#[tauri::command]
+pub async fn backup_database(out_path: String) -> Result<serde_json::Value, String> {
+    database_surrealdb::backup_database(out_path).await
+}
+
 #[tauri::command]
-pub async fn reset_database() -> Result<String, String> {
-    database_surrealdb::reset_database().await
+pub async fn restore_database(in_path: String) -> Result<serde_json::Value, String> {
+    database_surrealdb::restore_database(in_path).await
 }
 
 #[tauri::command]
@@ -39,10 +113,22 @@ pub async fn cleanup_old_records(days: i32) -> Result<String, String> {
     database_surrealdb::cleanup_old_records(days).await
 }
 
+// Loaded Datasets
+
+#[tauri::command]
+pub async fn list_recent_datasets(limit: Option<usize>) -> Result<Vec<database_surrealdb::LoadedDataset>, String> {
+    database_surrealdb::list_recent_datasets(limit).await
+}
+
+#[tauri::command]
+pub async fn delete_loaded_dataset(id: String) -> Result<bool, String> {
+    database_surrealdb::delete_loaded_dataset(id).await
+}
+
 // Legacy Configuration
 
 #[tauri::command]
-pub async fn save_configuration(name: String, content: serde_json::Value) -> Result<String, String> {
+pub async fn save_configuration(name: String, content: serde_json::Value) -> Result<serde_json::Value, String> {
     database_surrealdb::save_configuration(name, content).await
 }
 
@@ -63,6 +149,16 @@ pub async fn get_stream_configs() -> Result<Vec<database_surrealdb::StreamConfig
     database_surrealdb::get_stream_configs().await
 }
 
+#[tauri::command]
+pub async fn update_stream_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    database_surrealdb::update_stream_config(id, config_name, config_content).await
+}
+
+#[tauri::command]
+pub async fn delete_stream_config(id: String) -> Result<bool, String> {
+    database_surrealdb::delete_stream_config(id).await
+}
+
 #[tauri::command]
 pub async fn save_ogg_config(config_name: String, config_content: String) -> Result<String, String> {
     database_surrealdb::save_ogg_config(config_name, config_content).await
@@ -73,6 +169,16 @@ pub async fn get_ogg_configs() -> Result<Vec<database_surrealdb::OggConfig>, Str
     database_surrealdb::get_ogg_configs().await
 }
 
+#[tauri::command]
+pub async fn update_ogg_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    database_surrealdb::update_ogg_config(id, config_name, config_content).await
+}
+
+#[tauri::command]
+pub async fn delete_ogg_config(id: String) -> Result<bool, String> {
+    database_surrealdb::delete_ogg_config(id).await
+}
+
 #[tauri::command]
 pub async fn save_org_config(config_name: String, config_content: String) -> Result<String, String> {
     database_surrealdb::save_org_config(config_name, config_content).await
@@ -83,6 +189,16 @@ pub async fn get_org_configs() -> Result<Vec<database_surrealdb::OrgConfig>, Str
     database_surrealdb::get_org_configs().await
 }
 
+#[tauri::command]
+pub async fn update_org_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    database_surrealdb::update_org_config(id, config_name, config_content).await
+}
+
+#[tauri::command]
+pub async fn delete_org_config(id: String) -> Result<bool, String> {
+    database_surrealdb::delete_org_config(id).await
+}
+
 #[tauri::command]
 pub async fn save_orsg_config(config_name: String, config_content: String) -> Result<String, String> {
     database_surrealdb::save_orsg_config(config_name, config_content).await
@@ -93,6 +209,16 @@ pub async fn get_orsg_configs() -> Result<Vec<database_surrealdb::OrsgConfig>, S
     database_surrealdb::get_orsg_configs().await
 }
 
+#[tauri::command]
+pub async fn update_orsg_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    database_surrealdb::update_orsg_config(id, config_name, config_content).await
+}
+
+#[tauri::command]
+pub async fn delete_orsg_config(id: String) -> Result<bool, String> {
+    database_surrealdb::delete_orsg_config(id).await
+}
+
 #[tauri::command]
 pub async fn save_event_config(config_name: String, config_content: String) -> Result<String, String> {
     database_surrealdb::save_event_config(config_name, config_content).await
@@ -103,6 +229,16 @@ pub async fn get_event_configs() -> Result<Vec<database_surrealdb::EventConfig>,
     database_surrealdb::get_event_configs().await
 }
 
+#[tauri::command]
+pub async fn update_event_config(id: String, config_name: String, config_content: String) -> Result<bool, String> {
+    database_surrealdb::update_event_config(id, config_name, config_content).await
+}
+
+#[tauri::command]
+pub async fn delete_event_config(id: String) -> Result<bool, String> {
+    database_surrealdb::delete_event_config(id).await
+}
+
 #[tauri::command]
 pub async fn save_config_recipe(
     recipe_name: String,
@@ -128,6 +264,33 @@ pub async fn get_config_recipes() -> Result<Vec<database_surrealdb::ConfigRecipe
     database_surrealdb::get_config_recipes().await
 }
 
+#[tauri::command]
+pub async fn update_config_recipe(
+    id: String,
+    recipe_name: String,
+    stream_config_id: Option<i64>,
+    ogg_config_id: Option<i64>,
+    org_config_id: Option<i64>,
+    orsg_config_id: Option<i64>,
+    event_config_id: Option<i64>,
+) -> Result<bool, String> {
+    database_surrealdb::update_config_recipe(
+        id,
+        recipe_name,
+        stream_config_id,
+        ogg_config_id,
+        org_config_id,
+        orsg_config_id,
+        event_config_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_config_recipe(id: String) -> Result<bool, String> {
+    database_surrealdb::delete_config_recipe(id).await
+}
+
 // Generic CRUD
 
 #[tauri::command]
@@ -140,6 +303,11 @@ pub async fn save_config(table: String, name: String, content: String) -> Result
     database_surrealdb::save_config(table, name, content).await
 }
 
+#[tauri::command]
+pub async fn upsert_config(table: String, name: String, content: String) -> Result<serde_json::Value, String> {
+    database_surrealdb::upsert_config(table, name, content).await
+}
+
 #[tauri::command]
 pub async fn update_config(table: String, id: i64, field: String, value: String) -> Result<serde_json::Value, String> {
     database_surrealdb::update_config(table, id, field, value).await